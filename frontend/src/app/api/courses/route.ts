@@ -0,0 +1,34 @@
+import { NextRequest, NextResponse } from 'next/server'
+import { getServerSession } from 'next-auth'
+
+export async function GET(req: NextRequest) {
+  const session = await getServerSession()
+  if (!session?.user) {
+    return NextResponse.json({ error: 'Unauthorized' }, { status: 401 })
+  }
+
+  const url = new URL(req.url)
+  const active = url.searchParams.get('active')
+
+  try {
+    const apiUrl = `${process.env.NEXT_PUBLIC_API_URL}/v1/courses?limit=500${active ? `&active=${active}` : ''}`
+    const response = await fetch(apiUrl, {
+      headers: {
+        'Authorization': `Bearer ${session.user.accessToken}`
+      }
+    })
+
+    if (!response.ok) {
+      throw new Error(`Backend API error: ${response.status}`)
+    }
+
+    const data = await response.json()
+    return NextResponse.json(data)
+  } catch (error) {
+    console.error('Error fetching courses:', error)
+    return NextResponse.json(
+      { error: 'Failed to fetch courses' },
+      { status: 500 }
+    )
+  }
+}
@@ -3,6 +3,8 @@
 import React, { useState } from 'react'
 import { useForm } from 'react-hook-form'
 import { useSession } from 'next-auth/react'
+import { useQuery } from '@tanstack/react-query'
+import { Course } from '@/types/course'
 import { NewStudent, Student } from '@/types/student'
 
 interface StudentFormProps {
@@ -15,6 +17,10 @@ export function StudentForm({ student, onClose, onSuccess }: StudentFormProps) {
   const { data: session } = useSession()
   const [loading, setLoading] = useState(false)
   const [error, setError] = useState('')
+  const { data: coursesResponse } = useQuery<{ data: Course[] }>(['courses', 'active'], () =>
+    fetch('/api/courses?active=true').then((res) => res.json())
+  )
+  const courses = coursesResponse?.data || []
   
   const { 
     register, 
@@ -25,7 +31,7 @@ export function StudentForm({ student, onClose, onSuccess }: StudentFormProps) {
       name: student.name,
       email: student.email,
       phone: student.phone,
-      course: student.course
+      course_id: student.course_id
     } : undefined
   })
 
@@ -168,24 +174,29 @@ export function StudentForm({ student, onClose, onSuccess }: StudentFormProps) {
 
           {/* Course Field */}
           <div className="space-y-2">
-            <label htmlFor="course" className="block text-sm font-medium text-gray-700">
+            <label htmlFor="course_id" className="block text-sm font-medium text-gray-700">
               Course/Program <span className="text-red-500">*</span>
             </label>
-            <input
-              id="course"
-              type="text"
-              placeholder="e.g., Computer Science, Business Administration"
-              {...register('course', { required: 'Course is required' })}
-              className={`w-full px-4 py-3 border rounded-lg text-gray-900 placeholder-gray-500 transition-colors focus:outline-none focus:ring-2 focus:ring-blue-500 focus:border-transparent ${
-                errors.course ? 'border-red-300 bg-red-50' : 'border-gray-300 bg-white hover:border-gray-400'
+            <select
+              id="course_id"
+              {...register('course_id', { required: 'Course is required', valueAsNumber: true })}
+              className={`w-full px-4 py-3 border rounded-lg text-gray-900 transition-colors focus:outline-none focus:ring-2 focus:ring-blue-500 focus:border-transparent ${
+                errors.course_id ? 'border-red-300 bg-red-50' : 'border-gray-300 bg-white hover:border-gray-400'
               }`}
-            />
-            {errors.course && (
+            >
+              <option value="">Select a course</option>
+              {courses.map((course) => (
+                <option key={course.id} value={course.id}>
+                  {course.code} - {course.title}
+                </option>
+              ))}
+            </select>
+            {errors.course_id && (
               <p className="text-sm text-red-600 flex items-center">
                 <svg className="w-4 h-4 mr-1" fill="currentColor" viewBox="0 0 20 20">
                   <path fillRule="evenodd" d="M18 10a8 8 0 11-16 0 8 8 0 0116 0zm-7 4a1 1 0 11-2 0 1 1 0 012 0zm-1-9a1 1 0 00-1 1v4a1 1 0 102 0V6a1 1 0 00-1-1z" clipRule="evenodd" />
                 </svg>
-                {errors.course.message}
+                {errors.course_id.message}
               </p>
             )}
           </div>
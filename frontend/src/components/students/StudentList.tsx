@@ -2,6 +2,7 @@
 
 import { useState } from 'react'
 import { useQuery } from '@tanstack/react-query'
+import { Course } from '@/types/course'
 import { Student } from '@/types/student'
 import { PencilIcon, TrashIcon } from '@heroicons/react/24/outline'
 
@@ -20,6 +21,10 @@ export function StudentList({ onEdit, onDelete }: StudentListProps) {
   const { data: response, isLoading, error } = useQuery<ApiResponse>(['students', page], () =>
     fetch(`/api/students?page=${page}&limit=10`).then((res) => res.json())
   )
+  const { data: coursesResponse } = useQuery<{ data: Course[] }>(['courses'], () =>
+    fetch('/api/courses').then((res) => res.json())
+  )
+  const courseCodes = new Map((coursesResponse?.data || []).map((course) => [course.id, course.code]))
 
   const data = response?.data || []
 
@@ -106,7 +111,7 @@ export function StudentList({ onEdit, onDelete }: StudentListProps) {
                 </td>
                 <td className="px-6 py-4">
                   <span className="inline-flex px-2 py-1 text-xs font-semibold rounded-full bg-green-100 text-green-800">
-                    {student.course}
+                    {courseCodes.get(student.course_id) ?? student.course_id}
                   </span>
                 </td>
                 <td className="px-6 py-4 text-right text-sm font-medium">
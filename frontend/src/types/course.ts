@@ -0,0 +1,13 @@
+export interface Course {
+  id: number;
+  code: string;
+  title: string;
+  credits: number;
+  department: string;
+  description?: string;
+  is_active: boolean;
+  prerequisites?: string | null;
+  corequisites?: string | null;
+  created_at?: string;
+  updated_at?: string;
+}
@@ -3,7 +3,7 @@ export interface Student {
   name: string;
   email: string;
   phone: string;
-  course: string;
+  course_id: number;
   created_at?: string;
   updated_at?: string;
   created_by?: number;
@@ -14,5 +14,5 @@ export interface NewStudent {
   name: string;
   email: string;
   phone: string;
-  course: string;
+  course_id: number;
 } 
\ No newline at end of file
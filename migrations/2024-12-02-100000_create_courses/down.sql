@@ -0,0 +1,21 @@
+ALTER TABLE students
+ADD COLUMN course VARCHAR;
+
+UPDATE students
+SET
+    course = courses.title
+FROM
+    courses
+WHERE
+    courses.id = students.course_id;
+
+ALTER TABLE students
+ALTER COLUMN course
+SET NOT NULL;
+
+DROP INDEX idx_students_course_id;
+
+ALTER TABLE students
+DROP COLUMN course_id;
+
+DROP TABLE courses;
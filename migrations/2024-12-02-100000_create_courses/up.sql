@@ -0,0 +1,73 @@
+-- Course catalog
+CREATE TABLE courses (
+    id SERIAL PRIMARY KEY,
+    code VARCHAR(20) NOT NULL UNIQUE,
+    title VARCHAR(255) NOT NULL,
+    credits INTEGER NOT NULL DEFAULT 3 CHECK (credits >= 0),
+    department VARCHAR(100) NOT NULL,
+    description TEXT,
+    is_active BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TIMESTAMP
+    WITH
+        TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        updated_at TIMESTAMP
+    WITH
+        TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Backfill one catalog row per distinct free-text course. Values that only
+-- differ by case, spacing or punctuation ("CS", "c.s.") collapse into the same
+-- code; genuinely different spellings ("Computer Science") stay separate and
+-- can be merged by an administrator afterwards.
+CREATE TEMPORARY TABLE course_backfill AS
+SELECT
+    course,
+    COALESCE(
+        NULLIF(
+            LEFT(UPPER(REGEXP_REPLACE(course, '[^A-Za-z0-9]+', '', 'g')), 20),
+            ''
+        ),
+        'UNDECLARED'
+    ) AS code
+FROM
+    (
+        SELECT DISTINCT
+            TRIM(course) AS course
+        FROM
+            students
+    ) AS distinct_courses;
+
+INSERT INTO
+    courses (code, title, department)
+SELECT
+    code,
+    COALESCE(NULLIF(MIN(course), ''), 'Undeclared'),
+    'Unassigned'
+FROM
+    course_backfill
+GROUP BY
+    code;
+
+-- Replace the free-text column with a foreign key
+ALTER TABLE students
+ADD COLUMN course_id INTEGER REFERENCES courses (id);
+
+UPDATE students
+SET
+    course_id = courses.id
+FROM
+    course_backfill
+    JOIN courses ON courses.code = course_backfill.code
+WHERE
+    course_backfill.course = TRIM(students.course);
+
+ALTER TABLE students
+ALTER COLUMN course_id
+SET NOT NULL;
+
+ALTER TABLE students
+DROP COLUMN course;
+
+CREATE INDEX idx_students_course_id ON students (course_id);
+
+DROP TABLE course_backfill;
@@ -0,0 +1,31 @@
+-- Academic terms (semesters) and their calendar dates
+CREATE TABLE terms (
+    id SERIAL PRIMARY KEY,
+    code VARCHAR(20) NOT NULL UNIQUE,
+    name VARCHAR(100) NOT NULL,
+    start_date DATE NOT NULL,
+    end_date DATE NOT NULL,
+    registration_opens DATE NOT NULL,
+    registration_closes DATE NOT NULL,
+    add_drop_deadline DATE NOT NULL,
+    withdrawal_deadline DATE NOT NULL,
+    census_date DATE NOT NULL,
+    created_at TIMESTAMP
+    WITH
+        TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        updated_at TIMESTAMP
+    WITH
+        TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        CONSTRAINT terms_dates_ordered CHECK (
+            start_date < end_date
+            AND registration_opens <= registration_closes
+            AND registration_closes <= end_date
+            AND start_date <= add_drop_deadline
+            AND add_drop_deadline <= withdrawal_deadline
+            AND withdrawal_deadline <= end_date
+            AND census_date BETWEEN start_date AND end_date
+        ),
+        CONSTRAINT terms_no_overlap EXCLUDE USING gist (
+            daterange(start_date, end_date, '[]') WITH &&
+        )
+);
@@ -0,0 +1,3 @@
+DROP TABLE section_meetings;
+
+DROP TABLE sections;
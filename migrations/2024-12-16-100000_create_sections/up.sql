@@ -0,0 +1,33 @@
+-- Concrete offerings of a catalog course in a term
+CREATE TABLE sections (
+    id SERIAL PRIMARY KEY,
+    term_id INTEGER NOT NULL REFERENCES terms (id) ON DELETE RESTRICT,
+    course_id INTEGER NOT NULL REFERENCES courses (id) ON DELETE RESTRICT,
+    section_number VARCHAR(10) NOT NULL,
+    capacity INTEGER NOT NULL CHECK (capacity >= 0),
+    instructor_id INTEGER REFERENCES users (id) ON DELETE SET NULL,
+    room VARCHAR(50),
+    created_at TIMESTAMP
+    WITH
+        TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        updated_at TIMESTAMP
+    WITH
+        TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        UNIQUE (term_id, course_id, section_number)
+);
+
+CREATE INDEX idx_sections_term_id ON sections (term_id);
+
+CREATE INDEX idx_sections_instructor_id ON sections (instructor_id);
+
+-- Weekly meeting pattern; day_of_week follows ISO 8601 (1 = Monday)
+CREATE TABLE section_meetings (
+    id SERIAL PRIMARY KEY,
+    section_id INTEGER NOT NULL REFERENCES sections (id) ON DELETE CASCADE,
+    day_of_week SMALLINT NOT NULL CHECK (day_of_week BETWEEN 1 AND 7),
+    start_time TIME NOT NULL,
+    end_time TIME NOT NULL,
+    CHECK (start_time < end_time)
+);
+
+CREATE INDEX idx_section_meetings_section_id ON section_meetings (section_id);
@@ -0,0 +1,4 @@
+DROP TABLE enrollments;
+
+ALTER TABLE sections
+DROP COLUMN enrolled_count;
@@ -0,0 +1,34 @@
+-- Seats taken in each section, maintained by the enrollment API so capacity
+-- can be enforced with a single conditional UPDATE
+ALTER TABLE sections
+ADD COLUMN enrolled_count INTEGER NOT NULL DEFAULT 0 CHECK (enrolled_count >= 0);
+
+CREATE TABLE enrollments (
+    id SERIAL PRIMARY KEY,
+    student_id INTEGER NOT NULL REFERENCES students (id) ON DELETE RESTRICT,
+    section_id INTEGER NOT NULL REFERENCES sections (id) ON DELETE RESTRICT,
+    status VARCHAR(20) NOT NULL DEFAULT 'enrolled' CHECK (
+        status IN ('enrolled', 'dropped', 'withdrawn')
+    ),
+    enrolled_at TIMESTAMP
+    WITH
+        TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        dropped_at TIMESTAMP
+    WITH
+        TIME ZONE,
+        created_by INTEGER REFERENCES users (id) ON DELETE SET NULL,
+        updated_by INTEGER REFERENCES users (id) ON DELETE SET NULL,
+        created_at TIMESTAMP
+    WITH
+        TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        updated_at TIMESTAMP
+    WITH
+        TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Dropped rows are kept for history; only one live seat per student/section
+CREATE UNIQUE INDEX idx_enrollments_active_seat ON enrollments (student_id, section_id)
+WHERE
+    status = 'enrolled';
+
+CREATE INDEX idx_enrollments_section_id ON enrollments (section_id);
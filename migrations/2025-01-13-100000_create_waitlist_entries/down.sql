@@ -0,0 +1,4 @@
+DROP TABLE waitlist_entries;
+
+ALTER TABLE sections
+DROP COLUMN reserved_count;
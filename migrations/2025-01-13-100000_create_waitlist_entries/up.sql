@@ -0,0 +1,38 @@
+-- Seats held for waitlisted students who have an open offer
+ALTER TABLE sections
+ADD COLUMN reserved_count INTEGER NOT NULL DEFAULT 0 CHECK (reserved_count >= 0);
+
+-- Ranked by id: earlier entries are offered seats first
+CREATE TABLE waitlist_entries (
+    id SERIAL PRIMARY KEY,
+    student_id INTEGER NOT NULL REFERENCES students (id) ON DELETE CASCADE,
+    section_id INTEGER NOT NULL REFERENCES sections (id) ON DELETE CASCADE,
+    status VARCHAR(20) NOT NULL DEFAULT 'waiting' CHECK (
+        status IN (
+            'waiting',
+            'offered',
+            'enrolled',
+            'expired',
+            'cancelled'
+        )
+    ),
+    auto_enroll BOOLEAN NOT NULL DEFAULT FALSE,
+    offered_at TIMESTAMP
+    WITH
+        TIME ZONE,
+        offer_expires_at TIMESTAMP
+    WITH
+        TIME ZONE,
+        created_at TIMESTAMP
+    WITH
+        TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        updated_at TIMESTAMP
+    WITH
+        TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE UNIQUE INDEX idx_waitlist_entries_open ON waitlist_entries (student_id, section_id)
+WHERE
+    status IN ('waiting', 'offered');
+
+CREATE INDEX idx_waitlist_entries_section_status ON waitlist_entries (section_id, status, id);
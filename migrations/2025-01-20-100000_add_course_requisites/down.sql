@@ -0,0 +1,6 @@
+ALTER TABLE enrollments
+DROP COLUMN grade;
+
+ALTER TABLE courses
+DROP COLUMN prerequisites,
+DROP COLUMN corequisites;
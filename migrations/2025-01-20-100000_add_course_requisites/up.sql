@@ -0,0 +1,8 @@
+-- Requirement expressions such as "(CS101 AND MATH110) OR CS100 with grade >= C"
+ALTER TABLE courses
+ADD COLUMN prerequisites TEXT,
+ADD COLUMN corequisites TEXT;
+
+-- Final letter grade, posted once the term is over
+ALTER TABLE enrollments
+ADD COLUMN grade VARCHAR(5);
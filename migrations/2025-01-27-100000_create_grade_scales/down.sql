@@ -0,0 +1,9 @@
+ALTER TABLE enrollments
+DROP COLUMN grade_points,
+DROP COLUMN credit_earned,
+DROP COLUMN graded_at,
+DROP COLUMN graded_by;
+
+DROP TABLE grade_scale_entries;
+
+DROP TABLE grade_scales;
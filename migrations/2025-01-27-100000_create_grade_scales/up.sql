@@ -0,0 +1,87 @@
+-- Grade scales map each grade to quality points. Grades without points
+-- (pass/fail, incomplete) are left out of GPA.
+CREATE TABLE grade_scales (
+    id SERIAL PRIMARY KEY,
+    name VARCHAR(100) NOT NULL UNIQUE,
+    description TEXT,
+    is_default BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at TIMESTAMP
+    WITH
+        TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        updated_at TIMESTAMP
+    WITH
+        TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Only one scale is used for new grades at a time
+CREATE UNIQUE INDEX idx_grade_scales_default ON grade_scales (is_default)
+WHERE
+    is_default;
+
+CREATE TABLE grade_scale_entries (
+    id SERIAL PRIMARY KEY,
+    grade_scale_id INTEGER NOT NULL REFERENCES grade_scales (id) ON DELETE CASCADE,
+    grade VARCHAR(5) NOT NULL,
+    points DOUBLE PRECISION CHECK (points >= 0),
+    earns_credit BOOLEAN NOT NULL,
+    -- FALSE for placeholders such as Incomplete that are replaced later
+    is_final BOOLEAN NOT NULL DEFAULT TRUE,
+    UNIQUE (grade_scale_id, grade)
+);
+
+-- Points and credit are copied from the scale when the grade is posted, so
+-- later scale changes do not rewrite past GPAs
+ALTER TABLE enrollments
+ADD COLUMN grade_points DOUBLE PRECISION,
+ADD COLUMN credit_earned BOOLEAN NOT NULL DEFAULT FALSE,
+ADD COLUMN graded_at TIMESTAMP
+WITH
+    TIME ZONE,
+ADD COLUMN graded_by INTEGER REFERENCES users (id) ON DELETE SET NULL;
+
+INSERT INTO
+    grade_scales (name, description, is_default)
+VALUES
+    (
+        'Standard',
+        '4.0 letter scale with pass/fail and incomplete',
+        TRUE
+    );
+
+INSERT INTO
+    grade_scale_entries (
+        grade_scale_id,
+        grade,
+        points,
+        earns_credit,
+        is_final
+    )
+SELECT
+    grade_scales.id,
+    entry.grade,
+    entry.points,
+    entry.earns_credit,
+    entry.is_final
+FROM
+    grade_scales,
+    (
+        VALUES
+            ('A+', 4.0, TRUE, TRUE),
+            ('A', 4.0, TRUE, TRUE),
+            ('A-', 3.7, TRUE, TRUE),
+            ('B+', 3.3, TRUE, TRUE),
+            ('B', 3.0, TRUE, TRUE),
+            ('B-', 2.7, TRUE, TRUE),
+            ('C+', 2.3, TRUE, TRUE),
+            ('C', 2.0, TRUE, TRUE),
+            ('C-', 1.7, TRUE, TRUE),
+            ('D+', 1.3, TRUE, TRUE),
+            ('D', 1.0, TRUE, TRUE),
+            ('D-', 0.7, TRUE, TRUE),
+            ('F', 0.0, FALSE, TRUE),
+            ('P', NULL, TRUE, TRUE),
+            ('NP', NULL, FALSE, TRUE),
+            ('I', NULL, FALSE, FALSE)
+    ) AS entry (grade, points, earns_credit, is_final)
+WHERE
+    grade_scales.name = 'Standard';
@@ -0,0 +1,7 @@
+DROP TABLE student_programs;
+
+DROP TABLE program_requirement_courses;
+
+DROP TABLE program_requirements;
+
+DROP TABLE programs;
@@ -0,0 +1,60 @@
+-- Degree programs (majors and minors) and the requirement blocks a student's
+-- record is audited against before graduation
+CREATE TABLE programs (
+    id SERIAL PRIMARY KEY,
+    code VARCHAR(20) NOT NULL UNIQUE,
+    name VARCHAR(255) NOT NULL,
+    kind VARCHAR(10) NOT NULL CHECK (kind IN ('major', 'minor')),
+    department VARCHAR(100) NOT NULL,
+    description TEXT,
+    is_active BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TIMESTAMP
+    WITH
+        TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        updated_at TIMESTAMP
+    WITH
+        TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+-- min_credits applies to choose_credits and residency_credits blocks,
+-- min_gpa to min_gpa blocks
+CREATE TABLE program_requirements (
+    id SERIAL PRIMARY KEY,
+    program_id INTEGER NOT NULL REFERENCES programs (id) ON DELETE CASCADE,
+    position INTEGER NOT NULL,
+    name VARCHAR(255) NOT NULL,
+    kind VARCHAR(20) NOT NULL CHECK (
+        kind IN (
+            'required_courses',
+            'choose_credits',
+            'min_gpa',
+            'residency_credits'
+        )
+    ),
+    min_credits INTEGER CHECK (min_credits > 0),
+    min_gpa DOUBLE PRECISION CHECK (min_gpa >= 0),
+    UNIQUE (program_id, position)
+);
+
+-- Courses a block draws on; for min_gpa and residency_credits blocks an
+-- empty list means all coursework
+CREATE TABLE program_requirement_courses (
+    requirement_id INTEGER NOT NULL REFERENCES program_requirements (id) ON DELETE CASCADE,
+    course_id INTEGER NOT NULL REFERENCES courses (id) ON DELETE RESTRICT,
+    PRIMARY KEY (requirement_id, course_id)
+);
+
+CREATE TABLE student_programs (
+    id SERIAL PRIMARY KEY,
+    student_id INTEGER NOT NULL REFERENCES students (id) ON DELETE CASCADE,
+    program_id INTEGER NOT NULL REFERENCES programs (id) ON DELETE RESTRICT,
+    declared_at TIMESTAMP
+    WITH
+        TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        created_by INTEGER REFERENCES users (id) ON DELETE SET NULL,
+        UNIQUE (student_id, program_id)
+);
+
+CREATE INDEX idx_program_requirement_courses_course_id ON program_requirement_courses (course_id);
+
+CREATE INDEX idx_student_programs_program_id ON student_programs (program_id);
@@ -0,0 +1,11 @@
+DELETE FROM roles
+WHERE
+    name = 'advisor';
+
+DROP TABLE credit_overloads;
+
+DROP TABLE credit_limits;
+
+ALTER TABLE students
+DROP COLUMN level,
+DROP COLUMN academic_standing;
@@ -0,0 +1,74 @@
+-- Student level and academic standing select the credit load limits that
+-- apply at registration
+ALTER TABLE students
+ADD COLUMN level VARCHAR(20) NOT NULL DEFAULT 'undergraduate' CHECK (level IN ('undergraduate', 'graduate')),
+ADD COLUMN academic_standing VARCHAR(20) NOT NULL DEFAULT 'good' CHECK (
+    academic_standing IN ('good', 'warning', 'probation')
+);
+
+-- Per-term credit loads. A row with no academic_standing applies to every
+-- standing at that level that has no row of its own.
+CREATE TABLE credit_limits (
+    id SERIAL PRIMARY KEY,
+    level VARCHAR(20) NOT NULL CHECK (level IN ('undergraduate', 'graduate')),
+    academic_standing VARCHAR(20) CHECK (
+        academic_standing IN ('good', 'warning', 'probation')
+    ),
+    min_credits INTEGER NOT NULL CHECK (min_credits >= 0),
+    max_credits INTEGER NOT NULL,
+    full_time_credits INTEGER NOT NULL,
+    created_at TIMESTAMP
+    WITH
+        TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        updated_at TIMESTAMP
+    WITH
+        TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        CHECK (max_credits >= min_credits),
+        CHECK (full_time_credits BETWEEN min_credits AND max_credits)
+);
+
+CREATE UNIQUE INDEX idx_credit_limits_level_standing ON credit_limits (level, academic_standing)
+WHERE
+    academic_standing IS NOT NULL;
+
+CREATE UNIQUE INDEX idx_credit_limits_level_default ON credit_limits (level)
+WHERE
+    academic_standing IS NULL;
+
+-- An advisor's approval to exceed the maximum load for one term
+CREATE TABLE credit_overloads (
+    id SERIAL PRIMARY KEY,
+    student_id INTEGER NOT NULL REFERENCES students (id) ON DELETE CASCADE,
+    term_id INTEGER NOT NULL REFERENCES terms (id) ON DELETE CASCADE,
+    max_credits INTEGER NOT NULL CHECK (max_credits > 0),
+    reason TEXT NOT NULL,
+    approved_by INTEGER REFERENCES users (id) ON DELETE SET NULL,
+    created_at TIMESTAMP
+    WITH
+        TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        UNIQUE (student_id, term_id)
+);
+
+CREATE INDEX idx_credit_overloads_term_id ON credit_overloads (term_id);
+
+INSERT INTO
+    roles (name, description)
+VALUES
+    (
+        'advisor',
+        'Academic advisor who can approve credit overloads'
+    );
+
+INSERT INTO
+    credit_limits (
+        level,
+        academic_standing,
+        min_credits,
+        max_credits,
+        full_time_credits
+    )
+VALUES
+    ('undergraduate', NULL, 3, 18, 12),
+    ('undergraduate', 'probation', 3, 13, 12),
+    ('graduate', NULL, 3, 15, 9),
+    ('graduate', 'probation', 3, 10, 9);
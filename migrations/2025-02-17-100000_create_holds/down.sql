@@ -0,0 +1,5 @@
+DELETE FROM roles
+WHERE
+    name IN ('bursar', 'dean', 'health');
+
+DROP TABLE holds;
@@ -0,0 +1,58 @@
+-- Holds placed on a student by an office. While a hold is in effect
+-- (starts_on <= today, and ends_on unset or not yet passed) it blocks the
+-- actions flagged on it.
+CREATE TABLE holds (
+    id SERIAL PRIMARY KEY,
+    student_id INTEGER NOT NULL REFERENCES students (id) ON DELETE CASCADE,
+    kind VARCHAR(20) NOT NULL CHECK (
+        kind IN (
+            'financial',
+            'advising',
+            'disciplinary',
+            'immunization'
+        )
+    ),
+    reason TEXT NOT NULL,
+    office VARCHAR(100) NOT NULL,
+    starts_on DATE NOT NULL,
+    ends_on DATE,
+    blocks_registration BOOLEAN NOT NULL DEFAULT TRUE,
+    blocks_transcript BOOLEAN NOT NULL DEFAULT FALSE,
+    blocks_graduation BOOLEAN NOT NULL DEFAULT FALSE,
+    created_by INTEGER REFERENCES users (id) ON DELETE SET NULL,
+    updated_by INTEGER REFERENCES users (id) ON DELETE SET NULL,
+    created_at TIMESTAMP
+    WITH
+        TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        updated_at TIMESTAMP
+    WITH
+        TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        CHECK (
+            ends_on IS NULL
+            OR ends_on >= starts_on
+        ),
+        CHECK (
+            blocks_registration
+            OR blocks_transcript
+            OR blocks_graduation
+        )
+);
+
+CREATE INDEX idx_holds_student_id ON holds (student_id);
+
+-- Offices that place holds of their own kind; advisors place advising holds
+INSERT INTO
+    roles (name, description)
+VALUES
+    (
+        'bursar',
+        'Student accounts office that places financial holds'
+    ),
+    (
+        'dean',
+        'Dean of students office that places disciplinary holds'
+    ),
+    (
+        'health',
+        'Health services office that places immunization holds'
+    );
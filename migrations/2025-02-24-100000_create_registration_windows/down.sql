@@ -0,0 +1,7 @@
+DROP TABLE appointment_overrides;
+
+DROP TABLE registration_windows;
+
+ALTER TABLE students
+DROP COLUMN user_id,
+DROP COLUMN cohort;
@@ -0,0 +1,51 @@
+-- Link a student record to the user account the student signs in with, and
+-- group students into cohorts that registration windows can target
+ALTER TABLE students
+ADD COLUMN user_id INTEGER UNIQUE REFERENCES users (id) ON DELETE SET NULL,
+ADD COLUMN cohort VARCHAR(50);
+
+-- Priority registration. A window opens registration for the term to
+-- students with at least min_credits earned, or to one cohort; a window with
+-- neither opens it to everyone. A student's appointment is the earliest
+-- window they match.
+CREATE TABLE registration_windows (
+    id SERIAL PRIMARY KEY,
+    term_id INTEGER NOT NULL REFERENCES terms (id) ON DELETE CASCADE,
+    name VARCHAR(100) NOT NULL,
+    opens_at TIMESTAMP
+    WITH
+        TIME ZONE NOT NULL,
+        min_credits INTEGER CHECK (min_credits >= 0),
+        cohort VARCHAR(50),
+        created_at TIMESTAMP
+    WITH
+        TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        updated_at TIMESTAMP
+    WITH
+        TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        CHECK (
+            min_credits IS NULL
+            OR cohort IS NULL
+        )
+);
+
+CREATE INDEX idx_registration_windows_term_id ON registration_windows (term_id);
+
+-- A fixed appointment for one student in one term, replacing whatever window
+-- they would match (athletes, disability services)
+CREATE TABLE appointment_overrides (
+    id SERIAL PRIMARY KEY,
+    student_id INTEGER NOT NULL REFERENCES students (id) ON DELETE CASCADE,
+    term_id INTEGER NOT NULL REFERENCES terms (id) ON DELETE CASCADE,
+    opens_at TIMESTAMP
+    WITH
+        TIME ZONE NOT NULL,
+        reason TEXT NOT NULL,
+        created_by INTEGER REFERENCES users (id) ON DELETE SET NULL,
+        created_at TIMESTAMP
+    WITH
+        TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        UNIQUE (student_id, term_id)
+);
+
+CREATE INDEX idx_appointment_overrides_term_id ON appointment_overrides (term_id);
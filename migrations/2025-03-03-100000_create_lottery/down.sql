@@ -0,0 +1,6 @@
+DROP TABLE lottery_requests;
+
+DROP TABLE lottery_runs;
+
+ALTER TABLE sections
+DROP COLUMN lottery;
@@ -0,0 +1,45 @@
+-- Sections whose seats are held for a lottery. Students rank them during the
+-- request phase instead of enrolling; a lottery run allocates the seats and
+-- clears the flag so registration continues as usual.
+ALTER TABLE sections
+ADD COLUMN lottery BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- One lottery run per execution. The seed and priority weights are kept with
+-- the report so the allocation can be reproduced.
+CREATE TABLE lottery_runs (
+    id SERIAL PRIMARY KEY,
+    term_id INTEGER NOT NULL REFERENCES terms (id) ON DELETE CASCADE,
+    seed BIGINT NOT NULL,
+    weights JSONB NOT NULL DEFAULT '[]',
+    report JSONB NOT NULL,
+    run_by INTEGER REFERENCES users (id) ON DELETE SET NULL,
+    created_at TIMESTAMP
+    WITH
+        TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_lottery_runs_term_id ON lottery_runs (term_id);
+
+-- A student's ranked choices among lottery sections; rank 1 is the first
+-- choice
+CREATE TABLE lottery_requests (
+    id SERIAL PRIMARY KEY,
+    student_id INTEGER NOT NULL REFERENCES students (id) ON DELETE CASCADE,
+    section_id INTEGER NOT NULL REFERENCES sections (id) ON DELETE CASCADE,
+    rank INTEGER NOT NULL CHECK (rank > 0),
+    status VARCHAR(20) NOT NULL DEFAULT 'pending' CHECK (
+        status IN (
+            'pending',
+            'enrolled',
+            'waitlisted',
+            'unsuccessful'
+        )
+    ),
+    lottery_run_id INTEGER REFERENCES lottery_runs (id) ON DELETE SET NULL,
+    created_at TIMESTAMP
+    WITH
+        TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        UNIQUE (student_id, section_id)
+);
+
+CREATE INDEX idx_lottery_requests_section_id ON lottery_requests (section_id);
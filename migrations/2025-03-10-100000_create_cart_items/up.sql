@@ -0,0 +1,13 @@
+-- Sections a student plans to take. The term is the section's term; the cart
+-- is validated and submitted per term and emptied once submitted.
+CREATE TABLE cart_items (
+    id SERIAL PRIMARY KEY,
+    student_id INTEGER NOT NULL REFERENCES students (id) ON DELETE CASCADE,
+    section_id INTEGER NOT NULL REFERENCES sections (id) ON DELETE CASCADE,
+    created_at TIMESTAMP
+    WITH
+        TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        UNIQUE (student_id, section_id)
+);
+
+CREATE INDEX idx_cart_items_section_id ON cart_items (section_id);
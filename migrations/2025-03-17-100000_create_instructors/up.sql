@@ -0,0 +1,19 @@
+-- Faculty profiles for user accounts. Sections keep pointing at the user
+-- through sections.instructor_id; a user needs a profile to be assigned.
+CREATE TABLE instructors (
+    id SERIAL PRIMARY KEY,
+    user_id INTEGER NOT NULL UNIQUE REFERENCES users (id) ON DELETE CASCADE,
+    department VARCHAR(100) NOT NULL,
+    title VARCHAR(100) NOT NULL,
+    office VARCHAR(100),
+    -- Most sections the instructor may teach in one term
+    max_sections INTEGER NOT NULL DEFAULT 3 CHECK (max_sections >= 0),
+    created_at TIMESTAMP
+    WITH
+        TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        updated_at TIMESTAMP
+    WITH
+        TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_instructors_department ON instructors (department);
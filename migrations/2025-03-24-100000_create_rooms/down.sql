@@ -0,0 +1,8 @@
+ALTER TABLE sections
+DROP COLUMN room_id;
+
+DROP TABLE room_features;
+
+DROP TABLE rooms;
+
+DROP TABLE buildings;
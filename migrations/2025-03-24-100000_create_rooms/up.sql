@@ -0,0 +1,43 @@
+CREATE TABLE buildings (
+    id SERIAL PRIMARY KEY,
+    code VARCHAR(10) NOT NULL UNIQUE,
+    name VARCHAR(100) NOT NULL,
+    created_at TIMESTAMP
+    WITH
+        TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        updated_at TIMESTAMP
+    WITH
+        TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+-- A building with rooms cannot be deleted; neither can a room that sections
+-- are booked into.
+CREATE TABLE rooms (
+    id SERIAL PRIMARY KEY,
+    building_id INTEGER NOT NULL REFERENCES buildings (id),
+    room_number VARCHAR(20) NOT NULL,
+    capacity INTEGER NOT NULL CHECK (capacity >= 0),
+    created_at TIMESTAMP
+    WITH
+        TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        updated_at TIMESTAMP
+    WITH
+        TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        UNIQUE (building_id, room_number)
+);
+
+-- Equipment and room types such as "projector" or "lab", stored lowercase
+CREATE TABLE room_features (
+    room_id INTEGER NOT NULL REFERENCES rooms (id) ON DELETE CASCADE,
+    feature VARCHAR(30) NOT NULL,
+    PRIMARY KEY (room_id, feature)
+);
+
+CREATE INDEX idx_room_features_feature ON room_features (feature);
+
+-- The booked room. sections.room stays as a free-text location for sections
+-- that do not meet in an inventoried room (e.g. "Online").
+ALTER TABLE sections
+ADD COLUMN room_id INTEGER REFERENCES rooms (id);
+
+CREATE INDEX idx_sections_room_id ON sections (room_id);
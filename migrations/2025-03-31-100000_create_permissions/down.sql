@@ -0,0 +1,27 @@
+UPDATE users
+SET
+    role_id = (
+        SELECT
+            id
+        FROM
+            roles
+        WHERE
+            name = 'user'
+    )
+WHERE
+    role_id = (
+        SELECT
+            id
+        FROM
+            roles
+        WHERE
+            name = 'instructor'
+    );
+
+DELETE FROM roles
+WHERE
+    name = 'instructor';
+
+DROP TABLE role_permissions;
+
+DROP TABLE permissions;
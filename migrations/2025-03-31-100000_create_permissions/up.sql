@@ -0,0 +1,182 @@
+-- Named capabilities checked by route guards. Roles are granted permissions
+-- through role_permissions; the set of permissions is defined by the code.
+CREATE TABLE permissions (
+    id SERIAL PRIMARY KEY,
+    name VARCHAR(100) NOT NULL UNIQUE,
+    description TEXT,
+    created_at TIMESTAMP
+    WITH
+        TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE role_permissions (
+    role_id INTEGER NOT NULL REFERENCES roles (id) ON DELETE CASCADE,
+    permission_id INTEGER NOT NULL REFERENCES permissions (id) ON DELETE CASCADE,
+    granted_at TIMESTAMP
+    WITH
+        TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        PRIMARY KEY (role_id, permission_id)
+);
+
+CREATE INDEX idx_role_permissions_permission_id ON role_permissions (permission_id);
+
+INSERT INTO
+    permissions (name, description)
+VALUES
+    ('students.read', 'View student records'),
+    (
+        'students.read_all',
+        'View every student, not only advisees, taught students or the caller''s own record'
+    ),
+    (
+        'students.write',
+        'Create, update and delete student records'
+    ),
+    ('grades.post', 'Post final grades for sections'),
+    (
+        'grades.manage',
+        'View and post grades for any section'
+    ),
+    (
+        'grade_scales.manage',
+        'Create, update and delete grade scales'
+    ),
+    ('audit.read', 'View the audit log'),
+    (
+        'courses.manage',
+        'Create, update and delete courses'
+    ),
+    ('terms.manage', 'Create, update and delete terms'),
+    (
+        'sections.manage',
+        'Create, update and delete sections'
+    ),
+    (
+        'programs.manage',
+        'Create, update and delete degree programs'
+    ),
+    (
+        'credit_limits.manage',
+        'Create, update and delete credit limits'
+    ),
+    (
+        'overloads.approve',
+        'Approve and revoke credit overloads'
+    ),
+    (
+        'rooms.manage',
+        'Manage buildings and rooms and view room utilization'
+    ),
+    (
+        'instructors.manage',
+        'Manage instructor profiles and view any instructor''s sections'
+    ),
+    (
+        'registration.manage',
+        'Manage registration windows, appointment overrides and lottery runs, and dry-run requisite checks'
+    ),
+    (
+        'enrollments.write',
+        'Enroll and drop students and manage their carts, waitlist places and lottery requests'
+    ),
+    (
+        'enrollments.override',
+        'Enroll students despite schedule conflicts'
+    ),
+    (
+        'roles.manage',
+        'Manage roles, their permissions and the roles of users'
+    ),
+    (
+        'sessions.manage',
+        'View and revoke the sessions of other users'
+    ),
+    ('holds.financial', 'Place and lift financial holds'),
+    ('holds.advising', 'Place and lift advising holds'),
+    (
+        'holds.disciplinary',
+        'Place and lift disciplinary holds'
+    ),
+    (
+        'holds.immunization',
+        'Place and lift immunization holds'
+    );
+
+-- Faculty with an instructor profile move to their own role so grade posting
+-- can be granted to them alone.
+INSERT INTO
+    roles (name, description)
+VALUES
+    (
+        'instructor',
+        'Faculty member who teaches sections and posts grades'
+    );
+
+UPDATE users
+SET
+    role_id = (
+        SELECT
+            id
+        FROM
+            roles
+        WHERE
+            name = 'instructor'
+    )
+WHERE
+    id IN (
+        SELECT
+            user_id
+        FROM
+            instructors
+    )
+    AND role_id = (
+        SELECT
+            id
+        FROM
+            roles
+        WHERE
+            name = 'user'
+    );
+
+INSERT INTO
+    role_permissions (role_id, permission_id)
+SELECT
+    roles.id,
+    permissions.id
+FROM
+    roles
+    CROSS JOIN permissions
+WHERE
+    roles.name = 'admin'
+    OR (
+        roles.name = 'instructor'
+        AND permissions.name IN ('students.read', 'grades.post')
+    )
+    OR (
+        roles.name IN ('user', 'advisor', 'bursar', 'dean', 'health')
+        AND permissions.name = 'students.read'
+    )
+    -- Student users register themselves; their scope keeps them to their own
+    -- record
+    OR (
+        roles.name = 'user'
+        AND permissions.name = 'enrollments.write'
+    )
+    -- Each office manages its own kind of hold and sees every student;
+    -- advisors also approve overloads
+    OR (
+        roles.name = 'advisor'
+        AND permissions.name IN ('holds.advising', 'overloads.approve')
+    )
+    OR (
+        roles.name = 'bursar'
+        AND permissions.name IN ('holds.financial', 'students.read_all')
+    )
+    OR (
+        roles.name = 'dean'
+        AND permissions.name IN ('holds.disciplinary', 'students.read_all')
+    )
+    OR (
+        roles.name = 'health'
+        AND permissions.name IN ('holds.immunization', 'students.read_all')
+    );
@@ -0,0 +1,4 @@
+DROP INDEX idx_students_advisor_id;
+
+ALTER TABLE students
+DROP COLUMN advisor_id;
@@ -0,0 +1,6 @@
+-- The advisor assigned to each student. Advisors can only read the records
+-- of their own advisees.
+ALTER TABLE students
+ADD COLUMN advisor_id INTEGER REFERENCES users (id) ON DELETE SET NULL;
+
+CREATE INDEX idx_students_advisor_id ON students (advisor_id);
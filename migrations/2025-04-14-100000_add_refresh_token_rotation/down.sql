@@ -0,0 +1,14 @@
+DROP INDEX idx_user_tokens_family_id;
+
+DROP INDEX user_tokens_token_hash_idx;
+
+ALTER TABLE user_tokens
+DROP COLUMN family_id,
+DROP COLUMN rotated_at,
+DROP COLUMN revoked_at;
+
+ALTER TABLE user_tokens
+RENAME COLUMN token_hash TO token;
+
+ALTER TABLE user_tokens
+ADD CONSTRAINT user_tokens_token_key UNIQUE (token);
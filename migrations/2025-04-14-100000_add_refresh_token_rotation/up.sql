@@ -0,0 +1,27 @@
+-- Refresh tokens are opaque random strings and only their SHA-256 hash is
+-- stored. Each login starts a token family. Refreshing rotates the token
+-- within its family, and presenting a rotated or revoked token again revokes
+-- every token in the family. No tokens were ever issued into the old column.
+DELETE FROM user_tokens;
+
+ALTER TABLE user_tokens
+RENAME COLUMN token TO token_hash;
+
+-- Refresh looks tokens up by hash, so the hash must stay unique. The index
+-- replaces the constraint inherited from the old column, whose name no
+-- longer matches it.
+ALTER TABLE user_tokens
+DROP CONSTRAINT user_tokens_token_key;
+
+CREATE UNIQUE INDEX user_tokens_token_hash_idx ON user_tokens (token_hash);
+
+ALTER TABLE user_tokens
+ADD COLUMN family_id VARCHAR(36) NOT NULL,
+ADD COLUMN rotated_at TIMESTAMP
+WITH
+    TIME ZONE,
+ADD COLUMN revoked_at TIMESTAMP
+WITH
+    TIME ZONE;
+
+CREATE INDEX idx_user_tokens_family_id ON user_tokens (family_id);
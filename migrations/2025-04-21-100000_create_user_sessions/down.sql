@@ -0,0 +1,21 @@
+ALTER TABLE user_tokens
+ADD COLUMN family_id VARCHAR(36);
+
+UPDATE user_tokens
+SET
+    family_id = md5(session_id::TEXT)::UUID::TEXT;
+
+ALTER TABLE user_tokens
+ALTER COLUMN family_id
+SET NOT NULL;
+
+CREATE INDEX idx_user_tokens_family_id ON user_tokens (family_id);
+
+DROP INDEX idx_user_tokens_session_id;
+
+ALTER TABLE user_tokens
+DROP COLUMN session_id,
+DROP COLUMN access_jti,
+DROP COLUMN access_expires_at;
+
+DROP TABLE user_sessions;
@@ -0,0 +1,77 @@
+-- A session is one sign-in: the device and address it came from, and the
+-- refresh token family issued to it. Revoking a session revokes its refresh
+-- tokens and denies the access tokens issued from it.
+CREATE TABLE user_sessions (
+    id SERIAL PRIMARY KEY,
+    user_id INTEGER NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    user_agent TEXT,
+    ip_address VARCHAR(45),
+    created_at TIMESTAMP
+    WITH
+        TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        last_used_at TIMESTAMP
+    WITH
+        TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        expires_at TIMESTAMP
+    WITH
+        TIME ZONE NOT NULL,
+        revoked_at TIMESTAMP
+    WITH
+        TIME ZONE
+);
+
+CREATE INDEX idx_user_sessions_user_id ON user_sessions (user_id);
+
+-- Every token family becomes a session. The access token issued alongside
+-- each refresh token is recorded so that revoking the session can deny it.
+ALTER TABLE user_sessions
+ADD COLUMN family_id VARCHAR(36);
+
+INSERT INTO
+    user_sessions (
+        user_id,
+        family_id,
+        created_at,
+        last_used_at,
+        expires_at,
+        revoked_at
+    )
+SELECT
+    user_id,
+    family_id,
+    MIN(created_at),
+    MAX(created_at),
+    MAX(expires_at),
+    MAX(revoked_at)
+FROM
+    user_tokens
+GROUP BY
+    user_id,
+    family_id;
+
+ALTER TABLE user_tokens
+ADD COLUMN session_id INTEGER REFERENCES user_sessions (id) ON DELETE CASCADE,
+ADD COLUMN access_jti VARCHAR(36),
+ADD COLUMN access_expires_at TIMESTAMP
+WITH
+    TIME ZONE;
+
+UPDATE user_tokens
+SET
+    session_id = user_sessions.id
+FROM
+    user_sessions
+WHERE
+    user_sessions.family_id = user_tokens.family_id;
+
+DROP INDEX idx_user_tokens_family_id;
+
+ALTER TABLE user_tokens
+DROP COLUMN family_id,
+ALTER COLUMN session_id
+SET NOT NULL;
+
+ALTER TABLE user_sessions
+DROP COLUMN family_id;
+
+CREATE INDEX idx_user_tokens_session_id ON user_tokens (session_id);
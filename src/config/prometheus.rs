@@ -16,19 +16,4 @@ lazy_static! {
         &["endpoint"]
     )
     .unwrap();
-
-    // Database metrics
-    pub static ref DB_QUERY_COUNTER: IntCounterVec = register_int_counter_vec!(
-        "db_queries_total",
-        "Total number of database queries by table and operation",
-        &["table", "operation"]
-    )
-    .unwrap();
-
-    pub static ref DB_QUERY_DURATION: HistogramVec = register_histogram_vec!(
-        "db_query_duration_seconds",
-        "Database query duration in seconds",
-        &["table", "operation"]
-    )
-    .unwrap();
 }
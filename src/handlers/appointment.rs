@@ -0,0 +1,637 @@
+use actix_web::{web, HttpResponse};
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use diesel::result::{DatabaseErrorKind, Error as DieselError};
+use serde::Deserialize;
+use serde_json::json;
+use validator::{Validate, ValidationError};
+
+use crate::middleware::AuthenticatedUser;
+use crate::models::appointment::{
+    AppointmentOverride, NewAppointmentOverride, NewRegistrationWindow, RegistrationWindow,
+};
+use crate::models::{AuditContext, Student, Term};
+use crate::schema::{appointment_overrides, audit_logs, registration_windows, students};
+use crate::services::appointments;
+use crate::DbPool;
+
+#[derive(Debug, Deserialize, Validate)]
+#[validate(schema(function = "validate_window_audience"))]
+pub struct WindowRequest {
+    #[validate(length(min = 1, max = 100))]
+    pub name: String,
+    pub opens_at: DateTime<Utc>,
+    #[validate(range(min = 0, max = 1000))]
+    pub min_credits: Option<i32>,
+    #[validate(length(min = 1, max = 50))]
+    pub cohort: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct OverrideRequest {
+    pub student_id: i32,
+    pub opens_at: DateTime<Utc>,
+    #[validate(length(min = 1))]
+    pub reason: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AppointmentQuery {
+    pub term: Option<String>,
+}
+
+fn validate_window_audience(window: &WindowRequest) -> Result<(), ValidationError> {
+    if window.min_credits.is_none() || window.cohort.is_none() {
+        return Ok(());
+    }
+    let mut error = ValidationError::new("window_audience");
+    error.message = Some("a window targets either min_credits or a cohort, not both".into());
+    Err(error)
+}
+
+enum AppointmentError {
+    TermNotFound,
+    StudentNotFound,
+    NoLinkedStudent,
+    WindowNotFound,
+    OverrideNotFound,
+    Database(DieselError),
+}
+
+impl From<DieselError> for AppointmentError {
+    fn from(error: DieselError) -> Self {
+        AppointmentError::Database(error)
+    }
+}
+
+fn resolve_term(conn: &mut PgConnection, term_key: &str) -> Result<Term, AppointmentError> {
+    Term::resolve(conn, term_key).map_err(|e| match e {
+        DieselError::NotFound => AppointmentError::TermNotFound,
+        other => AppointmentError::Database(other),
+    })
+}
+
+fn find_student(conn: &mut PgConnection, student_id: i32) -> Result<Student, AppointmentError> {
+    students::table
+        .find(student_id)
+        .first::<Student>(conn)
+        .optional()?
+        .ok_or(AppointmentError::StudentNotFound)
+}
+
+pub async fn get_windows(pool: web::Data<DbPool>, path: web::Path<String>) -> HttpResponse {
+    let term_key = path.into_inner();
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => {
+            log::error!("Failed to get database connection: {:?}", e);
+            return HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Database connection error"
+            }));
+        }
+    };
+
+    let result = web::block(move || {
+        let term = resolve_term(&mut conn, &term_key)?;
+        Ok::<_, AppointmentError>(
+            registration_windows::table
+                .filter(registration_windows::term_id.eq(term.id))
+                .order((
+                    registration_windows::opens_at.asc(),
+                    registration_windows::id.asc(),
+                ))
+                .load::<RegistrationWindow>(&mut conn)?,
+        )
+    })
+    .await;
+
+    match result {
+        Ok(db_result) => match db_result {
+            Ok(windows) => HttpResponse::Ok().json(json!({
+                "status": "success",
+                "data": windows
+            })),
+            Err(err) => appointment_error_response(err, "Failed to fetch registration windows"),
+        },
+        Err(blocking_err) => {
+            log::error!("Blocking error: {:?}", blocking_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Internal server error"
+            }))
+        }
+    }
+}
+
+pub async fn create_window(
+    pool: web::Data<DbPool>,
+    path: web::Path<String>,
+    window_req: web::Json<WindowRequest>,
+) -> HttpResponse {
+    let term_key = path.into_inner();
+    let window_req = window_req.into_inner();
+    if let Err(errors) = window_req.validate() {
+        log::error!("Validation errors: {:?}", errors);
+        return HttpResponse::BadRequest().json(json!({
+            "status": "error",
+            "message": "Validation failed",
+            "errors": errors
+        }));
+    }
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => {
+            log::error!("Failed to get database connection: {:?}", e);
+            return HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Database connection error"
+            }));
+        }
+    };
+
+    let result = web::block(move || {
+        let term = resolve_term(&mut conn, &term_key)?;
+        Ok::<_, AppointmentError>(
+            diesel::insert_into(registration_windows::table)
+                .values(&NewRegistrationWindow {
+                    term_id: term.id,
+                    name: window_req.name,
+                    opens_at: window_req.opens_at,
+                    min_credits: window_req.min_credits,
+                    cohort: window_req.cohort,
+                })
+                .get_result::<RegistrationWindow>(&mut conn)?,
+        )
+    })
+    .await;
+
+    match result {
+        Ok(db_result) => match db_result {
+            Ok(window) => {
+                log::info!("Successfully created registration window {}", window.id);
+                HttpResponse::Created().json(json!({
+                    "status": "success",
+                    "data": window
+                }))
+            }
+            Err(err) => appointment_error_response(err, "Failed to create registration window"),
+        },
+        Err(blocking_err) => {
+            log::error!("Blocking error: {:?}", blocking_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Internal server error"
+            }))
+        }
+    }
+}
+
+pub async fn update_window(
+    pool: web::Data<DbPool>,
+    path: web::Path<(String, i32)>,
+    window_req: web::Json<WindowRequest>,
+) -> HttpResponse {
+    let (term_key, window_id) = path.into_inner();
+    let window_req = window_req.into_inner();
+    if let Err(errors) = window_req.validate() {
+        log::error!("Validation errors: {:?}", errors);
+        return HttpResponse::BadRequest().json(json!({
+            "status": "error",
+            "message": "Validation failed",
+            "errors": errors
+        }));
+    }
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => {
+            log::error!("Failed to get database connection: {:?}", e);
+            return HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Database connection error"
+            }));
+        }
+    };
+
+    let result = web::block(move || {
+        let term = resolve_term(&mut conn, &term_key)?;
+        diesel::update(
+            registration_windows::table
+                .filter(registration_windows::id.eq(window_id))
+                .filter(registration_windows::term_id.eq(term.id)),
+        )
+        .set((
+            &NewRegistrationWindow {
+                term_id: term.id,
+                name: window_req.name,
+                opens_at: window_req.opens_at,
+                min_credits: window_req.min_credits,
+                cohort: window_req.cohort,
+            },
+            registration_windows::updated_at.eq(Utc::now()),
+        ))
+        .get_result::<RegistrationWindow>(&mut conn)
+        .optional()?
+        .ok_or(AppointmentError::WindowNotFound)
+    })
+    .await;
+
+    match result {
+        Ok(db_result) => match db_result {
+            Ok(window) => {
+                log::info!("Successfully updated registration window {}", window.id);
+                HttpResponse::Ok().json(json!({
+                    "status": "success",
+                    "data": window
+                }))
+            }
+            Err(err) => appointment_error_response(err, "Failed to update registration window"),
+        },
+        Err(blocking_err) => {
+            log::error!("Blocking error: {:?}", blocking_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Internal server error"
+            }))
+        }
+    }
+}
+
+pub async fn delete_window(
+    pool: web::Data<DbPool>,
+    path: web::Path<(String, i32)>,
+) -> HttpResponse {
+    let (term_key, window_id) = path.into_inner();
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => {
+            log::error!("Failed to get database connection: {:?}", e);
+            return HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Database connection error"
+            }));
+        }
+    };
+
+    let result = web::block(move || {
+        let term = resolve_term(&mut conn, &term_key)?;
+        let deleted = diesel::delete(
+            registration_windows::table
+                .filter(registration_windows::id.eq(window_id))
+                .filter(registration_windows::term_id.eq(term.id)),
+        )
+        .execute(&mut conn)?;
+        match deleted {
+            0 => Err(AppointmentError::WindowNotFound),
+            _ => Ok(()),
+        }
+    })
+    .await;
+
+    match result {
+        Ok(db_result) => match db_result {
+            Ok(()) => {
+                log::info!("Successfully deleted registration window {}", window_id);
+                HttpResponse::Ok().json(json!({
+                    "status": "success",
+                    "message": "Registration window deleted successfully"
+                }))
+            }
+            Err(err) => appointment_error_response(err, "Failed to delete registration window"),
+        },
+        Err(blocking_err) => {
+            log::error!("Blocking error: {:?}", blocking_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Internal server error"
+            }))
+        }
+    }
+}
+
+pub async fn get_overrides(pool: web::Data<DbPool>, path: web::Path<String>) -> HttpResponse {
+    let term_key = path.into_inner();
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => {
+            log::error!("Failed to get database connection: {:?}", e);
+            return HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Database connection error"
+            }));
+        }
+    };
+
+    let result = web::block(move || {
+        let term = resolve_term(&mut conn, &term_key)?;
+        Ok::<_, AppointmentError>(
+            appointment_overrides::table
+                .filter(appointment_overrides::term_id.eq(term.id))
+                .order((
+                    appointment_overrides::opens_at.asc(),
+                    appointment_overrides::id.asc(),
+                ))
+                .load::<AppointmentOverride>(&mut conn)?,
+        )
+    })
+    .await;
+
+    match result {
+        Ok(db_result) => match db_result {
+            Ok(overrides) => HttpResponse::Ok().json(json!({
+                "status": "success",
+                "data": overrides
+            })),
+            Err(err) => appointment_error_response(err, "Failed to fetch appointment overrides"),
+        },
+        Err(blocking_err) => {
+            log::error!("Blocking error: {:?}", blocking_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Internal server error"
+            }))
+        }
+    }
+}
+
+/// Gives one student a fixed appointment for the term, such as early
+/// registration for athletes or through disability services.
+pub async fn create_override(
+    audit: AuditContext,
+    pool: web::Data<DbPool>,
+    path: web::Path<String>,
+    override_req: web::Json<OverrideRequest>,
+) -> HttpResponse {
+    let term_key = path.into_inner();
+    let override_req = override_req.into_inner();
+    if let Err(errors) = override_req.validate() {
+        log::error!("Validation errors: {:?}", errors);
+        return HttpResponse::BadRequest().json(json!({
+            "status": "error",
+            "message": "Validation failed",
+            "errors": errors
+        }));
+    }
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => {
+            log::error!("Failed to get database connection: {:?}", e);
+            return HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Database connection error"
+            }));
+        }
+    };
+
+    let result = web::block(move || {
+        conn.transaction::<_, AppointmentError, _>(|conn| {
+            let term = resolve_term(conn, &term_key)?;
+            find_student(conn, override_req.student_id)?;
+
+            let appointment_override = diesel::insert_into(appointment_overrides::table)
+                .values(&NewAppointmentOverride {
+                    student_id: override_req.student_id,
+                    term_id: term.id,
+                    opens_at: override_req.opens_at,
+                    reason: override_req.reason,
+                    created_by: audit.user_id,
+                })
+                .get_result::<AppointmentOverride>(conn)?;
+
+            diesel::insert_into(audit_logs::table)
+                .values(&audit.activity(
+                    "appointment_override.create",
+                    "appointment_override",
+                    Some(appointment_override.id),
+                    Some(json!({ "before": null, "after": appointment_override })),
+                ))
+                .execute(conn)?;
+
+            Ok(appointment_override)
+        })
+    })
+    .await;
+
+    match result {
+        Ok(db_result) => match db_result {
+            Ok(appointment_override) => {
+                log::info!(
+                    "Set registration appointment for student {} in term {}",
+                    appointment_override.student_id,
+                    appointment_override.term_id
+                );
+                HttpResponse::Created().json(json!({
+                    "status": "success",
+                    "data": appointment_override
+                }))
+            }
+            Err(err) => appointment_error_response(err, "Failed to create appointment override"),
+        },
+        Err(blocking_err) => {
+            log::error!("Blocking error: {:?}", blocking_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Internal server error"
+            }))
+        }
+    }
+}
+
+pub async fn delete_override(
+    audit: AuditContext,
+    pool: web::Data<DbPool>,
+    path: web::Path<(String, i32)>,
+) -> HttpResponse {
+    let (term_key, override_id) = path.into_inner();
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => {
+            log::error!("Failed to get database connection: {:?}", e);
+            return HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Database connection error"
+            }));
+        }
+    };
+
+    let result = web::block(move || {
+        conn.transaction::<_, AppointmentError, _>(|conn| {
+            let term = resolve_term(conn, &term_key)?;
+            let appointment_override = diesel::delete(
+                appointment_overrides::table
+                    .filter(appointment_overrides::id.eq(override_id))
+                    .filter(appointment_overrides::term_id.eq(term.id)),
+            )
+            .get_result::<AppointmentOverride>(conn)
+            .optional()?
+            .ok_or(AppointmentError::OverrideNotFound)?;
+
+            diesel::insert_into(audit_logs::table)
+                .values(&audit.activity(
+                    "appointment_override.delete",
+                    "appointment_override",
+                    Some(appointment_override.id),
+                    Some(json!({ "before": appointment_override, "after": null })),
+                ))
+                .execute(conn)?;
+            Ok(())
+        })
+    })
+    .await;
+
+    match result {
+        Ok(db_result) => match db_result {
+            Ok(()) => HttpResponse::Ok().json(json!({
+                "status": "success",
+                "message": "Appointment override deleted successfully"
+            })),
+            Err(err) => appointment_error_response(err, "Failed to delete appointment override"),
+        },
+        Err(blocking_err) => {
+            log::error!("Blocking error: {:?}", blocking_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Internal server error"
+            }))
+        }
+    }
+}
+
+/// The signed-in student's own appointment for a term (the current one by
+/// default).
+pub async fn get_my_appointment(
+    user: AuthenticatedUser,
+    pool: web::Data<DbPool>,
+    query: web::Query<AppointmentQuery>,
+) -> HttpResponse {
+    let term_key = query
+        .into_inner()
+        .term
+        .unwrap_or_else(|| "current".to_string());
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => {
+            log::error!("Failed to get database connection: {:?}", e);
+            return HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Database connection error"
+            }));
+        }
+    };
+
+    let result = web::block(move || {
+        let student = students::table
+            .filter(students::user_id.eq(user.user_id))
+            .first::<Student>(&mut conn)
+            .optional()?
+            .ok_or(AppointmentError::NoLinkedStudent)?;
+        let term = resolve_term(&mut conn, &term_key)?;
+        Ok::<_, AppointmentError>(appointments::for_student(&mut conn, &student, &term)?)
+    })
+    .await;
+
+    match result {
+        Ok(db_result) => match db_result {
+            Ok(appointment) => HttpResponse::Ok().json(json!({
+                "status": "success",
+                "data": appointment
+            })),
+            Err(err) => appointment_error_response(err, "Failed to fetch appointment"),
+        },
+        Err(blocking_err) => {
+            log::error!("Blocking error: {:?}", blocking_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Internal server error"
+            }))
+        }
+    }
+}
+
+pub async fn get_student_appointment(
+    _user: AuthenticatedUser,
+    pool: web::Data<DbPool>,
+    path: web::Path<i32>,
+    query: web::Query<AppointmentQuery>,
+) -> HttpResponse {
+    let student_id = path.into_inner();
+    let term_key = query
+        .into_inner()
+        .term
+        .unwrap_or_else(|| "current".to_string());
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => {
+            log::error!("Failed to get database connection: {:?}", e);
+            return HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Database connection error"
+            }));
+        }
+    };
+
+    let result = web::block(move || {
+        let student = find_student(&mut conn, student_id)?;
+        let term = resolve_term(&mut conn, &term_key)?;
+        Ok::<_, AppointmentError>(appointments::for_student(&mut conn, &student, &term)?)
+    })
+    .await;
+
+    match result {
+        Ok(db_result) => match db_result {
+            Ok(appointment) => HttpResponse::Ok().json(json!({
+                "status": "success",
+                "data": appointment
+            })),
+            Err(err) => appointment_error_response(err, "Failed to fetch appointment"),
+        },
+        Err(blocking_err) => {
+            log::error!("Blocking error: {:?}", blocking_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Internal server error"
+            }))
+        }
+    }
+}
+
+fn appointment_error_response(err: AppointmentError, failure_message: &str) -> HttpResponse {
+    let not_found = |message: &str| {
+        HttpResponse::NotFound().json(json!({
+            "status": "error",
+            "message": message
+        }))
+    };
+    match err {
+        AppointmentError::TermNotFound => not_found("Term not found"),
+        AppointmentError::StudentNotFound => not_found("Student not found"),
+        AppointmentError::NoLinkedStudent => {
+            not_found("No student record is linked to this account")
+        }
+        AppointmentError::WindowNotFound => not_found("Registration window not found"),
+        AppointmentError::OverrideNotFound => not_found("Appointment override not found"),
+        AppointmentError::Database(DieselError::DatabaseError(
+            DatabaseErrorKind::UniqueViolation,
+            _,
+        )) => HttpResponse::Conflict().json(json!({
+            "status": "error",
+            "message": "The student already has an appointment override for this term"
+        })),
+        AppointmentError::Database(db_err) => {
+            log::error!("Database error: {:?}", db_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": failure_message
+            }))
+        }
+    }
+}
@@ -0,0 +1,88 @@
+use actix_web::{web, HttpResponse};
+use diesel::prelude::*;
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::models::audit::AuditLog;
+use crate::schema::audit_logs;
+use crate::DbPool;
+
+#[derive(Debug, Deserialize)]
+pub struct AuditLogQuery {
+    pub user_id: Option<i32>,
+    pub action: Option<String>,
+    pub entity_type: Option<String>,
+    pub entity_id: Option<i32>,
+    pub page: Option<i64>,
+    pub limit: Option<i64>,
+}
+
+/// Audit entries, newest first.
+pub async fn get_audit_logs(
+    pool: web::Data<DbPool>,
+    query: web::Query<AuditLogQuery>,
+) -> HttpResponse {
+    let query = query.into_inner();
+    let page = query.page.unwrap_or(1).max(1);
+    let limit = query.limit.unwrap_or(50).clamp(1, 200);
+    let offset = (page - 1) * limit;
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => {
+            log::error!("Failed to get database connection: {:?}", e);
+            return HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Database connection error"
+            }));
+        }
+    };
+
+    let result = web::block(move || {
+        let mut logs_query = audit_logs::table
+            .order((audit_logs::created_at.desc(), audit_logs::id.desc()))
+            .into_boxed();
+
+        if let Some(user_id) = query.user_id {
+            logs_query = logs_query.filter(audit_logs::user_id.eq(user_id));
+        }
+        if let Some(action) = query.action {
+            logs_query = logs_query.filter(audit_logs::action.eq(action));
+        }
+        if let Some(entity_type) = query.entity_type {
+            logs_query = logs_query.filter(audit_logs::entity_type.eq(entity_type));
+        }
+        if let Some(entity_id) = query.entity_id {
+            logs_query = logs_query.filter(audit_logs::entity_id.eq(entity_id));
+        }
+
+        logs_query
+            .limit(limit)
+            .offset(offset)
+            .load::<AuditLog>(&mut conn)
+    })
+    .await;
+
+    match result {
+        Ok(db_result) => match db_result {
+            Ok(logs) => HttpResponse::Ok().json(json!({
+                "status": "success",
+                "data": logs
+            })),
+            Err(db_err) => {
+                log::error!("Database error fetching audit logs: {:?}", db_err);
+                HttpResponse::InternalServerError().json(json!({
+                    "status": "error",
+                    "message": "Failed to fetch audit logs"
+                }))
+            }
+        },
+        Err(blocking_err) => {
+            log::error!("Blocking error: {:?}", blocking_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Internal server error"
+            }))
+        }
+    }
+}
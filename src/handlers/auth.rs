@@ -1,13 +1,18 @@
 use actix_web::{web, HttpResponse};
-use chrono::{Duration, Utc};
+use chrono::Utc;
 use diesel::prelude::*;
+use diesel::result::Error as DieselError;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 use validator::Validate;
 
+use crate::config::AuthConfig;
+use crate::middleware::AuthenticatedUser;
 use crate::models::role::Role;
 use crate::models::user::{NewUser, User};
-use crate::schema::{roles, users};
+use crate::models::AuditContext;
+use crate::schema::{audit_logs, roles, users};
+use crate::services::tokens::{self, ClientInfo, IssuedTokens, TokenDenylist, TokenError};
 use crate::DbPool;
 
 #[derive(Debug, Deserialize, Validate)]
@@ -18,6 +23,13 @@ pub struct LoginRequest {
     pub password: String,
 }
 
+#[derive(Debug, Deserialize, Validate)]
+pub struct RefreshRequest {
+    #[serde(rename = "refreshToken")]
+    #[validate(length(min = 1))]
+    pub refresh_token: String,
+}
+
 #[derive(Debug, Deserialize, Validate)]
 pub struct RegisterRequest {
     #[validate(length(min = 3, max = 100))]
@@ -28,7 +40,6 @@ pub struct RegisterRequest {
     pub password: String,
     pub first_name: Option<String>,
     pub last_name: Option<String>,
-    pub role_id: i32,
 }
 
 #[derive(Debug, Serialize)]
@@ -36,9 +47,26 @@ pub struct AuthResponse {
     pub user: UserResponse,
     #[serde(rename = "accessToken")]
     pub access_token: String,
+    #[serde(rename = "refreshToken")]
+    pub refresh_token: String,
+    // Seconds until the access token expires
+    #[serde(rename = "expiresIn")]
+    pub expires_in: u64,
     pub role: String,
 }
 
+impl AuthResponse {
+    fn new(user: User, role: Role, tokens: IssuedTokens) -> Self {
+        AuthResponse {
+            user: UserResponse::from(user),
+            access_token: tokens.access_token,
+            refresh_token: tokens.refresh_token,
+            expires_in: tokens.expires_in,
+            role: role.name,
+        }
+    }
+}
+
 #[derive(Debug, Serialize)]
 pub struct UserResponse {
     pub id: i32,
@@ -64,8 +92,35 @@ impl From<User> for UserResponse {
     }
 }
 
-pub async fn login(pool: web::Data<DbPool>, login_req: web::Json<LoginRequest>) -> HttpResponse {
+/// The device and address a request comes from, recorded on sessions.
+fn client_info(context: &AuditContext) -> ClientInfo {
+    ClientInfo {
+        user_agent: context.user_agent.clone(),
+        ip_address: context.ip_address.clone(),
+    }
+}
+
+// Login reports failures as diesel errors, like the password hashing in
+// `register`.
+fn login_token_error(error: TokenError) -> DieselError {
+    match error {
+        TokenError::Database(db_err) => db_err,
+        other => DieselError::DatabaseError(
+            diesel::result::DatabaseErrorKind::Unknown,
+            Box::new(other.to_string()),
+        ),
+    }
+}
+
+pub async fn login(
+    audit: AuditContext,
+    pool: web::Data<DbPool>,
+    config: web::Data<AuthConfig>,
+    login_req: web::Json<LoginRequest>,
+) -> HttpResponse {
     let login_req = login_req.into_inner();
+    let config = config.into_inner();
+    let client = client_info(&audit);
 
     if let Err(errors) = login_req.validate() {
         log::error!("Login validation errors: {:?}", errors);
@@ -102,30 +157,20 @@ pub async fn login(pool: web::Data<DbPool>, login_req: web::Json<LoginRequest>)
                 // Get user role
                 let role = roles::table.find(user.role_id).first::<Role>(&mut *conn)?;
 
-                // Generate JWT token
-                let secret =
-                    std::env::var("JWT_SECRET").unwrap_or_else(|_| "default_secret".to_string());
-                let expiration = (Utc::now() + Duration::hours(24)).timestamp() as usize;
-
-                let token = user
-                    .generate_token(&secret, &role.name, expiration)
-                    .map_err(|e| {
-                        diesel::result::Error::DatabaseError(
-                            diesel::result::DatabaseErrorKind::Unknown,
-                            Box::new(format!("Token generation failed: {}", e)),
-                        )
-                    })?;
-
-                // Update last login
-                diesel::update(users::table.find(user.id))
-                    .set(users::last_login.eq(Some(Utc::now())))
-                    .execute(&mut *conn)?;
-
-                Ok(AuthResponse {
-                    user: UserResponse::from(user),
-                    access_token: token,
-                    role: role.name,
-                })
+                // Open a session with an access token and a refresh token and
+                // record the login, together or not at all
+                let issued = conn.transaction::<_, DieselError, _>(|conn| {
+                    let issued = tokens::issue(conn, &config, &user, &role.name, client)
+                        .map_err(login_token_error)?;
+
+                    diesel::update(users::table.find(user.id))
+                        .set(users::last_login.eq(Some(Utc::now())))
+                        .execute(conn)?;
+
+                    Ok(issued)
+                })?;
+
+                Ok(AuthResponse::new(user, role, issued))
             } else {
                 Err(diesel::result::Error::NotFound)
             }
@@ -166,6 +211,159 @@ pub async fn login(pool: web::Data<DbPool>, login_req: web::Json<LoginRequest>)
     }
 }
 
+/// Exchanges a refresh token for a new access token and refresh token. The
+/// presented token is spent; presenting it again revokes the session it was
+/// issued to.
+pub async fn refresh(
+    audit: AuditContext,
+    pool: web::Data<DbPool>,
+    config: web::Data<AuthConfig>,
+    denylist: web::Data<TokenDenylist>,
+    refresh_req: web::Json<RefreshRequest>,
+) -> HttpResponse {
+    let refresh_req = refresh_req.into_inner();
+    let config = config.into_inner();
+    let client = client_info(&audit);
+
+    if let Err(errors) = refresh_req.validate() {
+        return HttpResponse::BadRequest().json(json!({
+            "status": "error",
+            "message": "Validation failed",
+            "errors": errors
+        }));
+    }
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => {
+            log::error!("Failed to get database connection: {:?}", e);
+            return HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Database connection error"
+            }));
+        }
+    };
+
+    let result = web::block(move || {
+        let rotated = conn
+            .transaction(|conn| tokens::rotate(conn, &config, &refresh_req.refresh_token, client));
+        match rotated {
+            Err(TokenError::RefreshTokenReused {
+                user_id,
+                session_id,
+            }) => {
+                // The rotation rolled back; revoke the session on its own
+                let (_, denied) = conn.transaction::<_, DieselError, _>(|conn| {
+                    let revoked = tokens::revoke_sessions(conn, &[session_id])?;
+                    let audit = AuditContext {
+                        user_id: Some(user_id),
+                        ..audit
+                    };
+                    diesel::insert_into(audit_logs::table)
+                        .values(&audit.activity(
+                            "auth.refresh_reuse",
+                            "user_session",
+                            Some(session_id),
+                            None,
+                        ))
+                        .execute(conn)?;
+                    Ok(revoked)
+                })?;
+                denylist.extend(denied);
+                Err(TokenError::RefreshTokenReused {
+                    user_id,
+                    session_id,
+                })
+            }
+            result => result,
+        }
+    })
+    .await;
+
+    match result {
+        Ok(db_result) => match db_result {
+            Ok((user, role, issued)) => {
+                HttpResponse::Ok().json(AuthResponse::new(user, role, issued))
+            }
+            Err(TokenError::InvalidRefreshToken) => HttpResponse::Unauthorized().json(json!({
+                "status": "error",
+                "message": "Invalid or expired refresh token"
+            })),
+            Err(TokenError::RefreshTokenReused { user_id, .. }) => {
+                log::warn!("Refresh token reused for user {}", user_id);
+                HttpResponse::Unauthorized().json(json!({
+                    "status": "error",
+                    "message": "Refresh token has already been used; sign in again"
+                }))
+            }
+            Err(err) => {
+                log::error!("Error refreshing token: {:?}", err);
+                HttpResponse::InternalServerError().json(json!({
+                    "status": "error",
+                    "message": "Failed to refresh token"
+                }))
+            }
+        },
+        Err(blocking_err) => {
+            log::error!("Blocking error during token refresh: {:?}", blocking_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Internal server error"
+            }))
+        }
+    }
+}
+
+/// Signs out of the caller's session: its refresh token stops working and
+/// its access tokens, including the one presented, are denied.
+pub async fn logout(
+    user: AuthenticatedUser,
+    pool: web::Data<DbPool>,
+    denylist: web::Data<TokenDenylist>,
+) -> HttpResponse {
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => {
+            log::error!("Failed to get database connection: {:?}", e);
+            return HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Database connection error"
+            }));
+        }
+    };
+
+    let result = web::block(move || {
+        conn.transaction(|conn| tokens::revoke_sessions(conn, &[user.session_id]))
+    })
+    .await;
+
+    match result {
+        Ok(db_result) => match db_result {
+            Ok((_, denied)) => {
+                denylist.extend(denied);
+                HttpResponse::Ok().json(json!({
+                    "status": "success",
+                    "message": "Logged out"
+                }))
+            }
+            Err(db_err) => {
+                log::error!("Database error during logout: {:?}", db_err);
+                HttpResponse::InternalServerError().json(json!({
+                    "status": "error",
+                    "message": "Failed to log out"
+                }))
+            }
+        },
+        Err(blocking_err) => {
+            log::error!("Blocking error during logout: {:?}", blocking_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Internal server error"
+            }))
+        }
+    }
+}
+
 pub async fn register(
     pool: web::Data<DbPool>,
     register_req: web::Json<RegisterRequest>,
@@ -222,14 +420,21 @@ pub async fn register(
             )
         })?;
 
+        // New accounts always start as plain users; roles are changed
+        // through the role-management endpoints
+        let role_id = roles::table
+            .filter(roles::name.eq("user"))
+            .select(roles::id)
+            .first::<i32>(&mut *conn)?;
+
         // Create new user
         let new_user = NewUser {
             username: register_req.username,
             email: register_req.email,
-            password_hash: password_hash,
+            password_hash,
             first_name: register_req.first_name,
             last_name: register_req.last_name,
-            role_id: register_req.role_id,
+            role_id,
         };
 
         let user = diesel::insert_into(users::table)
@@ -0,0 +1,339 @@
+use actix_web::{web, HttpResponse};
+use diesel::prelude::*;
+use diesel::result::{DatabaseErrorKind, Error as DieselError};
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::handlers::enrollment::{registration_error_details, registration_error_response};
+use crate::middleware::AuthenticatedUser;
+use crate::models::cart::{CartItem, CartItemDetail, NewCartItem};
+use crate::models::{AuditContext, Term};
+use crate::schema::cart_items;
+use crate::services::cart::{self, CartCheck, CartError};
+use crate::services::credit_load;
+use crate::services::registration::{self, RegistrationError};
+use crate::DbPool;
+
+#[derive(Debug, Deserialize)]
+pub struct CartQuery {
+    pub term: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CartItemRequest {
+    pub section_id: i32,
+}
+
+fn resolve_term(conn: &mut PgConnection, term_key: Option<&str>) -> Result<Term, CartError> {
+    Term::resolve(conn, term_key.unwrap_or("current")).map_err(|e| match e {
+        DieselError::NotFound => RegistrationError::TermNotFound.into(),
+        other => other.into(),
+    })
+}
+
+fn check_json(check: &CartCheck) -> serde_json::Value {
+    json!({
+        "cart_item_id": check.item.id,
+        "section_id": check.item.section_id,
+        "passed": check.passed(),
+        "error": check.result.as_ref().err().map(registration_error_details),
+    })
+}
+
+pub async fn get_cart(
+    _user: AuthenticatedUser,
+    pool: web::Data<DbPool>,
+    path: web::Path<i32>,
+    query: web::Query<CartQuery>,
+) -> HttpResponse {
+    let student_id = path.into_inner();
+    let query = query.into_inner();
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => {
+            log::error!("Failed to get database connection: {:?}", e);
+            return HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Database connection error"
+            }));
+        }
+    };
+
+    let result = web::block(move || {
+        registration::find_student(&mut conn, student_id)?;
+        let term = resolve_term(&mut conn, query.term.as_deref())?;
+        let items = cart::items(&mut conn, student_id, &term)?;
+        Ok::<_, CartError>(CartItemDetail::load(&mut conn, items)?)
+    })
+    .await;
+
+    match result {
+        Ok(db_result) => match db_result {
+            Ok(items) => HttpResponse::Ok().json(json!({
+                "status": "success",
+                "data": items
+            })),
+            Err(err) => cart_error_response(err),
+        },
+        Err(blocking_err) => {
+            log::error!("Blocking error: {:?}", blocking_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Internal server error"
+            }))
+        }
+    }
+}
+
+pub async fn add_cart_item(
+    _user: AuthenticatedUser,
+    pool: web::Data<DbPool>,
+    path: web::Path<i32>,
+    item_req: web::Json<CartItemRequest>,
+) -> HttpResponse {
+    let student_id = path.into_inner();
+    let section_id = item_req.into_inner().section_id;
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => {
+            log::error!("Failed to get database connection: {:?}", e);
+            return HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Database connection error"
+            }));
+        }
+    };
+
+    let result = web::block(move || {
+        registration::find_student(&mut conn, student_id)?;
+        registration::find_section_with_term(&mut conn, section_id)?;
+
+        diesel::insert_into(cart_items::table)
+            .values(&NewCartItem {
+                student_id,
+                section_id,
+            })
+            .get_result::<CartItem>(&mut conn)
+            .map_err(|e| match e {
+                DieselError::DatabaseError(DatabaseErrorKind::UniqueViolation, _) => {
+                    CartError::AlreadyInCart
+                }
+                other => other.into(),
+            })
+    })
+    .await;
+
+    match result {
+        Ok(db_result) => match db_result {
+            Ok(item) => {
+                log::info!(
+                    "Added section {} to the cart of student {}",
+                    item.section_id,
+                    item.student_id
+                );
+                HttpResponse::Created().json(json!({
+                    "status": "success",
+                    "data": item
+                }))
+            }
+            Err(err) => cart_error_response(err),
+        },
+        Err(blocking_err) => {
+            log::error!("Blocking error: {:?}", blocking_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Internal server error"
+            }))
+        }
+    }
+}
+
+pub async fn remove_cart_item(
+    _user: AuthenticatedUser,
+    pool: web::Data<DbPool>,
+    path: web::Path<(i32, i32)>,
+) -> HttpResponse {
+    let (student_id, item_id) = path.into_inner();
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => {
+            log::error!("Failed to get database connection: {:?}", e);
+            return HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Database connection error"
+            }));
+        }
+    };
+
+    let result = web::block(move || {
+        let deleted = diesel::delete(
+            cart_items::table
+                .filter(cart_items::id.eq(item_id))
+                .filter(cart_items::student_id.eq(student_id)),
+        )
+        .execute(&mut conn)?;
+        match deleted {
+            0 => Err(CartError::ItemNotFound),
+            _ => Ok(()),
+        }
+    })
+    .await;
+
+    match result {
+        Ok(db_result) => match db_result {
+            Ok(()) => HttpResponse::Ok().json(json!({
+                "status": "success",
+                "message": "Section removed from the cart"
+            })),
+            Err(err) => cart_error_response(err),
+        },
+        Err(blocking_err) => {
+            log::error!("Blocking error: {:?}", blocking_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Internal server error"
+            }))
+        }
+    }
+}
+
+/// Runs the registration checks for every section in the cart and reports
+/// each result. Nothing is enrolled.
+pub async fn validate_cart(
+    _user: AuthenticatedUser,
+    audit: AuditContext,
+    pool: web::Data<DbPool>,
+    path: web::Path<i32>,
+    query: web::Query<CartQuery>,
+) -> HttpResponse {
+    let student_id = path.into_inner();
+    let query = query.into_inner();
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => {
+            log::error!("Failed to get database connection: {:?}", e);
+            return HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Database connection error"
+            }));
+        }
+    };
+
+    let result = web::block(move || {
+        let term = resolve_term(&mut conn, query.term.as_deref())?;
+        cart::validate(&mut conn, student_id, &term, &audit)
+    })
+    .await;
+
+    match result {
+        Ok(db_result) => match db_result {
+            Ok(checks) => HttpResponse::Ok().json(json!({
+                "status": "success",
+                "data": {
+                    "valid": checks.iter().all(CartCheck::passed),
+                    "items": checks.iter().map(check_json).collect::<Vec<_>>()
+                }
+            })),
+            Err(err) => cart_error_response(err),
+        },
+        Err(blocking_err) => {
+            log::error!("Blocking error: {:?}", blocking_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Internal server error"
+            }))
+        }
+    }
+}
+
+/// Enrolls the student in every section of the cart, or in none of them if
+/// any fails a check.
+pub async fn submit_cart(
+    _user: AuthenticatedUser,
+    audit: AuditContext,
+    pool: web::Data<DbPool>,
+    path: web::Path<i32>,
+    query: web::Query<CartQuery>,
+) -> HttpResponse {
+    let student_id = path.into_inner();
+    let query = query.into_inner();
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => {
+            log::error!("Failed to get database connection: {:?}", e);
+            return HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Database connection error"
+            }));
+        }
+    };
+
+    let result = web::block(move || {
+        conn.transaction::<_, CartError, _>(|conn| {
+            let term = resolve_term(conn, query.term.as_deref())?;
+            let enrollments = cart::submit(conn, student_id, &term, &audit)?;
+            let warnings = match enrollments.first() {
+                Some(enrollment) => {
+                    credit_load::warnings_for_section(conn, student_id, enrollment.section_id)?
+                }
+                None => Vec::new(),
+            };
+            Ok((enrollments, warnings))
+        })
+    })
+    .await;
+
+    match result {
+        Ok(db_result) => match db_result {
+            Ok((enrollments, warnings)) => {
+                log::info!(
+                    "Enrolled student {} in {} sections from the cart",
+                    student_id,
+                    enrollments.len()
+                );
+                HttpResponse::Created().json(json!({
+                    "status": "success",
+                    "data": enrollments,
+                    "warnings": warnings
+                }))
+            }
+            Err(err) => cart_error_response(err),
+        },
+        Err(blocking_err) => {
+            log::error!("Blocking error: {:?}", blocking_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Internal server error"
+            }))
+        }
+    }
+}
+
+fn cart_error_response(err: CartError) -> HttpResponse {
+    let message = err.to_string();
+    match err {
+        CartError::ItemNotFound => HttpResponse::NotFound().json(json!({
+            "status": "error",
+            "message": message
+        })),
+        CartError::AlreadyInCart => HttpResponse::Conflict().json(json!({
+            "status": "error",
+            "message": message
+        })),
+        CartError::Empty(_) => HttpResponse::UnprocessableEntity().json(json!({
+            "status": "error",
+            "message": message
+        })),
+        CartError::Rejected(checks) => HttpResponse::UnprocessableEntity().json(json!({
+            "status": "error",
+            "message": message,
+            "items": checks.iter().map(check_json).collect::<Vec<_>>()
+        })),
+        CartError::Registration(err) => registration_error_response(err),
+    }
+}
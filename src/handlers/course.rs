@@ -0,0 +1,465 @@
+use actix_web::{web, HttpResponse};
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel::result::{DatabaseErrorKind, Error as DieselError};
+use serde::Deserialize;
+use serde_json::json;
+use validator::Validate;
+
+use crate::handlers::enrollment::registration_error_response;
+use crate::models::course::{normalize_code, normalize_requisites};
+use crate::models::{Course, NewCourse, Term, UpdateCourse};
+use crate::schema::courses;
+use crate::services::registration::{self, RegistrationError};
+use crate::services::requisites;
+use crate::DbPool;
+
+enum CourseError {
+    UnknownGrades(Vec<String>),
+    Database(DieselError),
+}
+
+impl From<DieselError> for CourseError {
+    fn from(error: DieselError) -> Self {
+        CourseError::Database(error)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RequisiteCheckQuery {
+    pub student_id: i32,
+    pub term: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CourseQuery {
+    pub page: Option<i64>,
+    pub limit: Option<i64>,
+    pub department: Option<String>,
+    pub active: Option<bool>,
+}
+
+pub async fn get_courses(pool: web::Data<DbPool>, query: web::Query<CourseQuery>) -> HttpResponse {
+    let query = query.into_inner();
+    let page = query.page.unwrap_or(1);
+    let limit = query.limit.unwrap_or(50);
+    let offset = (page - 1) * limit;
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => {
+            log::error!("Failed to get database connection: {:?}", e);
+            return HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Database connection error"
+            }));
+        }
+    };
+
+    let result = web::block(move || {
+        let mut courses_query = courses::table.order(courses::code.asc()).into_boxed();
+
+        if let Some(department) = query.department {
+            courses_query = courses_query.filter(courses::department.eq(department));
+        }
+        if let Some(active) = query.active {
+            courses_query = courses_query.filter(courses::is_active.eq(active));
+        }
+
+        courses_query
+            .limit(limit)
+            .offset(offset)
+            .load::<Course>(&mut *conn)
+    })
+    .await;
+
+    match result {
+        Ok(db_result) => match db_result {
+            Ok(courses) => HttpResponse::Ok().json(json!({
+                "status": "success",
+                "data": courses
+            })),
+            Err(db_err) => {
+                log::error!("Database error fetching courses: {:?}", db_err);
+                HttpResponse::InternalServerError().json(json!({
+                    "status": "error",
+                    "message": "Failed to fetch courses"
+                }))
+            }
+        },
+        Err(blocking_err) => {
+            log::error!("Blocking error: {:?}", blocking_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Internal server error"
+            }))
+        }
+    }
+}
+
+pub async fn get_course(pool: web::Data<DbPool>, path: web::Path<i32>) -> HttpResponse {
+    let course_id = path.into_inner();
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => {
+            log::error!("Failed to get database connection: {:?}", e);
+            return HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Database connection error"
+            }));
+        }
+    };
+
+    let result = web::block(move || courses::table.find(course_id).first::<Course>(&mut *conn)).await;
+
+    match result {
+        Ok(db_result) => match db_result {
+            Ok(course) => HttpResponse::Ok().json(json!({
+                "status": "success",
+                "data": course
+            })),
+            Err(DieselError::NotFound) => course_not_found(course_id),
+            Err(db_err) => {
+                log::error!("Database error fetching course {}: {:?}", course_id, db_err);
+                HttpResponse::InternalServerError().json(json!({
+                    "status": "error",
+                    "message": "Failed to fetch course"
+                }))
+            }
+        },
+        Err(blocking_err) => {
+            log::error!("Blocking error: {:?}", blocking_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Internal server error"
+            }))
+        }
+    }
+}
+
+pub async fn create_course(
+    pool: web::Data<DbPool>,
+    new_course: web::Json<NewCourse>,
+) -> HttpResponse {
+    let mut new_course = new_course.into_inner();
+    new_course.code = normalize_code(&new_course.code);
+    new_course.prerequisites = normalize_requisites(new_course.prerequisites);
+    new_course.corequisites = normalize_requisites(new_course.corequisites);
+
+    if let Err(errors) = new_course.validate() {
+        log::error!("Validation errors: {:?}", errors);
+        return HttpResponse::BadRequest().json(json!({
+            "status": "error",
+            "message": "Validation failed",
+            "errors": errors
+        }));
+    }
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => {
+            log::error!("Failed to get database connection: {:?}", e);
+            return HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Database connection error"
+            }));
+        }
+    };
+
+    let result = web::block(move || {
+        let expressions = [&new_course.prerequisites, &new_course.corequisites];
+        ensure_known_grades(&mut conn, expressions.map(Option::as_ref))?;
+        let course = diesel::insert_into(courses::table)
+            .values(&new_course)
+            .get_result::<Course>(&mut *conn)?;
+        Ok(course)
+    })
+    .await;
+
+    match result {
+        Ok(db_result) => match db_result {
+            Ok(course) => {
+                log::info!("Successfully created course: {}", course.code);
+                HttpResponse::Created().json(json!({
+                    "status": "success",
+                    "data": course
+                }))
+            }
+            Err(CourseError::UnknownGrades(grades)) => unknown_grades(&grades),
+            Err(CourseError::Database(DieselError::DatabaseError(
+                DatabaseErrorKind::UniqueViolation,
+                _,
+            ))) => duplicate_course_code(),
+            Err(CourseError::Database(db_err)) => {
+                log::error!("Database error creating course: {:?}", db_err);
+                HttpResponse::InternalServerError().json(json!({
+                    "status": "error",
+                    "message": "Failed to create course"
+                }))
+            }
+        },
+        Err(blocking_err) => {
+            log::error!("Blocking error: {:?}", blocking_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Internal server error"
+            }))
+        }
+    }
+}
+
+pub async fn update_course(
+    pool: web::Data<DbPool>,
+    path: web::Path<i32>,
+    course: web::Json<NewCourse>,
+) -> HttpResponse {
+    let mut course = course.into_inner();
+    course.code = normalize_code(&course.code);
+    course.prerequisites = normalize_requisites(course.prerequisites);
+    course.corequisites = normalize_requisites(course.corequisites);
+
+    if let Err(errors) = course.validate() {
+        log::error!("Validation errors: {:?}", errors);
+        return HttpResponse::BadRequest().json(json!({
+            "status": "error",
+            "message": "Validation failed",
+            "errors": errors
+        }));
+    }
+
+    save_course_changes(pool, path.into_inner(), course.into()).await
+}
+
+pub async fn patch_course(
+    pool: web::Data<DbPool>,
+    path: web::Path<i32>,
+    changes: web::Json<UpdateCourse>,
+) -> HttpResponse {
+    let mut changes = changes.into_inner();
+    changes.code = changes.code.as_deref().map(normalize_code);
+    changes.prerequisites = changes.prerequisites.map(normalize_requisites);
+    changes.corequisites = changes.corequisites.map(normalize_requisites);
+
+    if let Err(errors) = changes.validate() {
+        log::error!("Validation errors: {:?}", errors);
+        return HttpResponse::BadRequest().json(json!({
+            "status": "error",
+            "message": "Validation failed",
+            "errors": errors
+        }));
+    }
+
+    save_course_changes(pool, path.into_inner(), changes).await
+}
+
+pub async fn delete_course(pool: web::Data<DbPool>, path: web::Path<i32>) -> HttpResponse {
+    let course_id = path.into_inner();
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => {
+            log::error!("Failed to get database connection: {:?}", e);
+            return HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Database connection error"
+            }));
+        }
+    };
+
+    let result =
+        web::block(move || diesel::delete(courses::table.find(course_id)).execute(&mut *conn))
+            .await;
+
+    match result {
+        Ok(db_result) => match db_result {
+            Ok(0) => course_not_found(course_id),
+            Ok(_) => {
+                log::info!("Successfully deleted course {}", course_id);
+                HttpResponse::Ok().json(json!({
+                    "status": "success",
+                    "message": "Course deleted successfully"
+                }))
+            }
+            Err(DieselError::DatabaseError(DatabaseErrorKind::ForeignKeyViolation, _)) => {
+                HttpResponse::Conflict().json(json!({
+                    "status": "error",
+                    "message": "Course is still in use; deactivate it instead"
+                }))
+            }
+            Err(db_err) => {
+                log::error!("Database error deleting course {}: {:?}", course_id, db_err);
+                HttpResponse::InternalServerError().json(json!({
+                    "status": "error",
+                    "message": "Failed to delete course"
+                }))
+            }
+        },
+        Err(blocking_err) => {
+            log::error!("Blocking error: {:?}", blocking_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Internal server error"
+            }))
+        }
+    }
+}
+
+async fn save_course_changes(
+    pool: web::Data<DbPool>,
+    course_id: i32,
+    changes: UpdateCourse,
+) -> HttpResponse {
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => {
+            log::error!("Failed to get database connection: {:?}", e);
+            return HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Database connection error"
+            }));
+        }
+    };
+
+    let result = web::block(move || {
+        let expressions = [&changes.prerequisites, &changes.corequisites];
+        ensure_known_grades(&mut conn, expressions.map(|e| e.as_ref()?.as_ref()))?;
+        let course = diesel::update(courses::table.find(course_id))
+            .set((&changes, courses::updated_at.eq(Utc::now())))
+            .get_result::<Course>(&mut *conn)?;
+        Ok(course)
+    })
+    .await;
+
+    match result {
+        Ok(db_result) => match db_result {
+            Ok(course) => {
+                log::info!("Successfully updated course: {}", course.code);
+                HttpResponse::Ok().json(json!({
+                    "status": "success",
+                    "data": course
+                }))
+            }
+            Err(CourseError::UnknownGrades(grades)) => unknown_grades(&grades),
+            Err(CourseError::Database(DieselError::NotFound)) => course_not_found(course_id),
+            Err(CourseError::Database(DieselError::DatabaseError(
+                DatabaseErrorKind::UniqueViolation,
+                _,
+            ))) => duplicate_course_code(),
+            Err(CourseError::Database(db_err)) => {
+                log::error!("Database error updating course {}: {:?}", course_id, db_err);
+                HttpResponse::InternalServerError().json(json!({
+                    "status": "error",
+                    "message": "Failed to update course"
+                }))
+            }
+        },
+        Err(blocking_err) => {
+            log::error!("Blocking error: {:?}", blocking_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Internal server error"
+            }))
+        }
+    }
+}
+
+/// Dry-runs the prerequisite and corequisite check for a student without
+/// enrolling them. Defaults to the current term.
+pub async fn check_requisites(
+    pool: web::Data<DbPool>,
+    path: web::Path<i32>,
+    query: web::Query<RequisiteCheckQuery>,
+) -> HttpResponse {
+    let course_id = path.into_inner();
+    let query = query.into_inner();
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => {
+            log::error!("Failed to get database connection: {:?}", e);
+            return HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Database connection error"
+            }));
+        }
+    };
+
+    let result = web::block(move || {
+        registration::find_student(&mut conn, query.student_id)?;
+        let course = courses::table
+            .find(course_id)
+            .first::<Course>(&mut conn)
+            .optional()?
+            .ok_or(RegistrationError::CourseNotFound)?;
+        let term = Term::resolve(&mut conn, query.term.as_deref().unwrap_or("current"))
+            .optional()?
+            .ok_or(RegistrationError::TermNotFound)?;
+
+        Ok(requisites::evaluate(
+            &mut conn,
+            query.student_id,
+            &course,
+            &term,
+        )?)
+    })
+    .await;
+
+    match result {
+        Ok(db_result) => match db_result {
+            Ok(report) => HttpResponse::Ok().json(json!({
+                "status": "success",
+                "data": report
+            })),
+            Err(err) => registration_error_response(err),
+        },
+        Err(blocking_err) => {
+            log::error!("Blocking error: {:?}", blocking_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Internal server error"
+            }))
+        }
+    }
+}
+
+fn course_not_found(course_id: i32) -> HttpResponse {
+    log::warn!("Course {} not found", course_id);
+    HttpResponse::NotFound().json(json!({
+        "status": "error",
+        "message": "Course not found"
+    }))
+}
+
+fn duplicate_course_code() -> HttpResponse {
+    HttpResponse::Conflict().json(json!({
+        "status": "error",
+        "message": "A course with this code already exists"
+    }))
+}
+
+/// Rejects minimum grades the default grade scale cannot compare against, so
+/// a typo cannot leave a requisite nobody can meet.
+fn ensure_known_grades(
+    conn: &mut PgConnection,
+    expressions: [Option<&String>; 2],
+) -> Result<(), CourseError> {
+    let mut unknown = Vec::new();
+    for expression in expressions.into_iter().flatten() {
+        unknown.extend(requisites::unknown_grades(conn, expression)?);
+    }
+    if !unknown.is_empty() {
+        return Err(CourseError::UnknownGrades(unknown));
+    }
+    Ok(())
+}
+
+fn unknown_grades(grades: &[String]) -> HttpResponse {
+    HttpResponse::BadRequest().json(json!({
+        "status": "error",
+        "message": format!(
+            "Minimum grades not on the default grade scale: {}",
+            grades.join(", ")
+        )
+    }))
+}
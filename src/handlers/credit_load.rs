@@ -0,0 +1,542 @@
+use actix_web::{web, HttpResponse};
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel::result::{DatabaseErrorKind, Error as DieselError};
+use serde::Deserialize;
+use serde_json::json;
+use validator::Validate;
+
+use crate::models::credit_load::{CreditLimit, CreditOverload, NewCreditLimit, NewCreditOverload};
+use crate::models::{AuditContext, Student, Term};
+use crate::schema::{audit_logs, credit_limits, credit_overloads, students};
+use crate::services::credit_load;
+use crate::DbPool;
+
+#[derive(Debug, Deserialize)]
+pub struct CreditLoadQuery {
+    pub term: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct OverloadRequest {
+    #[validate(length(min = 1, max = 20))]
+    pub term: String,
+    #[validate(range(min = 1, max = 100))]
+    pub max_credits: i32,
+    #[validate(length(min = 1))]
+    pub reason: String,
+}
+
+enum CreditLoadError {
+    StudentNotFound,
+    TermNotFound,
+    LimitNotFound,
+    OverloadNotFound,
+    OverloadNotNeeded(i32),
+    NoMaximumToExceed,
+    Database(DieselError),
+}
+
+impl From<DieselError> for CreditLoadError {
+    fn from(error: DieselError) -> Self {
+        CreditLoadError::Database(error)
+    }
+}
+
+fn find_student(conn: &mut PgConnection, student_id: i32) -> Result<Student, CreditLoadError> {
+    students::table
+        .find(student_id)
+        .first::<Student>(conn)
+        .optional()?
+        .ok_or(CreditLoadError::StudentNotFound)
+}
+
+fn find_term(conn: &mut PgConnection, term_key: &str) -> Result<Term, CreditLoadError> {
+    Term::resolve(conn, term_key)
+        .optional()?
+        .ok_or(CreditLoadError::TermNotFound)
+}
+
+pub async fn get_credit_limits(pool: web::Data<DbPool>) -> HttpResponse {
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => {
+            log::error!("Failed to get database connection: {:?}", e);
+            return HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Database connection error"
+            }));
+        }
+    };
+
+    let result = web::block(move || {
+        credit_limits::table
+            .order((
+                credit_limits::level.asc(),
+                credit_limits::academic_standing.asc().nulls_first(),
+            ))
+            .load::<CreditLimit>(&mut conn)
+    })
+    .await;
+
+    match result {
+        Ok(db_result) => match db_result {
+            Ok(limits) => HttpResponse::Ok().json(json!({
+                "status": "success",
+                "data": limits
+            })),
+            Err(db_err) => {
+                log::error!("Database error fetching credit limits: {:?}", db_err);
+                HttpResponse::InternalServerError().json(json!({
+                    "status": "error",
+                    "message": "Failed to fetch credit limits"
+                }))
+            }
+        },
+        Err(blocking_err) => {
+            log::error!("Blocking error: {:?}", blocking_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Internal server error"
+            }))
+        }
+    }
+}
+
+pub async fn create_credit_limit(
+    pool: web::Data<DbPool>,
+    limit_req: web::Json<NewCreditLimit>,
+) -> HttpResponse {
+    let limit_req = limit_req.into_inner();
+    if let Err(errors) = limit_req.validate() {
+        log::error!("Validation errors: {:?}", errors);
+        return HttpResponse::BadRequest().json(json!({
+            "status": "error",
+            "message": "Validation failed",
+            "errors": errors
+        }));
+    }
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => {
+            log::error!("Failed to get database connection: {:?}", e);
+            return HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Database connection error"
+            }));
+        }
+    };
+
+    let result = web::block(move || {
+        diesel::insert_into(credit_limits::table)
+            .values(&limit_req)
+            .get_result::<CreditLimit>(&mut conn)
+            .map_err(CreditLoadError::from)
+    })
+    .await;
+
+    match result {
+        Ok(db_result) => match db_result {
+            Ok(limit) => {
+                log::info!("Successfully created credit limit {}", limit.id);
+                HttpResponse::Created().json(json!({
+                    "status": "success",
+                    "data": limit
+                }))
+            }
+            Err(err) => credit_load_error_response(err, "Failed to create credit limit"),
+        },
+        Err(blocking_err) => {
+            log::error!("Blocking error: {:?}", blocking_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Internal server error"
+            }))
+        }
+    }
+}
+
+pub async fn update_credit_limit(
+    pool: web::Data<DbPool>,
+    path: web::Path<i32>,
+    limit_req: web::Json<NewCreditLimit>,
+) -> HttpResponse {
+    let limit_id = path.into_inner();
+    let limit_req = limit_req.into_inner();
+    if let Err(errors) = limit_req.validate() {
+        log::error!("Validation errors: {:?}", errors);
+        return HttpResponse::BadRequest().json(json!({
+            "status": "error",
+            "message": "Validation failed",
+            "errors": errors
+        }));
+    }
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => {
+            log::error!("Failed to get database connection: {:?}", e);
+            return HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Database connection error"
+            }));
+        }
+    };
+
+    let result = web::block(move || {
+        diesel::update(credit_limits::table.find(limit_id))
+            .set((&limit_req, credit_limits::updated_at.eq(Utc::now())))
+            .get_result::<CreditLimit>(&mut conn)
+            .optional()?
+            .ok_or(CreditLoadError::LimitNotFound)
+    })
+    .await;
+
+    match result {
+        Ok(db_result) => match db_result {
+            Ok(limit) => {
+                log::info!("Successfully updated credit limit {}", limit.id);
+                HttpResponse::Ok().json(json!({
+                    "status": "success",
+                    "data": limit
+                }))
+            }
+            Err(err) => credit_load_error_response(err, "Failed to update credit limit"),
+        },
+        Err(blocking_err) => {
+            log::error!("Blocking error: {:?}", blocking_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Internal server error"
+            }))
+        }
+    }
+}
+
+pub async fn delete_credit_limit(pool: web::Data<DbPool>, path: web::Path<i32>) -> HttpResponse {
+    let limit_id = path.into_inner();
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => {
+            log::error!("Failed to get database connection: {:?}", e);
+            return HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Database connection error"
+            }));
+        }
+    };
+
+    let result = web::block(move || {
+        match diesel::delete(credit_limits::table.find(limit_id)).execute(&mut conn)? {
+            0 => Err(CreditLoadError::LimitNotFound),
+            _ => Ok(()),
+        }
+    })
+    .await;
+
+    match result {
+        Ok(db_result) => match db_result {
+            Ok(()) => {
+                log::info!("Successfully deleted credit limit {}", limit_id);
+                HttpResponse::Ok().json(json!({
+                    "status": "success",
+                    "message": "Credit limit deleted successfully"
+                }))
+            }
+            Err(err) => credit_load_error_response(err, "Failed to delete credit limit"),
+        },
+        Err(blocking_err) => {
+            log::error!("Blocking error: {:?}", blocking_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Internal server error"
+            }))
+        }
+    }
+}
+
+/// The student's registered credits for a term (the current one by default)
+/// with the limits that apply and any warnings.
+pub async fn get_credit_load(
+    pool: web::Data<DbPool>,
+    path: web::Path<i32>,
+    query: web::Query<CreditLoadQuery>,
+) -> HttpResponse {
+    let student_id = path.into_inner();
+    let term_key = query
+        .into_inner()
+        .term
+        .unwrap_or_else(|| "current".to_string());
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => {
+            log::error!("Failed to get database connection: {:?}", e);
+            return HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Database connection error"
+            }));
+        }
+    };
+
+    let result = web::block(move || {
+        let student = find_student(&mut conn, student_id)?;
+        let term = find_term(&mut conn, &term_key)?;
+        Ok::<_, CreditLoadError>(credit_load::load(&mut conn, &student, &term)?)
+    })
+    .await;
+
+    match result {
+        Ok(db_result) => match db_result {
+            Ok(load) => HttpResponse::Ok().json(json!({
+                "status": "success",
+                "data": load
+            })),
+            Err(err) => credit_load_error_response(err, "Failed to fetch credit load"),
+        },
+        Err(blocking_err) => {
+            log::error!("Blocking error: {:?}", blocking_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Internal server error"
+            }))
+        }
+    }
+}
+
+pub async fn get_overloads(pool: web::Data<DbPool>, path: web::Path<i32>) -> HttpResponse {
+    let student_id = path.into_inner();
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => {
+            log::error!("Failed to get database connection: {:?}", e);
+            return HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Database connection error"
+            }));
+        }
+    };
+
+    let result = web::block(move || {
+        find_student(&mut conn, student_id)?;
+        Ok::<_, CreditLoadError>(
+            credit_overloads::table
+                .filter(credit_overloads::student_id.eq(student_id))
+                .order(credit_overloads::created_at.desc())
+                .load::<CreditOverload>(&mut conn)?,
+        )
+    })
+    .await;
+
+    match result {
+        Ok(db_result) => match db_result {
+            Ok(overloads) => HttpResponse::Ok().json(json!({
+                "status": "success",
+                "data": overloads
+            })),
+            Err(err) => credit_load_error_response(err, "Failed to fetch overloads"),
+        },
+        Err(blocking_err) => {
+            log::error!("Blocking error: {:?}", blocking_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Internal server error"
+            }))
+        }
+    }
+}
+
+/// Records an advisor's approval for the student to exceed their maximum
+/// load in one term.
+pub async fn approve_overload(
+    audit: AuditContext,
+    pool: web::Data<DbPool>,
+    path: web::Path<i32>,
+    overload_req: web::Json<OverloadRequest>,
+) -> HttpResponse {
+    let student_id = path.into_inner();
+    let overload_req = overload_req.into_inner();
+    if let Err(errors) = overload_req.validate() {
+        log::error!("Validation errors: {:?}", errors);
+        return HttpResponse::BadRequest().json(json!({
+            "status": "error",
+            "message": "Validation failed",
+            "errors": errors
+        }));
+    }
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => {
+            log::error!("Failed to get database connection: {:?}", e);
+            return HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Database connection error"
+            }));
+        }
+    };
+
+    let result = web::block(move || {
+        conn.transaction::<_, CreditLoadError, _>(|conn| {
+            let student = find_student(conn, student_id)?;
+            let term = find_term(conn, &overload_req.term)?;
+            let limit = CreditLimit::for_student(conn, &student)?
+                .ok_or(CreditLoadError::NoMaximumToExceed)?;
+            if overload_req.max_credits <= limit.max_credits {
+                return Err(CreditLoadError::OverloadNotNeeded(limit.max_credits));
+            }
+
+            let overload = diesel::insert_into(credit_overloads::table)
+                .values(&NewCreditOverload {
+                    student_id,
+                    term_id: term.id,
+                    max_credits: overload_req.max_credits,
+                    reason: overload_req.reason,
+                    approved_by: audit.user_id,
+                })
+                .get_result::<CreditOverload>(conn)?;
+
+            diesel::insert_into(audit_logs::table)
+                .values(&audit.activity(
+                    "credit_overload.approve",
+                    "credit_overload",
+                    Some(overload.id),
+                    Some(json!({ "before": null, "after": overload })),
+                ))
+                .execute(conn)?;
+
+            Ok(overload)
+        })
+    })
+    .await;
+
+    match result {
+        Ok(db_result) => match db_result {
+            Ok(overload) => {
+                log::info!(
+                    "Approved overload of {} credits for student {}",
+                    overload.max_credits,
+                    overload.student_id
+                );
+                HttpResponse::Created().json(json!({
+                    "status": "success",
+                    "data": overload
+                }))
+            }
+            Err(err) => credit_load_error_response(err, "Failed to approve overload"),
+        },
+        Err(blocking_err) => {
+            log::error!("Blocking error: {:?}", blocking_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Internal server error"
+            }))
+        }
+    }
+}
+
+/// Withdraws an overload approval. Courses already taken under it stay.
+pub async fn revoke_overload(
+    audit: AuditContext,
+    pool: web::Data<DbPool>,
+    path: web::Path<(i32, i32)>,
+) -> HttpResponse {
+    let (student_id, overload_id) = path.into_inner();
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => {
+            log::error!("Failed to get database connection: {:?}", e);
+            return HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Database connection error"
+            }));
+        }
+    };
+
+    let result = web::block(move || {
+        conn.transaction::<_, CreditLoadError, _>(|conn| {
+            let overload = diesel::delete(
+                credit_overloads::table
+                    .filter(credit_overloads::id.eq(overload_id))
+                    .filter(credit_overloads::student_id.eq(student_id)),
+            )
+            .get_result::<CreditOverload>(conn)
+            .optional()?
+            .ok_or(CreditLoadError::OverloadNotFound)?;
+
+            diesel::insert_into(audit_logs::table)
+                .values(&audit.activity(
+                    "credit_overload.revoke",
+                    "credit_overload",
+                    Some(overload.id),
+                    Some(json!({ "before": overload, "after": null })),
+                ))
+                .execute(conn)?;
+            Ok(())
+        })
+    })
+    .await;
+
+    match result {
+        Ok(db_result) => match db_result {
+            Ok(()) => HttpResponse::Ok().json(json!({
+                "status": "success",
+                "message": "Overload revoked successfully"
+            })),
+            Err(err) => credit_load_error_response(err, "Failed to revoke overload"),
+        },
+        Err(blocking_err) => {
+            log::error!("Blocking error: {:?}", blocking_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Internal server error"
+            }))
+        }
+    }
+}
+
+fn credit_load_error_response(err: CreditLoadError, failure_message: &str) -> HttpResponse {
+    let not_found = |message: &str| {
+        HttpResponse::NotFound().json(json!({
+            "status": "error",
+            "message": message
+        }))
+    };
+    match err {
+        CreditLoadError::StudentNotFound => not_found("Student not found"),
+        CreditLoadError::TermNotFound => not_found("Term not found"),
+        CreditLoadError::LimitNotFound => not_found("Credit limit not found"),
+        CreditLoadError::OverloadNotFound => not_found("Overload not found"),
+        CreditLoadError::OverloadNotNeeded(max) => {
+            HttpResponse::UnprocessableEntity().json(json!({
+                "status": "error",
+                "message": format!(
+                    "An overload must allow more than the regular maximum of {} credits",
+                    max
+                )
+            }))
+        }
+        CreditLoadError::NoMaximumToExceed => HttpResponse::UnprocessableEntity().json(json!({
+            "status": "error",
+            "message": "No credit limit applies to the student, so there is no maximum to exceed"
+        })),
+        CreditLoadError::Database(DieselError::DatabaseError(
+            DatabaseErrorKind::UniqueViolation,
+            _,
+        )) => HttpResponse::Conflict().json(json!({
+            "status": "error",
+            "message": "A matching record already exists"
+        })),
+        CreditLoadError::Database(db_err) => {
+            log::error!("Database error: {:?}", db_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": failure_message
+            }))
+        }
+    }
+}
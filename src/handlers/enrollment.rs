@@ -0,0 +1,396 @@
+use actix_web::{web, HttpResponse};
+use diesel::prelude::*;
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::middleware::permission::require_permission;
+use crate::middleware::AuthenticatedUser;
+use crate::models::permission::Permission;
+use crate::models::{AuditContext, Enrollment, EnrollmentDetail, Term, WaitlistPosition};
+use crate::schema::{enrollments, sections};
+use crate::services::credit_load;
+use crate::services::registration::{self, Overrides, RegistrationError};
+use crate::services::waitlist;
+use crate::DbPool;
+
+#[derive(Debug, Deserialize)]
+pub struct EnrollmentQuery {
+    pub term: Option<String>,
+    pub status: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct EnrollmentRequest {
+    pub section_id: i32,
+    /// Join the waitlist instead of failing when the section is full.
+    #[serde(default)]
+    pub waitlist: bool,
+    /// When waitlisted, take a freed seat immediately rather than via an offer.
+    #[serde(default)]
+    pub auto_enroll: bool,
+    /// Enroll despite timetable conflicts. Administrators only.
+    #[serde(default)]
+    pub override_conflicts: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SwapRequest {
+    /// The section to move into.
+    pub section_id: i32,
+    /// Swap despite timetable conflicts. Administrators only.
+    #[serde(default)]
+    pub override_conflicts: bool,
+}
+
+enum EnrollmentOutcome {
+    Enrolled(Enrollment, Vec<String>),
+    Waitlisted(WaitlistPosition),
+}
+
+pub async fn get_enrollments(
+    pool: web::Data<DbPool>,
+    path: web::Path<i32>,
+    query: web::Query<EnrollmentQuery>,
+) -> HttpResponse {
+    let student_id = path.into_inner();
+    let query = query.into_inner();
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => {
+            log::error!("Failed to get database connection: {:?}", e);
+            return HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Database connection error"
+            }));
+        }
+    };
+
+    let result = web::block(move || {
+        registration::find_student(&mut conn, student_id)?;
+
+        let mut enrollments_query = enrollments::table
+            .inner_join(sections::table)
+            .filter(enrollments::student_id.eq(student_id))
+            .select(enrollments::all_columns)
+            .order(enrollments::enrolled_at.desc())
+            .into_boxed();
+
+        if let Some(term_key) = query.term {
+            let term = Term::resolve(&mut conn, &term_key)?;
+            enrollments_query = enrollments_query.filter(sections::term_id.eq(term.id));
+        }
+        if let Some(status) = query.status {
+            enrollments_query = enrollments_query.filter(enrollments::status.eq(status));
+        }
+
+        let enrollments = enrollments_query.load::<Enrollment>(&mut conn)?;
+        Ok::<_, RegistrationError>(EnrollmentDetail::load(&mut conn, enrollments)?)
+    })
+    .await;
+
+    match result {
+        Ok(db_result) => match db_result {
+            Ok(enrollments) => HttpResponse::Ok().json(json!({
+                "status": "success",
+                "data": enrollments
+            })),
+            Err(err) => registration_error_response(err),
+        },
+        Err(blocking_err) => {
+            log::error!("Blocking error: {:?}", blocking_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Internal server error"
+            }))
+        }
+    }
+}
+
+pub async fn create_enrollment(
+    audit: AuditContext,
+    user: AuthenticatedUser,
+    pool: web::Data<DbPool>,
+    path: web::Path<i32>,
+    enrollment_req: web::Json<EnrollmentRequest>,
+) -> HttpResponse {
+    let student_id = path.into_inner();
+    let enrollment_req = enrollment_req.into_inner();
+
+    if enrollment_req.override_conflicts {
+        let permission = Permission::ENROLLMENTS_OVERRIDE;
+        if let Err(response) = require_permission(&pool, &user, permission).await {
+            return response;
+        }
+    }
+    let overrides = Overrides {
+        schedule_conflicts: enrollment_req.override_conflicts,
+    };
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => {
+            log::error!("Failed to get database connection: {:?}", e);
+            return HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Database connection error"
+            }));
+        }
+    };
+
+    let result = web::block(move || {
+        conn.transaction(|conn| {
+            let section_id = enrollment_req.section_id;
+            match registration::enroll(conn, student_id, section_id, overrides, &audit) {
+                Ok(enrollment) => {
+                    let warnings = credit_load::warnings_for_section(conn, student_id, section_id)?;
+                    Ok(EnrollmentOutcome::Enrolled(enrollment, warnings))
+                }
+                Err(RegistrationError::SectionFull) if enrollment_req.waitlist => {
+                    let entry = waitlist::join(
+                        conn,
+                        student_id,
+                        section_id,
+                        enrollment_req.auto_enroll,
+                        &audit,
+                    )?;
+                    let position = waitlist::position(conn, &entry)?;
+                    Ok(EnrollmentOutcome::Waitlisted(WaitlistPosition {
+                        entry,
+                        position,
+                    }))
+                }
+                Err(err) => Err(err),
+            }
+        })
+    })
+    .await;
+
+    match result {
+        Ok(db_result) => match db_result {
+            Ok(EnrollmentOutcome::Enrolled(enrollment, warnings)) => {
+                log::info!(
+                    "Enrolled student {} in section {}",
+                    enrollment.student_id,
+                    enrollment.section_id
+                );
+                HttpResponse::Created().json(json!({
+                    "status": "success",
+                    "data": enrollment,
+                    "warnings": warnings
+                }))
+            }
+            Ok(EnrollmentOutcome::Waitlisted(waitlisted)) => {
+                log::info!(
+                    "Waitlisted student {} for section {} at position {:?}",
+                    waitlisted.entry.student_id,
+                    waitlisted.entry.section_id,
+                    waitlisted.position
+                );
+                HttpResponse::Accepted().json(json!({
+                    "status": "waitlisted",
+                    "message": "Section is full; student was added to the waitlist",
+                    "data": waitlisted
+                }))
+            }
+            Err(err) => registration_error_response(err),
+        },
+        Err(blocking_err) => {
+            log::error!("Blocking error: {:?}", blocking_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Internal server error"
+            }))
+        }
+    }
+}
+
+pub async fn drop_enrollment(
+    audit: AuditContext,
+    pool: web::Data<DbPool>,
+    path: web::Path<(i32, i32)>,
+) -> HttpResponse {
+    let (student_id, enrollment_id) = path.into_inner();
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => {
+            log::error!("Failed to get database connection: {:?}", e);
+            return HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Database connection error"
+            }));
+        }
+    };
+
+    let result = web::block(move || {
+        conn.transaction(|conn| {
+            let enrollment =
+                registration::drop_enrollment(conn, student_id, enrollment_id, &audit)?;
+            let warnings =
+                credit_load::warnings_for_section(conn, student_id, enrollment.section_id)?;
+            Ok::<_, RegistrationError>((enrollment, warnings))
+        })
+    })
+    .await;
+
+    match result {
+        Ok(db_result) => match db_result {
+            Ok((enrollment, warnings)) => {
+                log::info!(
+                    "Student {} left section {} ({})",
+                    enrollment.student_id,
+                    enrollment.section_id,
+                    enrollment.status
+                );
+                HttpResponse::Ok().json(json!({
+                    "status": "success",
+                    "data": enrollment,
+                    "warnings": warnings
+                }))
+            }
+            Err(err) => registration_error_response(err),
+        },
+        Err(blocking_err) => {
+            log::error!("Blocking error: {:?}", blocking_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Internal server error"
+            }))
+        }
+    }
+}
+
+/// Drops the enrollment and adds the requested section in one transaction;
+/// if the new section fails any check the student keeps the old one.
+pub async fn swap_enrollment(
+    audit: AuditContext,
+    user: AuthenticatedUser,
+    pool: web::Data<DbPool>,
+    path: web::Path<(i32, i32)>,
+    swap_req: web::Json<SwapRequest>,
+) -> HttpResponse {
+    let (student_id, enrollment_id) = path.into_inner();
+    let swap_req = swap_req.into_inner();
+
+    if swap_req.override_conflicts {
+        let permission = Permission::ENROLLMENTS_OVERRIDE;
+        if let Err(response) = require_permission(&pool, &user, permission).await {
+            return response;
+        }
+    }
+    let overrides = Overrides {
+        schedule_conflicts: swap_req.override_conflicts,
+    };
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => {
+            log::error!("Failed to get database connection: {:?}", e);
+            return HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Database connection error"
+            }));
+        }
+    };
+
+    let result = web::block(move || {
+        conn.transaction(|conn| {
+            let (dropped, added) = registration::swap(
+                conn,
+                student_id,
+                enrollment_id,
+                swap_req.section_id,
+                overrides,
+                &audit,
+            )?;
+            let warnings = credit_load::warnings_for_section(conn, student_id, added.section_id)?;
+            Ok::<_, RegistrationError>((dropped, added, warnings))
+        })
+    })
+    .await;
+
+    match result {
+        Ok(db_result) => match db_result {
+            Ok((dropped, added, warnings)) => {
+                log::info!(
+                    "Student {} swapped section {} for section {}",
+                    added.student_id,
+                    dropped.section_id,
+                    added.section_id
+                );
+                HttpResponse::Ok().json(json!({
+                    "status": "success",
+                    "data": {
+                        "dropped": dropped,
+                        "added": added
+                    },
+                    "warnings": warnings
+                }))
+            }
+            Err(err) => registration_error_response(err),
+        },
+        Err(blocking_err) => {
+            log::error!("Blocking error: {:?}", blocking_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Internal server error"
+            }))
+        }
+    }
+}
+
+pub(crate) fn registration_error_response(err: RegistrationError) -> HttpResponse {
+    let mut response = match &err {
+        RegistrationError::StudentNotFound
+        | RegistrationError::SectionNotFound
+        | RegistrationError::CourseNotFound
+        | RegistrationError::TermNotFound
+        | RegistrationError::EnrollmentNotFound
+        | RegistrationError::WaitlistEntryNotFound
+        | RegistrationError::Database(diesel::result::Error::NotFound) => HttpResponse::NotFound(),
+        RegistrationError::AlreadyEnrolled
+        | RegistrationError::SectionFull
+        | RegistrationError::AlreadyWaitlisted
+        | RegistrationError::ScheduleConflict(_) => HttpResponse::Conflict(),
+        RegistrationError::OnHold(_) => HttpResponse::Forbidden(),
+        RegistrationError::RequisitesNotMet(_)
+        | RegistrationError::BeforeAppointment { .. }
+        | RegistrationError::RegistrationClosed(_)
+        | RegistrationError::WithdrawalDeadlinePassed(_)
+        | RegistrationError::AddDropDeadlinePassed(_)
+        | RegistrationError::SwapAcrossTerms
+        | RegistrationError::OfferExpired
+        | RegistrationError::LotteryPending
+        | RegistrationError::CreditLimitExceeded { .. }
+        | RegistrationError::BelowMinimumLoad { .. } => HttpResponse::UnprocessableEntity(),
+        RegistrationError::Database(db_err) => {
+            log::error!("Database error during registration: {:?}", db_err);
+            return HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Failed to process enrollment"
+            }));
+        }
+    };
+
+    let mut body = registration_error_details(&err);
+    body["status"] = json!("error");
+    response.json(body)
+}
+
+/// The message for a failed registration check along with whatever explains
+/// it: the blocking holds, the conflicting meetings, the unmet requirements
+/// or when the student's appointment opens.
+pub(crate) fn registration_error_details(err: &RegistrationError) -> serde_json::Value {
+    let mut details = json!({ "message": err.to_string() });
+    match err {
+        RegistrationError::ScheduleConflict(conflicts) => details["conflicts"] = json!(conflicts),
+        RegistrationError::OnHold(holds) => details["holds"] = json!(holds),
+        RegistrationError::RequisitesNotMet(report) => details["requirements"] = json!(report),
+        RegistrationError::BeforeAppointment { opens_at, .. } => {
+            details["opens_at"] = json!(opens_at)
+        }
+        _ => {}
+    }
+    details
+}
@@ -0,0 +1,666 @@
+use actix_web::{web, HttpResponse};
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel::result::{DatabaseErrorKind, Error as DieselError};
+use serde::Deserialize;
+use serde_json::json;
+use validator::{Validate, ValidationError};
+
+use crate::middleware::AuthenticatedUser;
+use crate::models::permission::Permission;
+use crate::models::{
+    AuditContext, GradeScale, GradeScaleDetail, NewGradeScale, NewGradeScaleEntry, RosterEntry,
+    Section, Student, Term,
+};
+use crate::schema::{grade_scale_entries, grade_scales, sections, students};
+use crate::services::grades::{self, GradeError};
+use crate::DbPool;
+
+#[derive(Debug, Deserialize, Validate)]
+#[validate(schema(function = "validate_scale_entries"))]
+pub struct GradeScaleRequest {
+    #[validate(length(min = 1, max = 100))]
+    pub name: String,
+    pub description: Option<String>,
+    #[serde(default)]
+    pub is_default: bool,
+    #[validate]
+    pub entries: Vec<GradeScaleEntryRequest>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct GradeScaleEntryRequest {
+    #[validate(length(min = 1, max = 5))]
+    pub grade: String,
+    /// Quality points; leave out for grades that do not count toward GPA.
+    #[validate(range(min = 0.0, max = 10.0))]
+    pub points: Option<f64>,
+    pub earns_credit: bool,
+    #[serde(default = "default_final")]
+    pub is_final: bool,
+}
+
+fn default_final() -> bool {
+    true
+}
+
+fn validate_scale_entries(scale: &GradeScaleRequest) -> Result<(), ValidationError> {
+    let mut grades: Vec<String> = scale
+        .entries
+        .iter()
+        .map(|entry| entry.grade.trim().to_uppercase())
+        .collect();
+    grades.sort();
+    grades.dedup();
+
+    let message = if scale.entries.is_empty() {
+        "a grade scale needs at least one grade"
+    } else if grades.len() != scale.entries.len() {
+        "each grade may appear only once"
+    } else {
+        return Ok(());
+    };
+
+    let mut error = ValidationError::new("grade_scale_entries");
+    error.message = Some(message.into());
+    Err(error)
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct PostGradesRequest {
+    #[validate]
+    pub grades: Vec<PostedGrade>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct PostedGrade {
+    pub enrollment_id: i32,
+    #[validate(length(min = 1, max = 5))]
+    pub grade: String,
+}
+
+enum GradeScaleError {
+    NotFound,
+    DefaultRequired,
+    Database(DieselError),
+}
+
+impl From<DieselError> for GradeScaleError {
+    fn from(error: DieselError) -> Self {
+        GradeScaleError::Database(error)
+    }
+}
+
+/// Inserts the scale's grades, upper-cased so lookups are case-insensitive.
+fn insert_entries(
+    conn: &mut PgConnection,
+    grade_scale_id: i32,
+    entries: &[GradeScaleEntryRequest],
+) -> QueryResult<()> {
+    let new_entries: Vec<NewGradeScaleEntry> = entries
+        .iter()
+        .map(|entry| NewGradeScaleEntry {
+            grade_scale_id,
+            grade: entry.grade.trim().to_uppercase(),
+            points: entry.points,
+            earns_credit: entry.earns_credit,
+            is_final: entry.is_final,
+        })
+        .collect();
+
+    diesel::insert_into(grade_scale_entries::table)
+        .values(&new_entries)
+        .execute(conn)?;
+    Ok(())
+}
+
+/// Clears the current default so another scale can take its place.
+fn clear_default(conn: &mut PgConnection) -> QueryResult<()> {
+    diesel::update(grade_scales::table.filter(grade_scales::is_default.eq(true)))
+        .set((
+            grade_scales::is_default.eq(false),
+            grade_scales::updated_at.eq(Utc::now()),
+        ))
+        .execute(conn)?;
+    Ok(())
+}
+
+pub async fn get_grade_scales(pool: web::Data<DbPool>) -> HttpResponse {
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => {
+            log::error!("Failed to get database connection: {:?}", e);
+            return HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Database connection error"
+            }));
+        }
+    };
+
+    let result = web::block(move || {
+        let scales = grade_scales::table
+            .order(grade_scales::name.asc())
+            .load::<GradeScale>(&mut conn)?;
+        GradeScaleDetail::load(&mut conn, scales)
+    })
+    .await;
+
+    match result {
+        Ok(db_result) => match db_result {
+            Ok(scales) => HttpResponse::Ok().json(json!({
+                "status": "success",
+                "data": scales
+            })),
+            Err(db_err) => {
+                log::error!("Database error fetching grade scales: {:?}", db_err);
+                HttpResponse::InternalServerError().json(json!({
+                    "status": "error",
+                    "message": "Failed to fetch grade scales"
+                }))
+            }
+        },
+        Err(blocking_err) => {
+            log::error!("Blocking error: {:?}", blocking_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Internal server error"
+            }))
+        }
+    }
+}
+
+pub async fn get_grade_scale(pool: web::Data<DbPool>, path: web::Path<i32>) -> HttpResponse {
+    let scale_id = path.into_inner();
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => {
+            log::error!("Failed to get database connection: {:?}", e);
+            return HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Database connection error"
+            }));
+        }
+    };
+
+    let result = web::block(move || {
+        let scale = grade_scales::table
+            .find(scale_id)
+            .first::<GradeScale>(&mut conn)
+            .optional()?
+            .ok_or(GradeScaleError::NotFound)?;
+        Ok::<_, GradeScaleError>(GradeScaleDetail::load(&mut conn, vec![scale])?.remove(0))
+    })
+    .await;
+
+    match result {
+        Ok(db_result) => match db_result {
+            Ok(scale) => HttpResponse::Ok().json(json!({
+                "status": "success",
+                "data": scale
+            })),
+            Err(err) => grade_scale_error_response(err, "Failed to fetch grade scale"),
+        },
+        Err(blocking_err) => {
+            log::error!("Blocking error: {:?}", blocking_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Internal server error"
+            }))
+        }
+    }
+}
+
+pub async fn create_grade_scale(
+    pool: web::Data<DbPool>,
+    scale_req: web::Json<GradeScaleRequest>,
+) -> HttpResponse {
+    let scale_req = scale_req.into_inner();
+    if let Err(errors) = scale_req.validate() {
+        log::error!("Validation errors: {:?}", errors);
+        return HttpResponse::BadRequest().json(json!({
+            "status": "error",
+            "message": "Validation failed",
+            "errors": errors
+        }));
+    }
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => {
+            log::error!("Failed to get database connection: {:?}", e);
+            return HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Database connection error"
+            }));
+        }
+    };
+
+    let result = web::block(move || {
+        conn.transaction::<_, GradeScaleError, _>(|conn| {
+            if scale_req.is_default {
+                clear_default(conn)?;
+            }
+
+            let scale = diesel::insert_into(grade_scales::table)
+                .values(&NewGradeScale {
+                    name: scale_req.name,
+                    description: scale_req.description,
+                    is_default: scale_req.is_default,
+                })
+                .get_result::<GradeScale>(conn)?;
+            insert_entries(conn, scale.id, &scale_req.entries)?;
+
+            Ok(GradeScaleDetail::load(conn, vec![scale])?.remove(0))
+        })
+    })
+    .await;
+
+    match result {
+        Ok(db_result) => match db_result {
+            Ok(scale) => {
+                log::info!("Successfully created grade scale {}", scale.scale.name);
+                HttpResponse::Created().json(json!({
+                    "status": "success",
+                    "data": scale
+                }))
+            }
+            Err(err) => grade_scale_error_response(err, "Failed to create grade scale"),
+        },
+        Err(blocking_err) => {
+            log::error!("Blocking error: {:?}", blocking_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Internal server error"
+            }))
+        }
+    }
+}
+
+/// Replaces a scale and its grades. Grades already posted keep the points
+/// they were given.
+pub async fn update_grade_scale(
+    pool: web::Data<DbPool>,
+    path: web::Path<i32>,
+    scale_req: web::Json<GradeScaleRequest>,
+) -> HttpResponse {
+    let scale_id = path.into_inner();
+    let scale_req = scale_req.into_inner();
+    if let Err(errors) = scale_req.validate() {
+        log::error!("Validation errors: {:?}", errors);
+        return HttpResponse::BadRequest().json(json!({
+            "status": "error",
+            "message": "Validation failed",
+            "errors": errors
+        }));
+    }
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => {
+            log::error!("Failed to get database connection: {:?}", e);
+            return HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Database connection error"
+            }));
+        }
+    };
+
+    let result = web::block(move || {
+        conn.transaction::<_, GradeScaleError, _>(|conn| {
+            let existing = grade_scales::table
+                .find(scale_id)
+                .for_update()
+                .first::<GradeScale>(conn)
+                .optional()?
+                .ok_or(GradeScaleError::NotFound)?;
+            if existing.is_default && !scale_req.is_default {
+                return Err(GradeScaleError::DefaultRequired);
+            }
+            if scale_req.is_default {
+                clear_default(conn)?;
+            }
+
+            let scale = diesel::update(grade_scales::table.find(existing.id))
+                .set((
+                    &NewGradeScale {
+                        name: scale_req.name,
+                        description: scale_req.description,
+                        is_default: scale_req.is_default,
+                    },
+                    grade_scales::updated_at.eq(Utc::now()),
+                ))
+                .get_result::<GradeScale>(conn)?;
+
+            diesel::delete(
+                grade_scale_entries::table.filter(grade_scale_entries::grade_scale_id.eq(scale.id)),
+            )
+            .execute(conn)?;
+            insert_entries(conn, scale.id, &scale_req.entries)?;
+
+            Ok(GradeScaleDetail::load(conn, vec![scale])?.remove(0))
+        })
+    })
+    .await;
+
+    match result {
+        Ok(db_result) => match db_result {
+            Ok(scale) => {
+                log::info!("Successfully updated grade scale {}", scale.scale.id);
+                HttpResponse::Ok().json(json!({
+                    "status": "success",
+                    "data": scale
+                }))
+            }
+            Err(err) => grade_scale_error_response(err, "Failed to update grade scale"),
+        },
+        Err(blocking_err) => {
+            log::error!("Blocking error: {:?}", blocking_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Internal server error"
+            }))
+        }
+    }
+}
+
+pub async fn delete_grade_scale(pool: web::Data<DbPool>, path: web::Path<i32>) -> HttpResponse {
+    let scale_id = path.into_inner();
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => {
+            log::error!("Failed to get database connection: {:?}", e);
+            return HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Database connection error"
+            }));
+        }
+    };
+
+    let result = web::block(move || {
+        conn.transaction::<_, GradeScaleError, _>(|conn| {
+            let scale = grade_scales::table
+                .find(scale_id)
+                .for_update()
+                .first::<GradeScale>(conn)
+                .optional()?
+                .ok_or(GradeScaleError::NotFound)?;
+            if scale.is_default {
+                return Err(GradeScaleError::DefaultRequired);
+            }
+
+            diesel::delete(grade_scales::table.find(scale.id)).execute(conn)?;
+            Ok(())
+        })
+    })
+    .await;
+
+    match result {
+        Ok(db_result) => match db_result {
+            Ok(()) => {
+                log::info!("Successfully deleted grade scale {}", scale_id);
+                HttpResponse::Ok().json(json!({
+                    "status": "success",
+                    "message": "Grade scale deleted successfully"
+                }))
+            }
+            Err(err) => grade_scale_error_response(err, "Failed to delete grade scale"),
+        },
+        Err(blocking_err) => {
+            log::error!("Blocking error: {:?}", blocking_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Internal server error"
+            }))
+        }
+    }
+}
+
+fn grade_scale_error_response(err: GradeScaleError, failure_message: &str) -> HttpResponse {
+    match err {
+        GradeScaleError::NotFound => HttpResponse::NotFound().json(json!({
+            "status": "error",
+            "message": "Grade scale not found"
+        })),
+        GradeScaleError::DefaultRequired => HttpResponse::Conflict().json(json!({
+            "status": "error",
+            "message": "Make another scale the default first"
+        })),
+        GradeScaleError::Database(DieselError::DatabaseError(
+            DatabaseErrorKind::UniqueViolation,
+            _,
+        )) => HttpResponse::Conflict().json(json!({
+            "status": "error",
+            "message": "A grade scale with this name already exists"
+        })),
+        GradeScaleError::Database(db_err) => {
+            log::error!("Database error: {:?}", db_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": failure_message
+            }))
+        }
+    }
+}
+
+/// Loads the section and checks the caller teaches it or may manage the
+/// grades of every section.
+fn find_gradable_section(
+    conn: &mut PgConnection,
+    term_key: &str,
+    section_id: i32,
+    user: &AuthenticatedUser,
+) -> Result<Section, GradeError> {
+    let term = Term::resolve(conn, term_key)
+        .optional()?
+        .ok_or(GradeError::TermNotFound)?;
+    let section = sections::table
+        .filter(sections::term_id.eq(term.id))
+        .filter(sections::id.eq(section_id))
+        .first::<Section>(conn)
+        .optional()?
+        .ok_or(GradeError::SectionNotFound)?;
+
+    if section.instructor_id != Some(user.user_id)
+        && !user.has_permission(conn, Permission::GRADES_MANAGE)?
+    {
+        return Err(GradeError::NotInstructor);
+    }
+    Ok(section)
+}
+
+/// The section's roster with any grades posted so far.
+pub async fn get_section_grades(
+    user: AuthenticatedUser,
+    pool: web::Data<DbPool>,
+    path: web::Path<(String, i32)>,
+) -> HttpResponse {
+    let (term_key, section_id) = path.into_inner();
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => {
+            log::error!("Failed to get database connection: {:?}", e);
+            return HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Database connection error"
+            }));
+        }
+    };
+
+    let result = web::block(move || {
+        let section = find_gradable_section(&mut conn, &term_key, section_id, &user)?;
+        Ok::<_, GradeError>(RosterEntry::load_for_section(&mut conn, section.id)?)
+    })
+    .await;
+
+    match result {
+        Ok(db_result) => match db_result {
+            Ok(roster) => HttpResponse::Ok().json(json!({
+                "status": "success",
+                "data": roster
+            })),
+            Err(err) => grade_error_response(err),
+        },
+        Err(blocking_err) => {
+            log::error!("Blocking error: {:?}", blocking_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Internal server error"
+            }))
+        }
+    }
+}
+
+pub async fn post_section_grades(
+    audit: AuditContext,
+    user: AuthenticatedUser,
+    pool: web::Data<DbPool>,
+    path: web::Path<(String, i32)>,
+    grades_req: web::Json<PostGradesRequest>,
+) -> HttpResponse {
+    let (term_key, section_id) = path.into_inner();
+    let grades_req = grades_req.into_inner();
+
+    if let Err(errors) = grades_req.validate() {
+        log::error!("Validation errors: {:?}", errors);
+        return HttpResponse::BadRequest().json(json!({
+            "status": "error",
+            "message": "Validation failed",
+            "errors": errors
+        }));
+    }
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => {
+            log::error!("Failed to get database connection: {:?}", e);
+            return HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Database connection error"
+            }));
+        }
+    };
+
+    let result = web::block(move || {
+        conn.transaction(|conn| {
+            let section = find_gradable_section(conn, &term_key, section_id, &user)?;
+            let grades = grades_req
+                .grades
+                .into_iter()
+                .map(|posted| (posted.enrollment_id, posted.grade))
+                .collect();
+            grades::post_grades(conn, section.id, grades, &audit)
+        })
+    })
+    .await;
+
+    match result {
+        Ok(db_result) => match db_result {
+            Ok(enrollments) => {
+                log::info!(
+                    "Posted {} grades for section {}",
+                    enrollments.len(),
+                    section_id
+                );
+                HttpResponse::Ok().json(json!({
+                    "status": "success",
+                    "data": enrollments
+                }))
+            }
+            Err(err) => grade_error_response(err),
+        },
+        Err(blocking_err) => {
+            log::error!("Blocking error: {:?}", blocking_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Internal server error"
+            }))
+        }
+    }
+}
+
+/// Term-by-term and cumulative GPA from posted grades.
+pub async fn get_student_gpa(pool: web::Data<DbPool>, path: web::Path<i32>) -> HttpResponse {
+    let student_id = path.into_inner();
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => {
+            log::error!("Failed to get database connection: {:?}", e);
+            return HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Database connection error"
+            }));
+        }
+    };
+
+    let result = web::block(move || {
+        students::table
+            .find(student_id)
+            .first::<Student>(&mut conn)?;
+        grades::student_gpa(&mut conn, student_id)
+    })
+    .await;
+
+    match result {
+        Ok(db_result) => match db_result {
+            Ok(gpa) => HttpResponse::Ok().json(json!({
+                "status": "success",
+                "data": gpa
+            })),
+            Err(DieselError::NotFound) => HttpResponse::NotFound().json(json!({
+                "status": "error",
+                "message": "Student not found"
+            })),
+            Err(db_err) => {
+                log::error!(
+                    "Database error computing GPA for student {}: {:?}",
+                    student_id,
+                    db_err
+                );
+                HttpResponse::InternalServerError().json(json!({
+                    "status": "error",
+                    "message": "Failed to compute GPA"
+                }))
+            }
+        },
+        Err(blocking_err) => {
+            log::error!("Blocking error: {:?}", blocking_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Internal server error"
+            }))
+        }
+    }
+}
+
+fn grade_error_response(err: GradeError) -> HttpResponse {
+    let message = err.to_string();
+    match err {
+        GradeError::TermNotFound
+        | GradeError::SectionNotFound
+        | GradeError::EnrollmentNotFound(_) => HttpResponse::NotFound().json(json!({
+            "status": "error",
+            "message": message
+        })),
+        GradeError::NotInstructor => HttpResponse::Forbidden().json(json!({
+            "status": "error",
+            "message": message
+        })),
+        GradeError::UnknownGrade(..) | GradeError::NoDefaultScale => {
+            HttpResponse::UnprocessableEntity().json(json!({
+                "status": "error",
+                "message": message
+            }))
+        }
+        GradeError::Database(db_err) => {
+            log::error!("Database error posting grades: {:?}", db_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Failed to process grades"
+            }))
+        }
+    }
+}
@@ -0,0 +1,444 @@
+use actix_web::{web, HttpResponse};
+use chrono::{NaiveDate, Utc};
+use diesel::prelude::*;
+use diesel::result::Error as DieselError;
+use serde::Deserialize;
+use serde_json::json;
+use validator::{Validate, ValidationError};
+
+use crate::middleware::AuthenticatedUser;
+use crate::models::hold::{NewHold, UpdateHold};
+use crate::models::{AuditContext, Hold, Student};
+use crate::schema::{audit_logs, holds, students};
+use crate::DbPool;
+
+/// A hold as placed or edited. `starts_on` defaults to today for a new hold
+/// and is kept on edits; without `ends_on` the hold stays until it is lifted.
+/// Holds block registration unless told otherwise.
+#[derive(Debug, Deserialize, Validate)]
+#[validate(schema(function = "validate_hold"))]
+pub struct HoldRequest {
+    #[validate(custom = "validate_hold_kind")]
+    pub kind: String,
+    #[validate(length(min = 1))]
+    pub reason: String,
+    #[validate(length(min = 1, max = 100))]
+    pub office: String,
+    pub starts_on: Option<NaiveDate>,
+    pub ends_on: Option<NaiveDate>,
+    #[serde(default = "default_blocks_registration")]
+    pub blocks_registration: bool,
+    #[serde(default)]
+    pub blocks_transcript: bool,
+    #[serde(default)]
+    pub blocks_graduation: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct HoldQuery {
+    pub active: Option<bool>,
+}
+
+fn default_blocks_registration() -> bool {
+    true
+}
+
+fn validate_hold_kind(kind: &str) -> Result<(), ValidationError> {
+    if Hold::KINDS.contains(&kind) {
+        return Ok(());
+    }
+    let mut error = ValidationError::new("hold_kind");
+    error.message = Some("kind must be financial, advising, disciplinary or immunization".into());
+    Err(error)
+}
+
+fn validate_hold(hold: &HoldRequest) -> Result<(), ValidationError> {
+    if hold.blocks_registration || hold.blocks_transcript || hold.blocks_graduation {
+        return Ok(());
+    }
+    let mut error = ValidationError::new("hold");
+    error.message = Some("a hold must block registration, transcripts or graduation".into());
+    Err(error)
+}
+
+/// The hold's start, falling back to `default_start`, and its end, which may
+/// not come before the start.
+fn hold_dates(
+    hold: &HoldRequest,
+    default_start: NaiveDate,
+) -> Result<(NaiveDate, Option<NaiveDate>), HoldError> {
+    let starts_on = hold.starts_on.unwrap_or(default_start);
+    if hold.ends_on.is_some_and(|ends_on| ends_on < starts_on) {
+        return Err(HoldError::EndsBeforeStart);
+    }
+    Ok((starts_on, hold.ends_on))
+}
+
+enum HoldError {
+    StudentNotFound,
+    NotFound,
+    EndsBeforeStart,
+    Forbidden(String),
+    Database(DieselError),
+}
+
+impl From<DieselError> for HoldError {
+    fn from(error: DieselError) -> Self {
+        HoldError::Database(error)
+    }
+}
+
+fn ensure_student(conn: &mut PgConnection, student_id: i32) -> Result<(), HoldError> {
+    students::table
+        .find(student_id)
+        .first::<Student>(conn)
+        .optional()?
+        .map(|_| ())
+        .ok_or(HoldError::StudentNotFound)
+}
+
+fn find_hold(conn: &mut PgConnection, student_id: i32, hold_id: i32) -> Result<Hold, HoldError> {
+    holds::table
+        .filter(holds::id.eq(hold_id))
+        .filter(holds::student_id.eq(student_id))
+        .for_update()
+        .first::<Hold>(conn)
+        .optional()?
+        .ok_or(HoldError::NotFound)
+}
+
+/// Each office manages only its own kind of hold, through the kind's
+/// permission.
+fn ensure_may_manage(
+    conn: &mut PgConnection,
+    user: &AuthenticatedUser,
+    kind: &str,
+) -> Result<(), HoldError> {
+    match Hold::permission(kind) {
+        Some(permission) if user.has_permission(conn, permission)? => Ok(()),
+        _ => Err(HoldError::Forbidden(kind.to_string())),
+    }
+}
+
+pub async fn get_holds(
+    _user: AuthenticatedUser,
+    pool: web::Data<DbPool>,
+    path: web::Path<i32>,
+    query: web::Query<HoldQuery>,
+) -> HttpResponse {
+    let student_id = path.into_inner();
+    let active_only = query.active.unwrap_or(false);
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => {
+            log::error!("Failed to get database connection: {:?}", e);
+            return HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Database connection error"
+            }));
+        }
+    };
+
+    let result = web::block(move || {
+        ensure_student(&mut conn, student_id)?;
+        let today = Utc::now().date_naive();
+        let holds = holds::table
+            .filter(holds::student_id.eq(student_id))
+            .order((holds::starts_on.desc(), holds::id.desc()))
+            .load::<Hold>(&mut conn)?
+            .into_iter()
+            .filter(|hold| !active_only || hold.is_active_on(today))
+            .collect::<Vec<_>>();
+        Ok::<_, HoldError>(holds)
+    })
+    .await;
+
+    match result {
+        Ok(db_result) => match db_result {
+            Ok(holds) => HttpResponse::Ok().json(json!({
+                "status": "success",
+                "data": holds
+            })),
+            Err(err) => hold_error_response(err, "Failed to fetch holds"),
+        },
+        Err(blocking_err) => {
+            log::error!("Blocking error: {:?}", blocking_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Internal server error"
+            }))
+        }
+    }
+}
+
+pub async fn create_hold(
+    audit: AuditContext,
+    user: AuthenticatedUser,
+    pool: web::Data<DbPool>,
+    path: web::Path<i32>,
+    hold_req: web::Json<HoldRequest>,
+) -> HttpResponse {
+    let student_id = path.into_inner();
+    let hold_req = hold_req.into_inner();
+
+    if let Err(errors) = hold_req.validate() {
+        log::error!("Validation errors: {:?}", errors);
+        return HttpResponse::BadRequest().json(json!({
+            "status": "error",
+            "message": "Validation failed",
+            "errors": errors
+        }));
+    }
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => {
+            log::error!("Failed to get database connection: {:?}", e);
+            return HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Database connection error"
+            }));
+        }
+    };
+
+    let result = web::block(move || {
+        conn.transaction::<_, HoldError, _>(|conn| {
+            ensure_may_manage(conn, &user, &hold_req.kind)?;
+            ensure_student(conn, student_id)?;
+            let (starts_on, ends_on) = hold_dates(&hold_req, Utc::now().date_naive())?;
+
+            let hold = diesel::insert_into(holds::table)
+                .values(&NewHold {
+                    student_id,
+                    kind: hold_req.kind,
+                    reason: hold_req.reason,
+                    office: hold_req.office,
+                    starts_on,
+                    ends_on,
+                    blocks_registration: hold_req.blocks_registration,
+                    blocks_transcript: hold_req.blocks_transcript,
+                    blocks_graduation: hold_req.blocks_graduation,
+                    created_by: audit.user_id,
+                    updated_by: audit.user_id,
+                })
+                .get_result::<Hold>(conn)?;
+
+            diesel::insert_into(audit_logs::table)
+                .values(&audit.activity(
+                    "hold.create",
+                    "hold",
+                    Some(hold.id),
+                    Some(json!({ "before": null, "after": hold })),
+                ))
+                .execute(conn)?;
+
+            Ok(hold)
+        })
+    })
+    .await;
+
+    match result {
+        Ok(db_result) => match db_result {
+            Ok(hold) => {
+                log::info!(
+                    "Placed {} hold {} on student {}",
+                    hold.kind,
+                    hold.id,
+                    hold.student_id
+                );
+                HttpResponse::Created().json(json!({
+                    "status": "success",
+                    "data": hold
+                }))
+            }
+            Err(err) => hold_error_response(err, "Failed to place hold"),
+        },
+        Err(blocking_err) => {
+            log::error!("Blocking error: {:?}", blocking_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Internal server error"
+            }))
+        }
+    }
+}
+
+/// Replaces a hold's details. Changing its kind needs the right to manage
+/// both the old kind and the new one.
+pub async fn update_hold(
+    audit: AuditContext,
+    user: AuthenticatedUser,
+    pool: web::Data<DbPool>,
+    path: web::Path<(i32, i32)>,
+    hold_req: web::Json<HoldRequest>,
+) -> HttpResponse {
+    let (student_id, hold_id) = path.into_inner();
+    let hold_req = hold_req.into_inner();
+
+    if let Err(errors) = hold_req.validate() {
+        log::error!("Validation errors: {:?}", errors);
+        return HttpResponse::BadRequest().json(json!({
+            "status": "error",
+            "message": "Validation failed",
+            "errors": errors
+        }));
+    }
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => {
+            log::error!("Failed to get database connection: {:?}", e);
+            return HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Database connection error"
+            }));
+        }
+    };
+
+    let result = web::block(move || {
+        conn.transaction::<_, HoldError, _>(|conn| {
+            ensure_may_manage(conn, &user, &hold_req.kind)?;
+            let before = find_hold(conn, student_id, hold_id)?;
+            ensure_may_manage(conn, &user, &before.kind)?;
+            let (starts_on, ends_on) = hold_dates(&hold_req, before.starts_on)?;
+
+            let after = diesel::update(holds::table.find(before.id))
+                .set(&UpdateHold {
+                    kind: hold_req.kind,
+                    reason: hold_req.reason,
+                    office: hold_req.office,
+                    starts_on,
+                    ends_on,
+                    blocks_registration: hold_req.blocks_registration,
+                    blocks_transcript: hold_req.blocks_transcript,
+                    blocks_graduation: hold_req.blocks_graduation,
+                    updated_by: audit.user_id,
+                    updated_at: Utc::now(),
+                })
+                .get_result::<Hold>(conn)?;
+
+            diesel::insert_into(audit_logs::table)
+                .values(&audit.activity(
+                    "hold.update",
+                    "hold",
+                    Some(after.id),
+                    Some(json!({ "before": before, "after": after })),
+                ))
+                .execute(conn)?;
+
+            Ok(after)
+        })
+    })
+    .await;
+
+    match result {
+        Ok(db_result) => match db_result {
+            Ok(hold) => {
+                log::info!("Updated hold {} on student {}", hold.id, hold.student_id);
+                HttpResponse::Ok().json(json!({
+                    "status": "success",
+                    "data": hold
+                }))
+            }
+            Err(err) => hold_error_response(err, "Failed to update hold"),
+        },
+        Err(blocking_err) => {
+            log::error!("Blocking error: {:?}", blocking_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Internal server error"
+            }))
+        }
+    }
+}
+
+/// Lifts a hold by removing it; the audit log keeps its history.
+pub async fn delete_hold(
+    audit: AuditContext,
+    user: AuthenticatedUser,
+    pool: web::Data<DbPool>,
+    path: web::Path<(i32, i32)>,
+) -> HttpResponse {
+    let (student_id, hold_id) = path.into_inner();
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => {
+            log::error!("Failed to get database connection: {:?}", e);
+            return HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Database connection error"
+            }));
+        }
+    };
+
+    let result = web::block(move || {
+        conn.transaction::<_, HoldError, _>(|conn| {
+            let before = find_hold(conn, student_id, hold_id)?;
+            ensure_may_manage(conn, &user, &before.kind)?;
+
+            diesel::delete(holds::table.find(before.id)).execute(conn)?;
+
+            diesel::insert_into(audit_logs::table)
+                .values(&audit.activity(
+                    "hold.delete",
+                    "hold",
+                    Some(before.id),
+                    Some(json!({ "before": before, "after": null })),
+                ))
+                .execute(conn)?;
+
+            Ok(())
+        })
+    })
+    .await;
+
+    match result {
+        Ok(db_result) => match db_result {
+            Ok(()) => {
+                log::info!("Lifted hold {} on student {}", hold_id, student_id);
+                HttpResponse::Ok().json(json!({
+                    "status": "success",
+                    "message": "Hold lifted successfully"
+                }))
+            }
+            Err(err) => hold_error_response(err, "Failed to lift hold"),
+        },
+        Err(blocking_err) => {
+            log::error!("Blocking error: {:?}", blocking_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Internal server error"
+            }))
+        }
+    }
+}
+
+fn hold_error_response(err: HoldError, failure_message: &str) -> HttpResponse {
+    match err {
+        HoldError::StudentNotFound => HttpResponse::NotFound().json(json!({
+            "status": "error",
+            "message": "Student not found"
+        })),
+        HoldError::NotFound => HttpResponse::NotFound().json(json!({
+            "status": "error",
+            "message": "Hold not found"
+        })),
+        HoldError::EndsBeforeStart => HttpResponse::UnprocessableEntity().json(json!({
+            "status": "error",
+            "message": "A hold cannot end before it starts"
+        })),
+        HoldError::Forbidden(kind) => HttpResponse::Forbidden().json(json!({
+            "status": "error",
+            "message": format!("Requires the holds.{} permission", kind)
+        })),
+        HoldError::Database(db_err) => {
+            log::error!("Database error: {:?}", db_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": failure_message
+            }))
+        }
+    }
+}
@@ -0,0 +1,579 @@
+use actix_web::http::header;
+use actix_web::{web, HttpResponse};
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel::result::{DatabaseErrorKind, Error as DieselError};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use validator::Validate;
+
+use crate::middleware::AuthenticatedUser;
+use crate::models::instructor::{Instructor, InstructorDetail, NewInstructor};
+use crate::models::permission::Permission;
+use crate::models::{RosterEntry, Section, SectionDetail, Term};
+use crate::schema::{instructors, roles, sections, users};
+use crate::DbPool;
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct InstructorRequest {
+    pub user_id: i32,
+    #[validate(length(min = 1, max = 100))]
+    pub department: String,
+    #[validate(length(min = 1, max = 100))]
+    pub title: String,
+    #[validate(length(max = 100))]
+    pub office: Option<String>,
+    #[validate(range(min = 0, max = 20))]
+    pub max_sections: Option<i32>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct InstructorQuery {
+    pub department: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TeachingQuery {
+    pub term: Option<String>,
+    /// `csv` downloads the class lists instead of returning JSON.
+    pub format: Option<String>,
+}
+
+/// A section the instructor teaches with its current class list.
+#[derive(Debug, Serialize)]
+pub struct TaughtSection {
+    pub section: SectionDetail,
+    pub roster: Vec<RosterEntry>,
+}
+
+enum InstructorError {
+    TermNotFound,
+    InstructorNotFound,
+    NoInstructorProfile,
+    Forbidden,
+    Database(DieselError),
+}
+
+impl From<DieselError> for InstructorError {
+    fn from(error: DieselError) -> Self {
+        InstructorError::Database(error)
+    }
+}
+
+const DEFAULT_MAX_SECTIONS: i32 = 3;
+
+fn find_instructor(
+    conn: &mut PgConnection,
+    instructor_id: i32,
+) -> Result<Instructor, InstructorError> {
+    instructors::table
+        .find(instructor_id)
+        .first::<Instructor>(conn)
+        .optional()?
+        .ok_or(InstructorError::InstructorNotFound)
+}
+
+/// The instructor's sections in the term with their rosters. Only the
+/// instructor and users who manage instructors may see them.
+fn load_teaching(
+    conn: &mut PgConnection,
+    instructor: &Instructor,
+    term_key: &str,
+    user: &AuthenticatedUser,
+) -> Result<(Term, Vec<TaughtSection>), InstructorError> {
+    if user.user_id != instructor.user_id
+        && !user.has_permission(conn, Permission::INSTRUCTORS_MANAGE)?
+    {
+        return Err(InstructorError::Forbidden);
+    }
+    let term = Term::resolve(conn, term_key).map_err(|e| match e {
+        DieselError::NotFound => InstructorError::TermNotFound,
+        other => InstructorError::Database(other),
+    })?;
+
+    let taught = sections::table
+        .filter(sections::term_id.eq(term.id))
+        .filter(sections::instructor_id.eq(instructor.user_id))
+        .order((sections::course_id.asc(), sections::section_number.asc()))
+        .load::<Section>(conn)?;
+
+    let mut result = Vec::with_capacity(taught.len());
+    for section in SectionDetail::load(conn, taught)? {
+        let roster = RosterEntry::load_for_section(conn, section.section.id)?;
+        result.push(TaughtSection { section, roster });
+    }
+    Ok((term, result))
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// One line per enrolled student across all of the instructor's sections.
+fn class_lists_csv(term: &Term, taught: &[TaughtSection]) -> String {
+    let mut csv =
+        String::from("term,course,section,student_id,student_name,student_email,enrolled_at\r\n");
+    for taught_section in taught {
+        for entry in &taught_section.roster {
+            let fields = [
+                term.code.clone(),
+                taught_section.section.course_code.clone(),
+                taught_section.section.section.section_number.clone(),
+                entry.enrollment.student_id.to_string(),
+                entry.student_name.clone(),
+                entry.student_email.clone(),
+                entry.enrollment.enrolled_at.to_rfc3339(),
+            ];
+            let line: Vec<String> = fields.iter().map(|field| csv_field(field)).collect();
+            csv.push_str(&line.join(","));
+            csv.push_str("\r\n");
+        }
+    }
+    csv
+}
+
+fn teaching_response(
+    instructor_id: i32,
+    format: Option<&str>,
+    result: Result<(Term, Vec<TaughtSection>), InstructorError>,
+) -> HttpResponse {
+    match (result, format) {
+        (Ok((term, taught)), Some("csv")) => HttpResponse::Ok()
+            .content_type("text/csv; charset=utf-8")
+            .insert_header((
+                header::CONTENT_DISPOSITION,
+                format!(
+                    "attachment; filename=\"class-lists-{}-{}.csv\"",
+                    instructor_id, term.code
+                ),
+            ))
+            .body(class_lists_csv(&term, &taught)),
+        (Ok((_, taught)), _) => HttpResponse::Ok().json(json!({
+            "status": "success",
+            "data": taught
+        })),
+        (Err(err), _) => instructor_error_response(err, "Failed to fetch sections"),
+    }
+}
+
+pub async fn get_instructors(
+    pool: web::Data<DbPool>,
+    query: web::Query<InstructorQuery>,
+) -> HttpResponse {
+    let query = query.into_inner();
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => {
+            log::error!("Failed to get database connection: {:?}", e);
+            return HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Database connection error"
+            }));
+        }
+    };
+
+    let result = web::block(move || {
+        let mut instructors_query = instructors::table.order(instructors::id.asc()).into_boxed();
+        if let Some(department) = query.department {
+            instructors_query = instructors_query.filter(instructors::department.eq(department));
+        }
+        let instructors = instructors_query.load::<Instructor>(&mut conn)?;
+        InstructorDetail::load(&mut conn, instructors)
+    })
+    .await;
+
+    match result {
+        Ok(db_result) => match db_result {
+            Ok(instructors) => HttpResponse::Ok().json(json!({
+                "status": "success",
+                "data": instructors
+            })),
+            Err(db_err) => instructor_error_response(db_err.into(), "Failed to fetch instructors"),
+        },
+        Err(blocking_err) => {
+            log::error!("Blocking error: {:?}", blocking_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Internal server error"
+            }))
+        }
+    }
+}
+
+pub async fn get_instructor(pool: web::Data<DbPool>, path: web::Path<i32>) -> HttpResponse {
+    let instructor_id = path.into_inner();
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => {
+            log::error!("Failed to get database connection: {:?}", e);
+            return HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Database connection error"
+            }));
+        }
+    };
+
+    let result = web::block(move || {
+        let instructor = find_instructor(&mut conn, instructor_id)?;
+        Ok::<_, InstructorError>(InstructorDetail::load(&mut conn, vec![instructor])?.remove(0))
+    })
+    .await;
+
+    match result {
+        Ok(db_result) => match db_result {
+            Ok(instructor) => HttpResponse::Ok().json(json!({
+                "status": "success",
+                "data": instructor
+            })),
+            Err(err) => instructor_error_response(err, "Failed to fetch instructor"),
+        },
+        Err(blocking_err) => {
+            log::error!("Blocking error: {:?}", blocking_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Internal server error"
+            }))
+        }
+    }
+}
+
+/// Moves the profile's user from the plain `user` role to `instructor`, as
+/// the permissions migration did for existing faculty. Users already holding
+/// another role, such as administrators, keep it.
+fn grant_instructor_role(conn: &mut PgConnection, user_id: i32) -> QueryResult<()> {
+    let instructor_role = roles::table
+        .filter(roles::name.eq("instructor"))
+        .select(roles::id)
+        .first::<i32>(conn)?;
+    let user_role = roles::table
+        .filter(roles::name.eq("user"))
+        .select(roles::id)
+        .first::<i32>(conn)?;
+
+    diesel::update(
+        users::table
+            .find(user_id)
+            .filter(users::role_id.eq(user_role)),
+    )
+    .set((
+        users::role_id.eq(instructor_role),
+        users::updated_at.eq(Utc::now()),
+    ))
+    .execute(conn)?;
+    Ok(())
+}
+
+pub async fn create_instructor(
+    pool: web::Data<DbPool>,
+    instructor_req: web::Json<InstructorRequest>,
+) -> HttpResponse {
+    let instructor_req = instructor_req.into_inner();
+    if let Err(errors) = instructor_req.validate() {
+        log::error!("Validation errors: {:?}", errors);
+        return HttpResponse::BadRequest().json(json!({
+            "status": "error",
+            "message": "Validation failed",
+            "errors": errors
+        }));
+    }
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => {
+            log::error!("Failed to get database connection: {:?}", e);
+            return HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Database connection error"
+            }));
+        }
+    };
+
+    let result = web::block(move || {
+        conn.transaction::<_, InstructorError, _>(|conn| {
+            let instructor = diesel::insert_into(instructors::table)
+                .values(&NewInstructor {
+                    user_id: instructor_req.user_id,
+                    department: instructor_req.department,
+                    title: instructor_req.title,
+                    office: instructor_req.office,
+                    max_sections: instructor_req.max_sections.unwrap_or(DEFAULT_MAX_SECTIONS),
+                })
+                .get_result::<Instructor>(conn)?;
+            grant_instructor_role(conn, instructor.user_id)?;
+            Ok(InstructorDetail::load(conn, vec![instructor])?.remove(0))
+        })
+    })
+    .await;
+
+    match result {
+        Ok(db_result) => match db_result {
+            Ok(instructor) => {
+                log::info!(
+                    "Created instructor profile {} for user {}",
+                    instructor.instructor.id,
+                    instructor.instructor.user_id
+                );
+                HttpResponse::Created().json(json!({
+                    "status": "success",
+                    "data": instructor
+                }))
+            }
+            Err(err) => instructor_error_response(err, "Failed to create instructor"),
+        },
+        Err(blocking_err) => {
+            log::error!("Blocking error: {:?}", blocking_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Internal server error"
+            }))
+        }
+    }
+}
+
+pub async fn update_instructor(
+    pool: web::Data<DbPool>,
+    path: web::Path<i32>,
+    instructor_req: web::Json<InstructorRequest>,
+) -> HttpResponse {
+    let instructor_id = path.into_inner();
+    let instructor_req = instructor_req.into_inner();
+    if let Err(errors) = instructor_req.validate() {
+        log::error!("Validation errors: {:?}", errors);
+        return HttpResponse::BadRequest().json(json!({
+            "status": "error",
+            "message": "Validation failed",
+            "errors": errors
+        }));
+    }
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => {
+            log::error!("Failed to get database connection: {:?}", e);
+            return HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Database connection error"
+            }));
+        }
+    };
+
+    let result = web::block(move || {
+        conn.transaction::<_, InstructorError, _>(|conn| {
+            let instructor = diesel::update(instructors::table.find(instructor_id))
+                .set((
+                    &NewInstructor {
+                        user_id: instructor_req.user_id,
+                        department: instructor_req.department,
+                        title: instructor_req.title,
+                        office: instructor_req.office,
+                        max_sections: instructor_req.max_sections.unwrap_or(DEFAULT_MAX_SECTIONS),
+                    },
+                    instructors::updated_at.eq(Utc::now()),
+                ))
+                .get_result::<Instructor>(conn)
+                .optional()?
+                .ok_or(InstructorError::InstructorNotFound)?;
+            grant_instructor_role(conn, instructor.user_id)?;
+            Ok(InstructorDetail::load(conn, vec![instructor])?.remove(0))
+        })
+    })
+    .await;
+
+    match result {
+        Ok(db_result) => match db_result {
+            Ok(instructor) => {
+                log::info!(
+                    "Successfully updated instructor {}",
+                    instructor.instructor.id
+                );
+                HttpResponse::Ok().json(json!({
+                    "status": "success",
+                    "data": instructor
+                }))
+            }
+            Err(err) => instructor_error_response(err, "Failed to update instructor"),
+        },
+        Err(blocking_err) => {
+            log::error!("Blocking error: {:?}", blocking_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Internal server error"
+            }))
+        }
+    }
+}
+
+pub async fn delete_instructor(pool: web::Data<DbPool>, path: web::Path<i32>) -> HttpResponse {
+    let instructor_id = path.into_inner();
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => {
+            log::error!("Failed to get database connection: {:?}", e);
+            return HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Database connection error"
+            }));
+        }
+    };
+
+    let result = web::block(move || {
+        let deleted = diesel::delete(instructors::table.find(instructor_id)).execute(&mut conn)?;
+        match deleted {
+            0 => Err(InstructorError::InstructorNotFound),
+            _ => Ok(()),
+        }
+    })
+    .await;
+
+    match result {
+        Ok(db_result) => match db_result {
+            Ok(()) => {
+                log::info!("Successfully deleted instructor {}", instructor_id);
+                HttpResponse::Ok().json(json!({
+                    "status": "success",
+                    "message": "Instructor deleted successfully"
+                }))
+            }
+            Err(err) => instructor_error_response(err, "Failed to delete instructor"),
+        },
+        Err(blocking_err) => {
+            log::error!("Blocking error: {:?}", blocking_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Internal server error"
+            }))
+        }
+    }
+}
+
+/// The instructor's sections for a term with their rosters, as JSON or as a
+/// CSV class list.
+pub async fn get_instructor_sections(
+    user: AuthenticatedUser,
+    pool: web::Data<DbPool>,
+    path: web::Path<i32>,
+    query: web::Query<TeachingQuery>,
+) -> HttpResponse {
+    let instructor_id = path.into_inner();
+    let query = query.into_inner();
+    let term_key = query.term.unwrap_or_else(|| "current".into());
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => {
+            log::error!("Failed to get database connection: {:?}", e);
+            return HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Database connection error"
+            }));
+        }
+    };
+
+    let result = web::block(move || {
+        let instructor = find_instructor(&mut conn, instructor_id)?;
+        load_teaching(&mut conn, &instructor, &term_key, &user)
+    })
+    .await;
+
+    match result {
+        Ok(db_result) => teaching_response(instructor_id, query.format.as_deref(), db_result),
+        Err(blocking_err) => {
+            log::error!("Blocking error: {:?}", blocking_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Internal server error"
+            }))
+        }
+    }
+}
+
+/// The signed-in instructor's own sections and rosters.
+pub async fn get_my_sections(
+    user: AuthenticatedUser,
+    pool: web::Data<DbPool>,
+    query: web::Query<TeachingQuery>,
+) -> HttpResponse {
+    let query = query.into_inner();
+    let term_key = query.term.unwrap_or_else(|| "current".into());
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => {
+            log::error!("Failed to get database connection: {:?}", e);
+            return HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Database connection error"
+            }));
+        }
+    };
+
+    let result = web::block(move || {
+        let instructor = Instructor::for_user(&mut conn, user.user_id)?
+            .ok_or(InstructorError::NoInstructorProfile)?;
+        let teaching = load_teaching(&mut conn, &instructor, &term_key, &user)?;
+        Ok::<_, InstructorError>((instructor.id, teaching))
+    })
+    .await;
+
+    match result {
+        Ok(Ok((instructor_id, teaching))) => {
+            teaching_response(instructor_id, query.format.as_deref(), Ok(teaching))
+        }
+        Ok(Err(err)) => instructor_error_response(err, "Failed to fetch sections"),
+        Err(blocking_err) => {
+            log::error!("Blocking error: {:?}", blocking_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Internal server error"
+            }))
+        }
+    }
+}
+
+fn instructor_error_response(err: InstructorError, failure_message: &str) -> HttpResponse {
+    let not_found = |message: &str| {
+        HttpResponse::NotFound().json(json!({
+            "status": "error",
+            "message": message
+        }))
+    };
+    match err {
+        InstructorError::TermNotFound => not_found("Term not found"),
+        InstructorError::InstructorNotFound => not_found("Instructor not found"),
+        InstructorError::NoInstructorProfile => {
+            not_found("No instructor profile is linked to this account")
+        }
+        InstructorError::Forbidden => HttpResponse::Forbidden().json(json!({
+            "status": "error",
+            "message": "Only the instructor or a user with the instructors.manage permission may view these sections"
+        })),
+        InstructorError::Database(DieselError::DatabaseError(
+            DatabaseErrorKind::UniqueViolation,
+            _,
+        )) => HttpResponse::Conflict().json(json!({
+            "status": "error",
+            "message": "This user already has an instructor profile"
+        })),
+        InstructorError::Database(DieselError::DatabaseError(
+            DatabaseErrorKind::ForeignKeyViolation,
+            _,
+        )) => HttpResponse::BadRequest().json(json!({
+            "status": "error",
+            "message": "User not found"
+        })),
+        InstructorError::Database(db_err) => {
+            log::error!("Database error on instructors: {:?}", db_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": failure_message
+            }))
+        }
+    }
+}
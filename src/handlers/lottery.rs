@@ -0,0 +1,449 @@
+use std::collections::HashSet;
+
+use actix_web::{web, HttpResponse};
+use diesel::prelude::*;
+use diesel::result::Error as DieselError;
+use serde::Deserialize;
+use serde_json::json;
+use validator::Validate;
+
+use crate::handlers::enrollment::registration_error_response;
+use crate::middleware::AuthenticatedUser;
+use crate::models::lottery::{LotteryRequest, LotteryRun, NewLotteryRequest};
+use crate::models::{AuditContext, Section, Term};
+use crate::schema::{audit_logs, lottery_requests, lottery_runs, sections};
+use crate::services::lottery::{self, LotteryError, PriorityWeight};
+use crate::services::registration::{self, RegistrationError};
+use crate::DbPool;
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct LotteryRequestsUpdate {
+    pub term: Option<String>,
+    /// Lottery section ids, first choice first.
+    #[validate(length(max = 20))]
+    pub sections: Vec<i32>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct LotteryRunRequest {
+    /// Reuse a seed to reproduce an earlier draw; a random one is picked
+    /// and recorded when omitted.
+    pub seed: Option<i64>,
+    #[serde(default)]
+    #[validate]
+    pub weights: Vec<PriorityWeight>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LotteryQuery {
+    pub term: Option<String>,
+}
+
+enum LotteryHandlerError {
+    TermNotFound,
+    RunNotFound,
+    NotLotterySection(i32),
+    DuplicateSection(i32),
+    NothingToRun(String),
+    Registration(RegistrationError),
+    Database(DieselError),
+}
+
+impl From<DieselError> for LotteryHandlerError {
+    fn from(error: DieselError) -> Self {
+        LotteryHandlerError::Database(error)
+    }
+}
+
+impl From<RegistrationError> for LotteryHandlerError {
+    fn from(error: RegistrationError) -> Self {
+        LotteryHandlerError::Registration(error)
+    }
+}
+
+impl From<LotteryError> for LotteryHandlerError {
+    fn from(error: LotteryError) -> Self {
+        match error {
+            LotteryError::NothingToRun(term_code) => LotteryHandlerError::NothingToRun(term_code),
+            LotteryError::Registration(err) => LotteryHandlerError::Registration(err),
+            LotteryError::Database(err) => LotteryHandlerError::Database(err),
+        }
+    }
+}
+
+fn resolve_term(conn: &mut PgConnection, term_key: &str) -> Result<Term, LotteryHandlerError> {
+    Term::resolve(conn, term_key).map_err(|e| match e {
+        DieselError::NotFound => LotteryHandlerError::TermNotFound,
+        other => LotteryHandlerError::Database(other),
+    })
+}
+
+fn term_requests(
+    conn: &mut PgConnection,
+    student_id: i32,
+    term: &Term,
+) -> Result<Vec<LotteryRequest>, LotteryHandlerError> {
+    Ok(lottery_requests::table
+        .inner_join(sections::table)
+        .filter(lottery_requests::student_id.eq(student_id))
+        .filter(sections::term_id.eq(term.id))
+        .order((lottery_requests::rank.asc(), lottery_requests::id.asc()))
+        .select(lottery_requests::all_columns)
+        .load::<LotteryRequest>(conn)?)
+}
+
+pub async fn get_lottery_requests(
+    _user: AuthenticatedUser,
+    pool: web::Data<DbPool>,
+    path: web::Path<i32>,
+    query: web::Query<LotteryQuery>,
+) -> HttpResponse {
+    let student_id = path.into_inner();
+    let term_key = query.into_inner().term.unwrap_or_else(|| "current".into());
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => {
+            log::error!("Failed to get database connection: {:?}", e);
+            return HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Database connection error"
+            }));
+        }
+    };
+
+    let result = web::block(move || {
+        registration::find_student(&mut conn, student_id)?;
+        let term = resolve_term(&mut conn, &term_key)?;
+        term_requests(&mut conn, student_id, &term)
+    })
+    .await;
+
+    match result {
+        Ok(db_result) => match db_result {
+            Ok(requests) => HttpResponse::Ok().json(json!({
+                "status": "success",
+                "data": requests
+            })),
+            Err(err) => lottery_error_response(err, "Failed to fetch lottery requests"),
+        },
+        Err(blocking_err) => {
+            log::error!("Blocking error: {:?}", blocking_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Internal server error"
+            }))
+        }
+    }
+}
+
+/// Replaces the student's pending lottery requests for the term with the
+/// given ranking. An empty list withdraws from the lottery.
+pub async fn update_lottery_requests(
+    _user: AuthenticatedUser,
+    audit: AuditContext,
+    pool: web::Data<DbPool>,
+    path: web::Path<i32>,
+    update_req: web::Json<LotteryRequestsUpdate>,
+) -> HttpResponse {
+    let student_id = path.into_inner();
+    let update_req = update_req.into_inner();
+    if let Err(errors) = update_req.validate() {
+        log::error!("Validation errors: {:?}", errors);
+        return HttpResponse::BadRequest().json(json!({
+            "status": "error",
+            "message": "Validation failed",
+            "errors": errors
+        }));
+    }
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => {
+            log::error!("Failed to get database connection: {:?}", e);
+            return HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Database connection error"
+            }));
+        }
+    };
+
+    let result = web::block(move || {
+        conn.transaction::<_, LotteryHandlerError, _>(|conn| {
+            registration::find_student(conn, student_id)?;
+            let term_key = update_req.term.as_deref().unwrap_or("current");
+            let term = resolve_term(conn, term_key)?;
+
+            let mut seen = HashSet::new();
+            for &section_id in &update_req.sections {
+                if !seen.insert(section_id) {
+                    return Err(LotteryHandlerError::DuplicateSection(section_id));
+                }
+                let open_for_lottery = sections::table
+                    .filter(sections::id.eq(section_id))
+                    .filter(sections::term_id.eq(term.id))
+                    .filter(sections::lottery.eq(true))
+                    .first::<Section>(conn)
+                    .optional()?
+                    .is_some();
+                if !open_for_lottery {
+                    return Err(LotteryHandlerError::NotLotterySection(section_id));
+                }
+            }
+
+            let before = term_requests(conn, student_id, &term)?;
+            let pending_ids: Vec<i32> = before
+                .iter()
+                .filter(|request| request.status == LotteryRequest::PENDING)
+                .map(|request| request.id)
+                .collect();
+            diesel::delete(
+                lottery_requests::table.filter(lottery_requests::id.eq_any(pending_ids)),
+            )
+            .execute(conn)?;
+
+            let new_requests: Vec<NewLotteryRequest> = update_req
+                .sections
+                .iter()
+                .enumerate()
+                .map(|(index, &section_id)| NewLotteryRequest {
+                    student_id,
+                    section_id,
+                    rank: index as i32 + 1,
+                })
+                .collect();
+            diesel::insert_into(lottery_requests::table)
+                .values(&new_requests)
+                .execute(conn)?;
+
+            let after = term_requests(conn, student_id, &term)?;
+            diesel::insert_into(audit_logs::table)
+                .values(&audit.activity(
+                    "lottery.request",
+                    "student",
+                    Some(student_id),
+                    Some(json!({ "term": term.code, "before": before, "after": after })),
+                ))
+                .execute(conn)?;
+
+            Ok(after)
+        })
+    })
+    .await;
+
+    match result {
+        Ok(db_result) => match db_result {
+            Ok(requests) => {
+                log::info!(
+                    "Student {} ranked {} lottery sections",
+                    student_id,
+                    requests.len()
+                );
+                HttpResponse::Ok().json(json!({
+                    "status": "success",
+                    "data": requests
+                }))
+            }
+            Err(err) => lottery_error_response(err, "Failed to update lottery requests"),
+        },
+        Err(blocking_err) => {
+            log::error!("Blocking error: {:?}", blocking_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Internal server error"
+            }))
+        }
+    }
+}
+
+pub async fn get_lottery_runs(pool: web::Data<DbPool>, path: web::Path<String>) -> HttpResponse {
+    let term_key = path.into_inner();
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => {
+            log::error!("Failed to get database connection: {:?}", e);
+            return HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Database connection error"
+            }));
+        }
+    };
+
+    let result = web::block(move || {
+        let term = resolve_term(&mut conn, &term_key)?;
+        Ok::<_, LotteryHandlerError>(
+            lottery_runs::table
+                .filter(lottery_runs::term_id.eq(term.id))
+                .order(lottery_runs::id.desc())
+                .load::<LotteryRun>(&mut conn)?,
+        )
+    })
+    .await;
+
+    match result {
+        Ok(db_result) => match db_result {
+            Ok(runs) => HttpResponse::Ok().json(json!({
+                "status": "success",
+                "data": runs
+            })),
+            Err(err) => lottery_error_response(err, "Failed to fetch lottery runs"),
+        },
+        Err(blocking_err) => {
+            log::error!("Blocking error: {:?}", blocking_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Internal server error"
+            }))
+        }
+    }
+}
+
+pub async fn get_lottery_run(
+    pool: web::Data<DbPool>,
+    path: web::Path<(String, i32)>,
+) -> HttpResponse {
+    let (term_key, run_id) = path.into_inner();
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => {
+            log::error!("Failed to get database connection: {:?}", e);
+            return HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Database connection error"
+            }));
+        }
+    };
+
+    let result = web::block(move || {
+        let term = resolve_term(&mut conn, &term_key)?;
+        lottery_runs::table
+            .filter(lottery_runs::id.eq(run_id))
+            .filter(lottery_runs::term_id.eq(term.id))
+            .first::<LotteryRun>(&mut conn)
+            .optional()?
+            .ok_or(LotteryHandlerError::RunNotFound)
+    })
+    .await;
+
+    match result {
+        Ok(db_result) => match db_result {
+            Ok(run) => HttpResponse::Ok().json(json!({
+                "status": "success",
+                "data": run
+            })),
+            Err(err) => lottery_error_response(err, "Failed to fetch lottery run"),
+        },
+        Err(blocking_err) => {
+            log::error!("Blocking error: {:?}", blocking_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Internal server error"
+            }))
+        }
+    }
+}
+
+/// Runs the lottery for every lottery section in the term. The whole run is
+/// one transaction, so a failure leaves the requests pending.
+pub async fn create_lottery_run(
+    audit: AuditContext,
+    pool: web::Data<DbPool>,
+    path: web::Path<String>,
+    run_req: web::Json<LotteryRunRequest>,
+) -> HttpResponse {
+    let term_key = path.into_inner();
+    let run_req = run_req.into_inner();
+    if let Err(errors) = run_req.validate() {
+        log::error!("Validation errors: {:?}", errors);
+        return HttpResponse::BadRequest().json(json!({
+            "status": "error",
+            "message": "Validation failed",
+            "errors": errors
+        }));
+    }
+    let seed = run_req.seed.unwrap_or_else(rand::random);
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => {
+            log::error!("Failed to get database connection: {:?}", e);
+            return HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Database connection error"
+            }));
+        }
+    };
+
+    let result = web::block(move || {
+        conn.transaction::<_, LotteryHandlerError, _>(|conn| {
+            let term = resolve_term(conn, &term_key)?;
+            Ok(lottery::run(conn, &term, seed, run_req.weights, &audit)?)
+        })
+    })
+    .await;
+
+    match result {
+        Ok(db_result) => match db_result {
+            Ok(run) => {
+                log::info!(
+                    "Ran lottery {} for term {} with seed {}",
+                    run.id,
+                    run.term_id,
+                    run.seed
+                );
+                HttpResponse::Created().json(json!({
+                    "status": "success",
+                    "data": run
+                }))
+            }
+            Err(err) => lottery_error_response(err, "Failed to run lottery"),
+        },
+        Err(blocking_err) => {
+            log::error!("Blocking error: {:?}", blocking_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Internal server error"
+            }))
+        }
+    }
+}
+
+fn lottery_error_response(err: LotteryHandlerError, failure_message: &str) -> HttpResponse {
+    match err {
+        LotteryHandlerError::TermNotFound => HttpResponse::NotFound().json(json!({
+            "status": "error",
+            "message": "Term not found"
+        })),
+        LotteryHandlerError::RunNotFound => HttpResponse::NotFound().json(json!({
+            "status": "error",
+            "message": "Lottery run not found"
+        })),
+        LotteryHandlerError::NotLotterySection(section_id) => HttpResponse::UnprocessableEntity()
+            .json(json!({
+                "status": "error",
+                "message": format!(
+                    "Section {} is not awaiting a lottery in this term",
+                    section_id
+                )
+            })),
+        LotteryHandlerError::DuplicateSection(section_id) => HttpResponse::UnprocessableEntity()
+            .json(json!({
+                "status": "error",
+                "message": format!("Section {} is ranked more than once", section_id)
+            })),
+        LotteryHandlerError::NothingToRun(term_code) => HttpResponse::Conflict().json(json!({
+            "status": "error",
+            "message": LotteryError::NothingToRun(term_code).to_string()
+        })),
+        LotteryHandlerError::Registration(err) => registration_error_response(err),
+        LotteryHandlerError::Database(db_err) => {
+            log::error!("Database error: {:?}", db_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": failure_message
+            }))
+        }
+    }
+}
@@ -0,0 +1,23 @@
+use actix_web::HttpResponse;
+use prometheus::{Encoder, TextEncoder};
+use serde_json::json;
+
+/// Request counts and durations recorded by `PrometheusMetrics`, in the
+/// Prometheus text format.
+pub async fn metrics() -> HttpResponse {
+    let encoder = TextEncoder::new();
+    let mut buffer = Vec::new();
+
+    match encoder.encode(&prometheus::gather(), &mut buffer) {
+        Ok(_) => HttpResponse::Ok()
+            .content_type(encoder.format_type())
+            .body(buffer),
+        Err(e) => {
+            log::error!("Failed to encode metrics: {}", e);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Failed to encode metrics"
+            }))
+        }
+    }
+}
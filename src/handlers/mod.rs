@@ -1,2 +1,22 @@
+pub mod appointment;
+pub mod audit;
 pub mod auth;
+pub mod cart;
+pub mod course;
+pub mod credit_load;
+pub mod enrollment;
+pub mod grade;
+pub mod hold;
+pub mod instructor;
+pub mod lottery;
+pub mod metrics;
+pub mod program;
+pub mod role;
+pub mod room;
+pub mod schedule;
+pub mod section;
+pub mod session;
 pub mod student;
+pub mod term;
+pub mod transcript;
+pub mod waitlist;
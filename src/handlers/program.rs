@@ -0,0 +1,750 @@
+use actix_web::{web, HttpResponse};
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel::result::{DatabaseErrorKind, Error as DieselError};
+use serde::Deserialize;
+use serde_json::json;
+use validator::{Validate, ValidationError};
+
+use crate::middleware::AuthenticatedUser;
+use crate::models::course::normalize_code;
+use crate::models::program::{NewProgramRequirementCourse, NewStudentProgram, StudentProgram};
+use crate::models::{
+    AuditContext, DeclaredProgram, NewProgram, NewProgramRequirement, Program, ProgramDetail,
+    ProgramRequirement, Student,
+};
+use crate::schema::{
+    audit_logs, courses, program_requirement_courses, program_requirements, programs,
+    student_programs, students,
+};
+use crate::services::degree_audit;
+use crate::DbPool;
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct ProgramRequest {
+    #[validate(length(min = 2, max = 20))]
+    pub code: String,
+    #[validate(length(min = 1, max = 255))]
+    pub name: String,
+    #[validate(custom = "validate_program_kind")]
+    pub kind: String,
+    #[validate(length(min = 1, max = 100))]
+    pub department: String,
+    pub description: Option<String>,
+    #[serde(default = "default_active")]
+    pub is_active: bool,
+    #[validate]
+    #[serde(default)]
+    pub requirements: Vec<RequirementRequest>,
+}
+
+/// One requirement block. `courses` are course codes; `min_credits` and
+/// `min_gpa` are required by the kinds that use them.
+#[derive(Debug, Deserialize, Validate)]
+#[validate(schema(function = "validate_requirement"))]
+pub struct RequirementRequest {
+    #[validate(length(min = 1, max = 255))]
+    pub name: String,
+    #[validate(custom = "validate_requirement_kind")]
+    pub kind: String,
+    #[serde(default)]
+    pub courses: Vec<String>,
+    #[validate(range(min = 1, max = 1000))]
+    pub min_credits: Option<i32>,
+    #[validate(range(min = 0.0, max = 10.0))]
+    pub min_gpa: Option<f64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DeclareProgramRequest {
+    pub program_id: i32,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DegreeAuditQuery {
+    pub program_id: Option<i32>,
+}
+
+fn default_active() -> bool {
+    true
+}
+
+fn validate_program_kind(kind: &str) -> Result<(), ValidationError> {
+    if Program::KINDS.contains(&kind) {
+        return Ok(());
+    }
+    let mut error = ValidationError::new("program_kind");
+    error.message = Some("kind must be major or minor".into());
+    Err(error)
+}
+
+fn validate_requirement_kind(kind: &str) -> Result<(), ValidationError> {
+    if ProgramRequirement::KINDS.contains(&kind) {
+        return Ok(());
+    }
+    let mut error = ValidationError::new("requirement_kind");
+    error.message =
+        Some("kind must be required_courses, choose_credits, min_gpa or residency_credits".into());
+    Err(error)
+}
+
+fn validate_requirement(requirement: &RequirementRequest) -> Result<(), ValidationError> {
+    let lists_courses = !requirement.courses.is_empty();
+    let message = match requirement.kind.as_str() {
+        ProgramRequirement::REQUIRED_COURSES if !lists_courses => {
+            "a required_courses block needs at least one course"
+        }
+        ProgramRequirement::CHOOSE_CREDITS if !lists_courses => {
+            "a choose_credits block needs at least one course"
+        }
+        ProgramRequirement::CHOOSE_CREDITS | ProgramRequirement::RESIDENCY_CREDITS
+            if requirement.min_credits.is_none() =>
+        {
+            "min_credits is required for this kind of block"
+        }
+        ProgramRequirement::MIN_GPA if requirement.min_gpa.is_none() => {
+            "min_gpa is required for a min_gpa block"
+        }
+        _ => return Ok(()),
+    };
+
+    let mut error = ValidationError::new("program_requirement");
+    error.message = Some(message.into());
+    Err(error)
+}
+
+enum ProgramError {
+    NotFound,
+    StudentNotFound,
+    UnknownCourses(Vec<String>),
+    InUse,
+    AlreadyDeclared,
+    NotDeclared,
+    Database(DieselError),
+}
+
+impl From<DieselError> for ProgramError {
+    fn from(error: DieselError) -> Self {
+        ProgramError::Database(error)
+    }
+}
+
+/// Inserts the program's requirement blocks in request order, resolving
+/// course codes to courses.
+fn insert_requirements(
+    conn: &mut PgConnection,
+    program_id: i32,
+    requirements: &[RequirementRequest],
+) -> Result<(), ProgramError> {
+    let mut codes: Vec<String> = requirements
+        .iter()
+        .flat_map(|requirement| requirement.courses.iter().map(|code| normalize_code(code)))
+        .collect();
+    codes.sort();
+    codes.dedup();
+
+    let known: Vec<(i32, String)> = courses::table
+        .filter(courses::code.eq_any(&codes))
+        .select((courses::id, courses::code))
+        .load(conn)?;
+    let unknown: Vec<String> = codes
+        .into_iter()
+        .filter(|code| !known.iter().any(|(_, known)| known == code))
+        .collect();
+    if !unknown.is_empty() {
+        return Err(ProgramError::UnknownCourses(unknown));
+    }
+
+    for (position, requirement) in requirements.iter().enumerate() {
+        let requirement_id = diesel::insert_into(program_requirements::table)
+            .values(&NewProgramRequirement {
+                program_id,
+                position: position as i32 + 1,
+                name: requirement.name.clone(),
+                kind: requirement.kind.clone(),
+                min_credits: requirement.min_credits,
+                min_gpa: requirement.min_gpa,
+            })
+            .returning(program_requirements::id)
+            .get_result::<i32>(conn)?;
+
+        let mut course_ids: Vec<i32> = requirement
+            .courses
+            .iter()
+            .filter_map(|code| {
+                let code = normalize_code(code);
+                known
+                    .iter()
+                    .find(|(_, known)| *known == code)
+                    .map(|(id, _)| *id)
+            })
+            .collect();
+        course_ids.sort();
+        course_ids.dedup();
+
+        let new_courses: Vec<NewProgramRequirementCourse> = course_ids
+            .into_iter()
+            .map(|course_id| NewProgramRequirementCourse {
+                requirement_id,
+                course_id,
+            })
+            .collect();
+        diesel::insert_into(program_requirement_courses::table)
+            .values(&new_courses)
+            .execute(conn)?;
+    }
+
+    Ok(())
+}
+
+fn new_program(program_req: &ProgramRequest) -> NewProgram {
+    NewProgram {
+        code: normalize_code(&program_req.code),
+        name: program_req.name.clone(),
+        kind: program_req.kind.clone(),
+        department: program_req.department.clone(),
+        description: program_req.description.clone(),
+        is_active: program_req.is_active,
+    }
+}
+
+fn find_student(conn: &mut PgConnection, student_id: i32) -> Result<Student, ProgramError> {
+    students::table
+        .find(student_id)
+        .first::<Student>(conn)
+        .optional()?
+        .ok_or(ProgramError::StudentNotFound)
+}
+
+pub async fn get_programs(pool: web::Data<DbPool>) -> HttpResponse {
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => {
+            log::error!("Failed to get database connection: {:?}", e);
+            return HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Database connection error"
+            }));
+        }
+    };
+
+    let result = web::block(move || {
+        let programs = programs::table
+            .order(programs::code.asc())
+            .load::<Program>(&mut conn)?;
+        ProgramDetail::load(&mut conn, programs)
+    })
+    .await;
+
+    match result {
+        Ok(db_result) => match db_result {
+            Ok(programs) => HttpResponse::Ok().json(json!({
+                "status": "success",
+                "data": programs
+            })),
+            Err(db_err) => {
+                log::error!("Database error fetching programs: {:?}", db_err);
+                HttpResponse::InternalServerError().json(json!({
+                    "status": "error",
+                    "message": "Failed to fetch programs"
+                }))
+            }
+        },
+        Err(blocking_err) => {
+            log::error!("Blocking error: {:?}", blocking_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Internal server error"
+            }))
+        }
+    }
+}
+
+pub async fn get_program(pool: web::Data<DbPool>, path: web::Path<i32>) -> HttpResponse {
+    let program_id = path.into_inner();
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => {
+            log::error!("Failed to get database connection: {:?}", e);
+            return HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Database connection error"
+            }));
+        }
+    };
+
+    let result = web::block(move || {
+        let program = programs::table
+            .find(program_id)
+            .first::<Program>(&mut conn)
+            .optional()?
+            .ok_or(ProgramError::NotFound)?;
+        Ok::<_, ProgramError>(ProgramDetail::load(&mut conn, vec![program])?.remove(0))
+    })
+    .await;
+
+    match result {
+        Ok(db_result) => match db_result {
+            Ok(program) => HttpResponse::Ok().json(json!({
+                "status": "success",
+                "data": program
+            })),
+            Err(err) => program_error_response(err, "Failed to fetch program"),
+        },
+        Err(blocking_err) => {
+            log::error!("Blocking error: {:?}", blocking_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Internal server error"
+            }))
+        }
+    }
+}
+
+pub async fn create_program(
+    pool: web::Data<DbPool>,
+    program_req: web::Json<ProgramRequest>,
+) -> HttpResponse {
+    let program_req = program_req.into_inner();
+    if let Err(errors) = program_req.validate() {
+        log::error!("Validation errors: {:?}", errors);
+        return HttpResponse::BadRequest().json(json!({
+            "status": "error",
+            "message": "Validation failed",
+            "errors": errors
+        }));
+    }
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => {
+            log::error!("Failed to get database connection: {:?}", e);
+            return HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Database connection error"
+            }));
+        }
+    };
+
+    let result = web::block(move || {
+        conn.transaction::<_, ProgramError, _>(|conn| {
+            let program = diesel::insert_into(programs::table)
+                .values(&new_program(&program_req))
+                .get_result::<Program>(conn)?;
+            insert_requirements(conn, program.id, &program_req.requirements)?;
+
+            Ok(ProgramDetail::load(conn, vec![program])?.remove(0))
+        })
+    })
+    .await;
+
+    match result {
+        Ok(db_result) => match db_result {
+            Ok(program) => {
+                log::info!("Successfully created program {}", program.program.code);
+                HttpResponse::Created().json(json!({
+                    "status": "success",
+                    "data": program
+                }))
+            }
+            Err(err) => program_error_response(err, "Failed to create program"),
+        },
+        Err(blocking_err) => {
+            log::error!("Blocking error: {:?}", blocking_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Internal server error"
+            }))
+        }
+    }
+}
+
+/// Replaces a program and its requirement blocks.
+pub async fn update_program(
+    pool: web::Data<DbPool>,
+    path: web::Path<i32>,
+    program_req: web::Json<ProgramRequest>,
+) -> HttpResponse {
+    let program_id = path.into_inner();
+    let program_req = program_req.into_inner();
+    if let Err(errors) = program_req.validate() {
+        log::error!("Validation errors: {:?}", errors);
+        return HttpResponse::BadRequest().json(json!({
+            "status": "error",
+            "message": "Validation failed",
+            "errors": errors
+        }));
+    }
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => {
+            log::error!("Failed to get database connection: {:?}", e);
+            return HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Database connection error"
+            }));
+        }
+    };
+
+    let result = web::block(move || {
+        conn.transaction::<_, ProgramError, _>(|conn| {
+            let program = diesel::update(programs::table.find(program_id))
+                .set((
+                    &new_program(&program_req),
+                    programs::updated_at.eq(Utc::now()),
+                ))
+                .get_result::<Program>(conn)
+                .optional()?
+                .ok_or(ProgramError::NotFound)?;
+
+            diesel::delete(
+                program_requirements::table.filter(program_requirements::program_id.eq(program.id)),
+            )
+            .execute(conn)?;
+            insert_requirements(conn, program.id, &program_req.requirements)?;
+
+            Ok(ProgramDetail::load(conn, vec![program])?.remove(0))
+        })
+    })
+    .await;
+
+    match result {
+        Ok(db_result) => match db_result {
+            Ok(program) => {
+                log::info!("Successfully updated program {}", program.program.id);
+                HttpResponse::Ok().json(json!({
+                    "status": "success",
+                    "data": program
+                }))
+            }
+            Err(err) => program_error_response(err, "Failed to update program"),
+        },
+        Err(blocking_err) => {
+            log::error!("Blocking error: {:?}", blocking_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Internal server error"
+            }))
+        }
+    }
+}
+
+pub async fn delete_program(pool: web::Data<DbPool>, path: web::Path<i32>) -> HttpResponse {
+    let program_id = path.into_inner();
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => {
+            log::error!("Failed to get database connection: {:?}", e);
+            return HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Database connection error"
+            }));
+        }
+    };
+
+    let result = web::block(move || {
+        match diesel::delete(programs::table.find(program_id)).execute(&mut conn) {
+            Ok(0) => Err(ProgramError::NotFound),
+            Ok(_) => Ok(()),
+            Err(DieselError::DatabaseError(DatabaseErrorKind::ForeignKeyViolation, _)) => {
+                Err(ProgramError::InUse)
+            }
+            Err(db_err) => Err(db_err.into()),
+        }
+    })
+    .await;
+
+    match result {
+        Ok(db_result) => match db_result {
+            Ok(()) => {
+                log::info!("Successfully deleted program {}", program_id);
+                HttpResponse::Ok().json(json!({
+                    "status": "success",
+                    "message": "Program deleted successfully"
+                }))
+            }
+            Err(err) => program_error_response(err, "Failed to delete program"),
+        },
+        Err(blocking_err) => {
+            log::error!("Blocking error: {:?}", blocking_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Internal server error"
+            }))
+        }
+    }
+}
+
+/// The majors and minors a student has declared.
+pub async fn get_student_programs(pool: web::Data<DbPool>, path: web::Path<i32>) -> HttpResponse {
+    let student_id = path.into_inner();
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => {
+            log::error!("Failed to get database connection: {:?}", e);
+            return HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Database connection error"
+            }));
+        }
+    };
+
+    let result = web::block(move || {
+        find_student(&mut conn, student_id)?;
+        Ok::<_, ProgramError>(DeclaredProgram::load_for_student(&mut conn, student_id)?)
+    })
+    .await;
+
+    match result {
+        Ok(db_result) => match db_result {
+            Ok(programs) => HttpResponse::Ok().json(json!({
+                "status": "success",
+                "data": programs
+            })),
+            Err(err) => program_error_response(err, "Failed to fetch student programs"),
+        },
+        Err(blocking_err) => {
+            log::error!("Blocking error: {:?}", blocking_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Internal server error"
+            }))
+        }
+    }
+}
+
+pub async fn declare_program(
+    audit: AuditContext,
+    pool: web::Data<DbPool>,
+    path: web::Path<i32>,
+    declare_req: web::Json<DeclareProgramRequest>,
+) -> HttpResponse {
+    let student_id = path.into_inner();
+    let program_id = declare_req.program_id;
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => {
+            log::error!("Failed to get database connection: {:?}", e);
+            return HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Database connection error"
+            }));
+        }
+    };
+
+    let result = web::block(move || {
+        conn.transaction::<_, ProgramError, _>(|conn| {
+            find_student(conn, student_id)?;
+            let program = programs::table
+                .find(program_id)
+                .filter(programs::is_active.eq(true))
+                .first::<Program>(conn)
+                .optional()?
+                .ok_or(ProgramError::NotFound)?;
+
+            let declaration = diesel::insert_into(student_programs::table)
+                .values(&NewStudentProgram {
+                    student_id,
+                    program_id: program.id,
+                    created_by: audit.user_id,
+                })
+                .get_result::<StudentProgram>(conn)
+                .map_err(|err| match err {
+                    DieselError::DatabaseError(DatabaseErrorKind::UniqueViolation, _) => {
+                        ProgramError::AlreadyDeclared
+                    }
+                    err => err.into(),
+                })?;
+
+            diesel::insert_into(audit_logs::table)
+                .values(&audit.activity(
+                    "program.declare",
+                    "student",
+                    Some(student_id),
+                    Some(json!({ "program_id": program.id, "program_code": program.code })),
+                ))
+                .execute(conn)?;
+
+            Ok(DeclaredProgram {
+                declaration,
+                program,
+            })
+        })
+    })
+    .await;
+
+    match result {
+        Ok(db_result) => match db_result {
+            Ok(declared) => HttpResponse::Created().json(json!({
+                "status": "success",
+                "data": declared
+            })),
+            Err(err) => program_error_response(err, "Failed to declare program"),
+        },
+        Err(blocking_err) => {
+            log::error!("Blocking error: {:?}", blocking_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Internal server error"
+            }))
+        }
+    }
+}
+
+pub async fn undeclare_program(
+    audit: AuditContext,
+    pool: web::Data<DbPool>,
+    path: web::Path<(i32, i32)>,
+) -> HttpResponse {
+    let (student_id, program_id) = path.into_inner();
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => {
+            log::error!("Failed to get database connection: {:?}", e);
+            return HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Database connection error"
+            }));
+        }
+    };
+
+    let result = web::block(move || {
+        conn.transaction::<_, ProgramError, _>(|conn| {
+            let declaration = diesel::delete(
+                student_programs::table
+                    .filter(student_programs::student_id.eq(student_id))
+                    .filter(student_programs::program_id.eq(program_id)),
+            )
+            .get_result::<StudentProgram>(conn)
+            .optional()?
+            .ok_or(ProgramError::NotDeclared)?;
+
+            diesel::insert_into(audit_logs::table)
+                .values(&audit.activity(
+                    "program.undeclare",
+                    "student",
+                    Some(student_id),
+                    Some(json!({ "before": declaration })),
+                ))
+                .execute(conn)?;
+            Ok(())
+        })
+    })
+    .await;
+
+    match result {
+        Ok(db_result) => match db_result {
+            Ok(()) => HttpResponse::Ok().json(json!({
+                "status": "success",
+                "message": "Program removed from the student's record"
+            })),
+            Err(err) => program_error_response(err, "Failed to remove program"),
+        },
+        Err(blocking_err) => {
+            log::error!("Blocking error: {:?}", blocking_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Internal server error"
+            }))
+        }
+    }
+}
+
+/// Evaluates the student's record against their declared programs.
+pub async fn get_degree_audit(
+    _user: AuthenticatedUser,
+    pool: web::Data<DbPool>,
+    path: web::Path<i32>,
+    query: web::Query<DegreeAuditQuery>,
+) -> HttpResponse {
+    let student_id = path.into_inner();
+    let program_id = query.program_id;
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => {
+            log::error!("Failed to get database connection: {:?}", e);
+            return HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Database connection error"
+            }));
+        }
+    };
+
+    let result = web::block(move || {
+        find_student(&mut conn, student_id)?;
+        let audit = degree_audit::audit(&mut conn, student_id, program_id)?;
+        if program_id.is_some() && audit.programs.is_empty() {
+            return Err(ProgramError::NotDeclared);
+        }
+        Ok(audit)
+    })
+    .await;
+
+    match result {
+        Ok(db_result) => match db_result {
+            Ok(audit) => HttpResponse::Ok().json(json!({
+                "status": "success",
+                "data": audit
+            })),
+            Err(err) => program_error_response(err, "Failed to run degree audit"),
+        },
+        Err(blocking_err) => {
+            log::error!("Blocking error: {:?}", blocking_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Internal server error"
+            }))
+        }
+    }
+}
+
+fn program_error_response(err: ProgramError, failure_message: &str) -> HttpResponse {
+    match err {
+        ProgramError::NotFound => HttpResponse::NotFound().json(json!({
+            "status": "error",
+            "message": "Program not found"
+        })),
+        ProgramError::StudentNotFound => HttpResponse::NotFound().json(json!({
+            "status": "error",
+            "message": "Student not found"
+        })),
+        ProgramError::NotDeclared => HttpResponse::NotFound().json(json!({
+            "status": "error",
+            "message": "Student has not declared this program"
+        })),
+        ProgramError::UnknownCourses(codes) => HttpResponse::UnprocessableEntity().json(json!({
+            "status": "error",
+            "message": "Requirements refer to courses that do not exist",
+            "courses": codes
+        })),
+        ProgramError::InUse => HttpResponse::Conflict().json(json!({
+            "status": "error",
+            "message": "Program has declared students; deactivate it instead"
+        })),
+        ProgramError::AlreadyDeclared => HttpResponse::Conflict().json(json!({
+            "status": "error",
+            "message": "Student has already declared this program"
+        })),
+        ProgramError::Database(DieselError::DatabaseError(
+            DatabaseErrorKind::UniqueViolation,
+            _,
+        )) => HttpResponse::Conflict().json(json!({
+            "status": "error",
+            "message": "A program with this code already exists"
+        })),
+        ProgramError::Database(db_err) => {
+            log::error!("Database error: {:?}", db_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": failure_message
+            }))
+        }
+    }
+}
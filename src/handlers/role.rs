@@ -0,0 +1,555 @@
+use std::collections::BTreeSet;
+
+use actix_web::{web, HttpResponse};
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel::result::{DatabaseErrorKind, Error as DieselError};
+use serde::Deserialize;
+use serde_json::json;
+use validator::Validate;
+
+use crate::handlers::auth::UserResponse;
+use crate::models::permission::{NewRolePermission, Permission, RoleDetail};
+use crate::models::role::{NewRole, Role};
+use crate::models::user::User;
+use crate::models::AuditContext;
+use crate::schema::{audit_logs, permissions, role_permissions, roles, user_sessions, users};
+use crate::services::tokens::{self, TokenDenylist};
+use crate::DbPool;
+
+/// The complete set of permissions a role should hold.
+#[derive(Debug, Deserialize)]
+pub struct RolePermissionsRequest {
+    pub permissions: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UserRoleRequest {
+    pub role_id: i32,
+}
+
+enum RoleError {
+    RoleNotFound,
+    DuplicateRole,
+    /// The admin role is referenced by name throughout the code.
+    ProtectedRole,
+    RoleInUse,
+    UnknownPermissions(Vec<String>),
+    UserNotFound,
+    Database(DieselError),
+}
+
+impl From<DieselError> for RoleError {
+    fn from(error: DieselError) -> Self {
+        RoleError::Database(error)
+    }
+}
+
+fn find_role(conn: &mut PgConnection, role_id: i32) -> Result<Role, RoleError> {
+    roles::table
+        .find(role_id)
+        .first::<Role>(conn)
+        .optional()?
+        .ok_or(RoleError::RoleNotFound)
+}
+
+fn duplicate_role(error: DieselError) -> RoleError {
+    match error {
+        DieselError::DatabaseError(DatabaseErrorKind::UniqueViolation, _) => {
+            RoleError::DuplicateRole
+        }
+        other => other.into(),
+    }
+}
+
+pub async fn get_roles(pool: web::Data<DbPool>) -> HttpResponse {
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => {
+            log::error!("Failed to get database connection: {:?}", e);
+            return HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Database connection error"
+            }));
+        }
+    };
+
+    let result = web::block(move || {
+        let roles = roles::table
+            .order(roles::name.asc())
+            .load::<Role>(&mut conn)?;
+        RoleDetail::load(&mut conn, roles)
+    })
+    .await;
+
+    match result {
+        Ok(db_result) => match db_result {
+            Ok(roles) => HttpResponse::Ok().json(json!({
+                "status": "success",
+                "data": roles
+            })),
+            Err(db_err) => role_error_response(db_err.into(), "Failed to fetch roles"),
+        },
+        Err(blocking_err) => {
+            log::error!("Blocking error: {:?}", blocking_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Internal server error"
+            }))
+        }
+    }
+}
+
+pub async fn get_role(pool: web::Data<DbPool>, path: web::Path<i32>) -> HttpResponse {
+    let role_id = path.into_inner();
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => {
+            log::error!("Failed to get database connection: {:?}", e);
+            return HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Database connection error"
+            }));
+        }
+    };
+
+    let result = web::block(move || {
+        let role = find_role(&mut conn, role_id)?;
+        Ok::<_, RoleError>(RoleDetail::load(&mut conn, vec![role])?.remove(0))
+    })
+    .await;
+
+    match result {
+        Ok(db_result) => match db_result {
+            Ok(role) => HttpResponse::Ok().json(json!({
+                "status": "success",
+                "data": role
+            })),
+            Err(err) => role_error_response(err, "Failed to fetch role"),
+        },
+        Err(blocking_err) => {
+            log::error!("Blocking error: {:?}", blocking_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Internal server error"
+            }))
+        }
+    }
+}
+
+pub async fn create_role(pool: web::Data<DbPool>, role_req: web::Json<NewRole>) -> HttpResponse {
+    let role_req = role_req.into_inner();
+    if let Err(errors) = role_req.validate() {
+        log::error!("Validation errors: {:?}", errors);
+        return HttpResponse::BadRequest().json(json!({
+            "status": "error",
+            "message": "Validation failed",
+            "errors": errors
+        }));
+    }
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => {
+            log::error!("Failed to get database connection: {:?}", e);
+            return HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Database connection error"
+            }));
+        }
+    };
+
+    let result = web::block(move || {
+        let role = diesel::insert_into(roles::table)
+            .values(&role_req)
+            .get_result::<Role>(&mut conn)
+            .map_err(duplicate_role)?;
+        Ok::<_, RoleError>(RoleDetail::load(&mut conn, vec![role])?.remove(0))
+    })
+    .await;
+
+    match result {
+        Ok(db_result) => match db_result {
+            Ok(role) => {
+                log::info!("Successfully created role {}", role.role.name);
+                HttpResponse::Created().json(json!({
+                    "status": "success",
+                    "data": role
+                }))
+            }
+            Err(err) => role_error_response(err, "Failed to create role"),
+        },
+        Err(blocking_err) => {
+            log::error!("Blocking error: {:?}", blocking_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Internal server error"
+            }))
+        }
+    }
+}
+
+pub async fn update_role(
+    pool: web::Data<DbPool>,
+    path: web::Path<i32>,
+    role_req: web::Json<NewRole>,
+) -> HttpResponse {
+    let role_id = path.into_inner();
+    let role_req = role_req.into_inner();
+    if let Err(errors) = role_req.validate() {
+        log::error!("Validation errors: {:?}", errors);
+        return HttpResponse::BadRequest().json(json!({
+            "status": "error",
+            "message": "Validation failed",
+            "errors": errors
+        }));
+    }
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => {
+            log::error!("Failed to get database connection: {:?}", e);
+            return HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Database connection error"
+            }));
+        }
+    };
+
+    let result = web::block(move || {
+        let existing = find_role(&mut conn, role_id)?;
+        if existing.is_admin() && role_req.name != existing.name {
+            return Err(RoleError::ProtectedRole);
+        }
+        let role = diesel::update(roles::table.find(existing.id))
+            .set((&role_req, roles::updated_at.eq(Utc::now())))
+            .get_result::<Role>(&mut conn)
+            .map_err(duplicate_role)?;
+        Ok(RoleDetail::load(&mut conn, vec![role])?.remove(0))
+    })
+    .await;
+
+    match result {
+        Ok(db_result) => match db_result {
+            Ok(role) => {
+                log::info!("Successfully updated role {}", role.role.id);
+                HttpResponse::Ok().json(json!({
+                    "status": "success",
+                    "data": role
+                }))
+            }
+            Err(err) => role_error_response(err, "Failed to update role"),
+        },
+        Err(blocking_err) => {
+            log::error!("Blocking error: {:?}", blocking_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Internal server error"
+            }))
+        }
+    }
+}
+
+pub async fn delete_role(pool: web::Data<DbPool>, path: web::Path<i32>) -> HttpResponse {
+    let role_id = path.into_inner();
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => {
+            log::error!("Failed to get database connection: {:?}", e);
+            return HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Database connection error"
+            }));
+        }
+    };
+
+    let result = web::block(move || {
+        let role = find_role(&mut conn, role_id)?;
+        if role.is_admin() {
+            return Err(RoleError::ProtectedRole);
+        }
+        diesel::delete(roles::table.find(role.id))
+            .execute(&mut conn)
+            .map_err(|e| match e {
+                DieselError::DatabaseError(DatabaseErrorKind::ForeignKeyViolation, _) => {
+                    RoleError::RoleInUse
+                }
+                other => other.into(),
+            })?;
+        Ok(())
+    })
+    .await;
+
+    match result {
+        Ok(db_result) => match db_result {
+            Ok(()) => {
+                log::info!("Successfully deleted role {}", role_id);
+                HttpResponse::Ok().json(json!({
+                    "status": "success",
+                    "message": "Role deleted successfully"
+                }))
+            }
+            Err(err) => role_error_response(err, "Failed to delete role"),
+        },
+        Err(blocking_err) => {
+            log::error!("Blocking error: {:?}", blocking_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Internal server error"
+            }))
+        }
+    }
+}
+
+pub async fn get_permissions(pool: web::Data<DbPool>) -> HttpResponse {
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => {
+            log::error!("Failed to get database connection: {:?}", e);
+            return HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Database connection error"
+            }));
+        }
+    };
+
+    let result = web::block(move || {
+        permissions::table
+            .order(permissions::name.asc())
+            .load::<Permission>(&mut conn)
+    })
+    .await;
+
+    match result {
+        Ok(db_result) => match db_result {
+            Ok(permissions) => HttpResponse::Ok().json(json!({
+                "status": "success",
+                "data": permissions
+            })),
+            Err(db_err) => role_error_response(db_err.into(), "Failed to fetch permissions"),
+        },
+        Err(blocking_err) => {
+            log::error!("Blocking error: {:?}", blocking_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Internal server error"
+            }))
+        }
+    }
+}
+
+/// Replaces the permissions granted to a role. Takes effect on the next
+/// request of every user with the role.
+pub async fn update_role_permissions(
+    audit: AuditContext,
+    pool: web::Data<DbPool>,
+    path: web::Path<i32>,
+    permissions_req: web::Json<RolePermissionsRequest>,
+) -> HttpResponse {
+    let role_id = path.into_inner();
+    let wanted: BTreeSet<String> = permissions_req
+        .into_inner()
+        .permissions
+        .into_iter()
+        .collect();
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => {
+            log::error!("Failed to get database connection: {:?}", e);
+            return HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Database connection error"
+            }));
+        }
+    };
+
+    let result = web::block(move || {
+        conn.transaction::<_, RoleError, _>(|conn| {
+            let role = find_role(conn, role_id)?;
+            let found = permissions::table
+                .filter(permissions::name.eq_any(&wanted))
+                .load::<Permission>(conn)?;
+            let unknown: Vec<String> = wanted
+                .iter()
+                .filter(|name| !found.iter().any(|permission| &permission.name == *name))
+                .cloned()
+                .collect();
+            if !unknown.is_empty() {
+                return Err(RoleError::UnknownPermissions(unknown));
+            }
+
+            diesel::delete(role_permissions::table.filter(role_permissions::role_id.eq(role.id)))
+                .execute(conn)?;
+            let grants: Vec<NewRolePermission> = found
+                .iter()
+                .map(|permission| NewRolePermission {
+                    role_id: role.id,
+                    permission_id: permission.id,
+                })
+                .collect();
+            diesel::insert_into(role_permissions::table)
+                .values(&grants)
+                .execute(conn)?;
+
+            diesel::insert_into(audit_logs::table)
+                .values(&audit.activity(
+                    "role.permissions",
+                    "role",
+                    Some(role.id),
+                    Some(json!({ "permissions": wanted })),
+                ))
+                .execute(conn)?;
+
+            Ok(RoleDetail::load(conn, vec![role])?.remove(0))
+        })
+    })
+    .await;
+
+    match result {
+        Ok(db_result) => match db_result {
+            Ok(role) => {
+                log::info!("Updated the permissions of role {}", role.role.name);
+                HttpResponse::Ok().json(json!({
+                    "status": "success",
+                    "data": role
+                }))
+            }
+            Err(err) => role_error_response(err, "Failed to update role permissions"),
+        },
+        Err(blocking_err) => {
+            log::error!("Blocking error: {:?}", blocking_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Internal server error"
+            }))
+        }
+    }
+}
+
+/// Moves a user to another role. Takes effect when their access token is
+/// next refreshed.
+pub async fn update_user_role(
+    audit: AuditContext,
+    pool: web::Data<DbPool>,
+    denylist: web::Data<TokenDenylist>,
+    path: web::Path<i32>,
+    role_req: web::Json<UserRoleRequest>,
+) -> HttpResponse {
+    let user_id = path.into_inner();
+    let role_id = role_req.into_inner().role_id;
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => {
+            log::error!("Failed to get database connection: {:?}", e);
+            return HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Database connection error"
+            }));
+        }
+    };
+
+    let result = web::block(move || {
+        conn.transaction::<_, RoleError, _>(|conn| {
+            let role = find_role(conn, role_id)?;
+            let before = users::table
+                .find(user_id)
+                .for_update()
+                .first::<User>(conn)
+                .optional()?
+                .ok_or(RoleError::UserNotFound)?;
+
+            let user = diesel::update(users::table.find(user_id))
+                .set((users::role_id.eq(role.id), users::updated_at.eq(Utc::now())))
+                .get_result::<User>(conn)?;
+
+            // Access tokens carry the role, so sign the user out everywhere
+            // rather than let the old role live on until they expire
+            let session_ids = if before.role_id == user.role_id {
+                Vec::new()
+            } else {
+                tokens::active_sessions()
+                    .filter(user_sessions::user_id.eq(user.id))
+                    .select(user_sessions::id)
+                    .load::<i32>(conn)?
+            };
+            let (revoked, denied) = tokens::revoke_sessions(conn, &session_ids)?;
+
+            diesel::insert_into(audit_logs::table)
+                .values(&audit.activity(
+                    "user.role",
+                    "user",
+                    Some(user.id),
+                    Some(json!({
+                        "before": before.role_id,
+                        "after": user.role_id,
+                        "sessions": revoked
+                    })),
+                ))
+                .execute(conn)?;
+
+            Ok((UserResponse::from(user), role, denied))
+        })
+    })
+    .await;
+
+    match result {
+        Ok(db_result) => match db_result {
+            Ok((user, role, denied)) => {
+                denylist.extend(denied);
+                log::info!("Moved user {} to role {}", user.id, role.name);
+                HttpResponse::Ok().json(json!({
+                    "status": "success",
+                    "data": user
+                }))
+            }
+            Err(err) => role_error_response(err, "Failed to update user role"),
+        },
+        Err(blocking_err) => {
+            log::error!("Blocking error: {:?}", blocking_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Internal server error"
+            }))
+        }
+    }
+}
+
+fn role_error_response(err: RoleError, failure_message: &str) -> HttpResponse {
+    match err {
+        RoleError::RoleNotFound => HttpResponse::NotFound().json(json!({
+            "status": "error",
+            "message": "Role not found"
+        })),
+        RoleError::DuplicateRole => HttpResponse::Conflict().json(json!({
+            "status": "error",
+            "message": "A role with that name already exists"
+        })),
+        RoleError::ProtectedRole => HttpResponse::UnprocessableEntity().json(json!({
+            "status": "error",
+            "message": "The admin role cannot be renamed or deleted"
+        })),
+        RoleError::RoleInUse => HttpResponse::Conflict().json(json!({
+            "status": "error",
+            "message": "Users are still assigned to this role"
+        })),
+        RoleError::UnknownPermissions(names) => HttpResponse::UnprocessableEntity().json(json!({
+            "status": "error",
+            "message": format!("Unknown permissions: {}", names.join(", "))
+        })),
+        RoleError::UserNotFound => HttpResponse::NotFound().json(json!({
+            "status": "error",
+            "message": "User not found"
+        })),
+        RoleError::Database(db_err) => {
+            log::error!("Database error on roles: {:?}", db_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": failure_message
+            }))
+        }
+    }
+}
@@ -0,0 +1,774 @@
+use std::collections::BTreeSet;
+
+use actix_web::{web, HttpResponse};
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel::result::{DatabaseErrorKind, Error as DieselError};
+use serde::Deserialize;
+use serde_json::json;
+use validator::{Validate, ValidationError};
+
+use crate::models::room::{Building, NewBuilding, NewRoom, Room, RoomDetail, RoomFeature};
+use crate::models::Term;
+use crate::schema::{buildings, room_features, rooms, sections, terms};
+use crate::services::room_utilization::{self, AVAILABLE_MINUTES_PER_WEEK};
+use crate::DbPool;
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct BuildingRequest {
+    #[validate(length(min = 1, max = 10))]
+    pub code: String,
+    #[validate(length(min = 1, max = 100))]
+    pub name: String,
+}
+
+/// A room as created or edited. The features replace the room's current
+/// ones and are stored trimmed and lowercase.
+#[derive(Debug, Deserialize, Validate)]
+pub struct RoomRequest {
+    pub building_id: i32,
+    #[validate(length(min = 1, max = 20))]
+    pub room_number: String,
+    #[validate(range(min = 0, max = 2000))]
+    pub capacity: i32,
+    #[validate(custom = "validate_features")]
+    #[serde(default)]
+    pub features: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RoomQuery {
+    pub building_id: Option<i32>,
+    pub min_capacity: Option<i32>,
+    /// Comma-separated features the room must all have, e.g. `projector,lab`.
+    pub features: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UtilizationQuery {
+    pub building_id: Option<i32>,
+}
+
+fn validate_features(features: &[String]) -> Result<(), ValidationError> {
+    if features
+        .iter()
+        .all(|feature| (1..=30).contains(&feature.trim().chars().count()))
+    {
+        return Ok(());
+    }
+    let mut error = ValidationError::new("features");
+    error.message = Some("features must be between 1 and 30 characters".into());
+    Err(error)
+}
+
+fn normalize_features(features: &[String]) -> BTreeSet<String> {
+    features
+        .iter()
+        .map(|feature| feature.trim().to_lowercase())
+        .collect()
+}
+
+enum RoomError {
+    TermNotFound,
+    BuildingNotFound,
+    RoomNotFound,
+    DuplicateBuilding,
+    DuplicateRoom,
+    BuildingHasRooms,
+    RoomBooked,
+    CapacityBelowBookings(i32),
+    Database(DieselError),
+}
+
+impl From<DieselError> for RoomError {
+    fn from(error: DieselError) -> Self {
+        RoomError::Database(error)
+    }
+}
+
+fn find_room(conn: &mut PgConnection, room_id: i32) -> Result<Room, RoomError> {
+    rooms::table
+        .find(room_id)
+        .first::<Room>(conn)
+        .optional()?
+        .ok_or(RoomError::RoomNotFound)
+}
+
+/// Maps the constraint violations of a room insert or update.
+fn room_write_error(error: DieselError) -> RoomError {
+    match error {
+        DieselError::DatabaseError(DatabaseErrorKind::UniqueViolation, _) => {
+            RoomError::DuplicateRoom
+        }
+        DieselError::DatabaseError(DatabaseErrorKind::ForeignKeyViolation, _) => {
+            RoomError::BuildingNotFound
+        }
+        other => other.into(),
+    }
+}
+
+fn replace_features(conn: &mut PgConnection, room_id: i32, features: &[String]) -> QueryResult<()> {
+    diesel::delete(room_features::table.filter(room_features::room_id.eq(room_id)))
+        .execute(conn)?;
+    let new_features: Vec<RoomFeature> = normalize_features(features)
+        .into_iter()
+        .map(|feature| RoomFeature { room_id, feature })
+        .collect();
+    diesel::insert_into(room_features::table)
+        .values(&new_features)
+        .execute(conn)?;
+    Ok(())
+}
+
+pub async fn get_buildings(pool: web::Data<DbPool>) -> HttpResponse {
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => {
+            log::error!("Failed to get database connection: {:?}", e);
+            return HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Database connection error"
+            }));
+        }
+    };
+
+    let result = web::block(move || {
+        buildings::table
+            .order(buildings::code.asc())
+            .load::<Building>(&mut conn)
+    })
+    .await;
+
+    match result {
+        Ok(db_result) => match db_result {
+            Ok(buildings) => HttpResponse::Ok().json(json!({
+                "status": "success",
+                "data": buildings
+            })),
+            Err(db_err) => room_error_response(db_err.into(), "Failed to fetch buildings"),
+        },
+        Err(blocking_err) => {
+            log::error!("Blocking error: {:?}", blocking_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Internal server error"
+            }))
+        }
+    }
+}
+
+pub async fn get_building(pool: web::Data<DbPool>, path: web::Path<i32>) -> HttpResponse {
+    let building_id = path.into_inner();
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => {
+            log::error!("Failed to get database connection: {:?}", e);
+            return HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Database connection error"
+            }));
+        }
+    };
+
+    let result = web::block(move || {
+        let building = buildings::table
+            .find(building_id)
+            .first::<Building>(&mut conn)
+            .optional()?
+            .ok_or(RoomError::BuildingNotFound)?;
+        let rooms = rooms::table
+            .filter(rooms::building_id.eq(building.id))
+            .order(rooms::room_number.asc())
+            .load::<Room>(&mut conn)?;
+        Ok::<_, RoomError>((building, RoomDetail::load(&mut conn, rooms)?))
+    })
+    .await;
+
+    match result {
+        Ok(db_result) => match db_result {
+            Ok((building, rooms)) => HttpResponse::Ok().json(json!({
+                "status": "success",
+                "data": {
+                    "building": building,
+                    "rooms": rooms
+                }
+            })),
+            Err(err) => room_error_response(err, "Failed to fetch building"),
+        },
+        Err(blocking_err) => {
+            log::error!("Blocking error: {:?}", blocking_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Internal server error"
+            }))
+        }
+    }
+}
+
+pub async fn create_building(
+    pool: web::Data<DbPool>,
+    building_req: web::Json<BuildingRequest>,
+) -> HttpResponse {
+    let building_req = building_req.into_inner();
+    if let Err(errors) = building_req.validate() {
+        log::error!("Validation errors: {:?}", errors);
+        return HttpResponse::BadRequest().json(json!({
+            "status": "error",
+            "message": "Validation failed",
+            "errors": errors
+        }));
+    }
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => {
+            log::error!("Failed to get database connection: {:?}", e);
+            return HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Database connection error"
+            }));
+        }
+    };
+
+    let result = web::block(move || {
+        diesel::insert_into(buildings::table)
+            .values(&NewBuilding {
+                code: building_req.code,
+                name: building_req.name,
+            })
+            .get_result::<Building>(&mut conn)
+            .map_err(|e| match e {
+                DieselError::DatabaseError(DatabaseErrorKind::UniqueViolation, _) => {
+                    RoomError::DuplicateBuilding
+                }
+                other => other.into(),
+            })
+    })
+    .await;
+
+    match result {
+        Ok(db_result) => match db_result {
+            Ok(building) => {
+                log::info!("Successfully created building {}", building.code);
+                HttpResponse::Created().json(json!({
+                    "status": "success",
+                    "data": building
+                }))
+            }
+            Err(err) => room_error_response(err, "Failed to create building"),
+        },
+        Err(blocking_err) => {
+            log::error!("Blocking error: {:?}", blocking_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Internal server error"
+            }))
+        }
+    }
+}
+
+pub async fn update_building(
+    pool: web::Data<DbPool>,
+    path: web::Path<i32>,
+    building_req: web::Json<BuildingRequest>,
+) -> HttpResponse {
+    let building_id = path.into_inner();
+    let building_req = building_req.into_inner();
+    if let Err(errors) = building_req.validate() {
+        log::error!("Validation errors: {:?}", errors);
+        return HttpResponse::BadRequest().json(json!({
+            "status": "error",
+            "message": "Validation failed",
+            "errors": errors
+        }));
+    }
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => {
+            log::error!("Failed to get database connection: {:?}", e);
+            return HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Database connection error"
+            }));
+        }
+    };
+
+    let result = web::block(move || {
+        diesel::update(buildings::table.find(building_id))
+            .set((
+                &NewBuilding {
+                    code: building_req.code,
+                    name: building_req.name,
+                },
+                buildings::updated_at.eq(Utc::now()),
+            ))
+            .get_result::<Building>(&mut conn)
+            .optional()
+            .map_err(|e| match e {
+                DieselError::DatabaseError(DatabaseErrorKind::UniqueViolation, _) => {
+                    RoomError::DuplicateBuilding
+                }
+                other => other.into(),
+            })?
+            .ok_or(RoomError::BuildingNotFound)
+    })
+    .await;
+
+    match result {
+        Ok(db_result) => match db_result {
+            Ok(building) => {
+                log::info!("Successfully updated building {}", building.id);
+                HttpResponse::Ok().json(json!({
+                    "status": "success",
+                    "data": building
+                }))
+            }
+            Err(err) => room_error_response(err, "Failed to update building"),
+        },
+        Err(blocking_err) => {
+            log::error!("Blocking error: {:?}", blocking_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Internal server error"
+            }))
+        }
+    }
+}
+
+pub async fn delete_building(pool: web::Data<DbPool>, path: web::Path<i32>) -> HttpResponse {
+    let building_id = path.into_inner();
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => {
+            log::error!("Failed to get database connection: {:?}", e);
+            return HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Database connection error"
+            }));
+        }
+    };
+
+    let result = web::block(move || {
+        let deleted = diesel::delete(buildings::table.find(building_id))
+            .execute(&mut conn)
+            .map_err(|e| match e {
+                DieselError::DatabaseError(DatabaseErrorKind::ForeignKeyViolation, _) => {
+                    RoomError::BuildingHasRooms
+                }
+                other => other.into(),
+            })?;
+        match deleted {
+            0 => Err(RoomError::BuildingNotFound),
+            _ => Ok(()),
+        }
+    })
+    .await;
+
+    match result {
+        Ok(db_result) => match db_result {
+            Ok(()) => {
+                log::info!("Successfully deleted building {}", building_id);
+                HttpResponse::Ok().json(json!({
+                    "status": "success",
+                    "message": "Building deleted successfully"
+                }))
+            }
+            Err(err) => room_error_response(err, "Failed to delete building"),
+        },
+        Err(blocking_err) => {
+            log::error!("Blocking error: {:?}", blocking_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Internal server error"
+            }))
+        }
+    }
+}
+
+pub async fn get_rooms(pool: web::Data<DbPool>, query: web::Query<RoomQuery>) -> HttpResponse {
+    let query = query.into_inner();
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => {
+            log::error!("Failed to get database connection: {:?}", e);
+            return HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Database connection error"
+            }));
+        }
+    };
+
+    let result = web::block(move || {
+        let mut rooms_query = rooms::table
+            .inner_join(buildings::table)
+            .order((buildings::code.asc(), rooms::room_number.asc()))
+            .select(rooms::all_columns)
+            .into_boxed();
+
+        if let Some(building_id) = query.building_id {
+            rooms_query = rooms_query.filter(rooms::building_id.eq(building_id));
+        }
+        if let Some(min_capacity) = query.min_capacity {
+            rooms_query = rooms_query.filter(rooms::capacity.ge(min_capacity));
+        }
+        let wanted: Vec<String> = query
+            .features
+            .as_deref()
+            .map(|features| features.split(',').map(String::from).collect())
+            .unwrap_or_default();
+        for feature in normalize_features(&wanted) {
+            rooms_query = rooms_query.filter(
+                rooms::id.eq_any(
+                    room_features::table
+                        .filter(room_features::feature.eq(feature))
+                        .select(room_features::room_id),
+                ),
+            );
+        }
+
+        let rooms = rooms_query.load::<Room>(&mut conn)?;
+        RoomDetail::load(&mut conn, rooms)
+    })
+    .await;
+
+    match result {
+        Ok(db_result) => match db_result {
+            Ok(rooms) => HttpResponse::Ok().json(json!({
+                "status": "success",
+                "data": rooms
+            })),
+            Err(db_err) => room_error_response(db_err.into(), "Failed to fetch rooms"),
+        },
+        Err(blocking_err) => {
+            log::error!("Blocking error: {:?}", blocking_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Internal server error"
+            }))
+        }
+    }
+}
+
+pub async fn get_room(pool: web::Data<DbPool>, path: web::Path<i32>) -> HttpResponse {
+    let room_id = path.into_inner();
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => {
+            log::error!("Failed to get database connection: {:?}", e);
+            return HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Database connection error"
+            }));
+        }
+    };
+
+    let result = web::block(move || {
+        let room = find_room(&mut conn, room_id)?;
+        Ok::<_, RoomError>(RoomDetail::load(&mut conn, vec![room])?.remove(0))
+    })
+    .await;
+
+    match result {
+        Ok(db_result) => match db_result {
+            Ok(room) => HttpResponse::Ok().json(json!({
+                "status": "success",
+                "data": room
+            })),
+            Err(err) => room_error_response(err, "Failed to fetch room"),
+        },
+        Err(blocking_err) => {
+            log::error!("Blocking error: {:?}", blocking_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Internal server error"
+            }))
+        }
+    }
+}
+
+pub async fn create_room(
+    pool: web::Data<DbPool>,
+    room_req: web::Json<RoomRequest>,
+) -> HttpResponse {
+    let room_req = room_req.into_inner();
+    if let Err(errors) = room_req.validate() {
+        log::error!("Validation errors: {:?}", errors);
+        return HttpResponse::BadRequest().json(json!({
+            "status": "error",
+            "message": "Validation failed",
+            "errors": errors
+        }));
+    }
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => {
+            log::error!("Failed to get database connection: {:?}", e);
+            return HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Database connection error"
+            }));
+        }
+    };
+
+    let result = web::block(move || {
+        conn.transaction::<_, RoomError, _>(|conn| {
+            let room = diesel::insert_into(rooms::table)
+                .values(&NewRoom {
+                    building_id: room_req.building_id,
+                    room_number: room_req.room_number,
+                    capacity: room_req.capacity,
+                })
+                .get_result::<Room>(conn)
+                .map_err(room_write_error)?;
+            replace_features(conn, room.id, &room_req.features)?;
+            Ok(RoomDetail::load(conn, vec![room])?.remove(0))
+        })
+    })
+    .await;
+
+    match result {
+        Ok(db_result) => match db_result {
+            Ok(room) => {
+                log::info!("Successfully created room {}", room.room.id);
+                HttpResponse::Created().json(json!({
+                    "status": "success",
+                    "data": room
+                }))
+            }
+            Err(err) => room_error_response(err, "Failed to create room"),
+        },
+        Err(blocking_err) => {
+            log::error!("Blocking error: {:?}", blocking_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Internal server error"
+            }))
+        }
+    }
+}
+
+/// Replaces a room's details and features. The capacity may not drop below
+/// a section booked into the room in a term that has not ended.
+pub async fn update_room(
+    pool: web::Data<DbPool>,
+    path: web::Path<i32>,
+    room_req: web::Json<RoomRequest>,
+) -> HttpResponse {
+    let room_id = path.into_inner();
+    let room_req = room_req.into_inner();
+    if let Err(errors) = room_req.validate() {
+        log::error!("Validation errors: {:?}", errors);
+        return HttpResponse::BadRequest().json(json!({
+            "status": "error",
+            "message": "Validation failed",
+            "errors": errors
+        }));
+    }
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => {
+            log::error!("Failed to get database connection: {:?}", e);
+            return HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Database connection error"
+            }));
+        }
+    };
+
+    let result = web::block(move || {
+        conn.transaction::<_, RoomError, _>(|conn| {
+            // Locked like a section booking so the two cannot interleave.
+            rooms::table
+                .find(room_id)
+                .for_update()
+                .first::<Room>(conn)
+                .optional()?
+                .ok_or(RoomError::RoomNotFound)?;
+
+            let largest_booking: Option<i32> = sections::table
+                .inner_join(terms::table)
+                .filter(sections::room_id.eq(room_id))
+                .filter(terms::end_date.ge(Utc::now().date_naive()))
+                .select(diesel::dsl::max(sections::capacity))
+                .first(conn)?;
+            if let Some(largest) = largest_booking.filter(|largest| *largest > room_req.capacity) {
+                return Err(RoomError::CapacityBelowBookings(largest));
+            }
+
+            let room = diesel::update(rooms::table.find(room_id))
+                .set((
+                    &NewRoom {
+                        building_id: room_req.building_id,
+                        room_number: room_req.room_number,
+                        capacity: room_req.capacity,
+                    },
+                    rooms::updated_at.eq(Utc::now()),
+                ))
+                .get_result::<Room>(conn)
+                .map_err(room_write_error)?;
+            replace_features(conn, room.id, &room_req.features)?;
+            Ok(RoomDetail::load(conn, vec![room])?.remove(0))
+        })
+    })
+    .await;
+
+    match result {
+        Ok(db_result) => match db_result {
+            Ok(room) => {
+                log::info!("Successfully updated room {}", room.room.id);
+                HttpResponse::Ok().json(json!({
+                    "status": "success",
+                    "data": room
+                }))
+            }
+            Err(err) => room_error_response(err, "Failed to update room"),
+        },
+        Err(blocking_err) => {
+            log::error!("Blocking error: {:?}", blocking_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Internal server error"
+            }))
+        }
+    }
+}
+
+pub async fn delete_room(pool: web::Data<DbPool>, path: web::Path<i32>) -> HttpResponse {
+    let room_id = path.into_inner();
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => {
+            log::error!("Failed to get database connection: {:?}", e);
+            return HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Database connection error"
+            }));
+        }
+    };
+
+    let result = web::block(move || {
+        let deleted = diesel::delete(rooms::table.find(room_id))
+            .execute(&mut conn)
+            .map_err(|e| match e {
+                DieselError::DatabaseError(DatabaseErrorKind::ForeignKeyViolation, _) => {
+                    RoomError::RoomBooked
+                }
+                other => other.into(),
+            })?;
+        match deleted {
+            0 => Err(RoomError::RoomNotFound),
+            _ => Ok(()),
+        }
+    })
+    .await;
+
+    match result {
+        Ok(db_result) => match db_result {
+            Ok(()) => {
+                log::info!("Successfully deleted room {}", room_id);
+                HttpResponse::Ok().json(json!({
+                    "status": "success",
+                    "message": "Room deleted successfully"
+                }))
+            }
+            Err(err) => room_error_response(err, "Failed to delete room"),
+        },
+        Err(blocking_err) => {
+            log::error!("Blocking error: {:?}", blocking_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Internal server error"
+            }))
+        }
+    }
+}
+
+/// How much each room is used in the term, by scheduled time and by seats.
+pub async fn get_room_utilization(
+    pool: web::Data<DbPool>,
+    path: web::Path<String>,
+    query: web::Query<UtilizationQuery>,
+) -> HttpResponse {
+    let term_key = path.into_inner();
+    let building_id = query.into_inner().building_id;
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => {
+            log::error!("Failed to get database connection: {:?}", e);
+            return HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Database connection error"
+            }));
+        }
+    };
+
+    let result = web::block(move || {
+        let term = Term::resolve(&mut conn, &term_key).map_err(|e| match e {
+            DieselError::NotFound => RoomError::TermNotFound,
+            other => RoomError::Database(other),
+        })?;
+        let report = room_utilization::report(&mut conn, &term, building_id)?;
+        Ok::<_, RoomError>((term, report))
+    })
+    .await;
+
+    match result {
+        Ok(db_result) => match db_result {
+            Ok((term, report)) => HttpResponse::Ok().json(json!({
+                "status": "success",
+                "data": {
+                    "term": term.code,
+                    "available_minutes_per_week": AVAILABLE_MINUTES_PER_WEEK,
+                    "rooms": report
+                }
+            })),
+            Err(err) => room_error_response(err, "Failed to build the utilization report"),
+        },
+        Err(blocking_err) => {
+            log::error!("Blocking error: {:?}", blocking_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Internal server error"
+            }))
+        }
+    }
+}
+
+fn room_error_response(err: RoomError, failure_message: &str) -> HttpResponse {
+    let error = |message: &str| json!({ "status": "error", "message": message });
+    match err {
+        RoomError::TermNotFound => HttpResponse::NotFound().json(error("Term not found")),
+        RoomError::BuildingNotFound => HttpResponse::NotFound().json(error("Building not found")),
+        RoomError::RoomNotFound => HttpResponse::NotFound().json(error("Room not found")),
+        RoomError::DuplicateBuilding => {
+            HttpResponse::Conflict().json(error("A building with that code already exists"))
+        }
+        RoomError::DuplicateRoom => HttpResponse::Conflict()
+            .json(error("This building already has a room with that number")),
+        RoomError::BuildingHasRooms => {
+            HttpResponse::Conflict().json(error("The building still has rooms"))
+        }
+        RoomError::RoomBooked => {
+            HttpResponse::Conflict().json(error("Sections are booked into this room"))
+        }
+        RoomError::CapacityBelowBookings(largest) => {
+            HttpResponse::Conflict().json(error(&format!(
+                "Capacity cannot be lower than the {} seats of a section booked into the room",
+                largest
+            )))
+        }
+        RoomError::Database(db_err) => {
+            log::error!("Database error on rooms: {:?}", db_err);
+            HttpResponse::InternalServerError().json(error(failure_message))
+        }
+    }
+}
@@ -0,0 +1,164 @@
+use std::collections::HashSet;
+
+use actix_web::{web, HttpResponse};
+use chrono::NaiveTime;
+use diesel::result::Error as DieselError;
+use serde::Deserialize;
+use serde_json::json;
+use validator::{Validate, ValidationError};
+
+use crate::models::Term;
+use crate::services::schedules::{self, ScheduleConstraints, ScheduleError};
+use crate::DbPool;
+
+#[derive(Debug, Deserialize, Validate)]
+#[validate(schema(function = "validate_schedule_request"))]
+pub struct ScheduleRequest {
+    /// Course codes, one section of each per schedule.
+    #[validate(length(min = 1, max = 10))]
+    pub courses: Vec<String>,
+    /// No classes may start before this time, such as `"10:00"`.
+    pub not_before: Option<NaiveTime>,
+    /// ISO weekdays (1 = Monday) to keep free.
+    #[serde(default)]
+    pub days_off: Vec<i16>,
+    #[serde(default)]
+    pub preferred_instructors: Vec<i32>,
+    /// Also use sections that have no seats left.
+    #[serde(default)]
+    pub include_full: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ScheduleQuery {
+    pub page: Option<usize>,
+    pub limit: Option<usize>,
+}
+
+fn validate_schedule_request(request: &ScheduleRequest) -> Result<(), ValidationError> {
+    let mut seen = HashSet::new();
+    if !request.courses.iter().all(|code| seen.insert(code)) {
+        let mut error = ValidationError::new("courses");
+        error.message = Some("each course may only be listed once".into());
+        return Err(error);
+    }
+    if request.days_off.iter().any(|day| !(1..=7).contains(day)) {
+        let mut error = ValidationError::new("days_off");
+        error.message = Some("days_off must be ISO weekdays from 1 to 7".into());
+        return Err(error);
+    }
+    Ok(())
+}
+
+enum ScheduleHandlerError {
+    TermNotFound,
+    Schedule(ScheduleError),
+}
+
+impl From<ScheduleError> for ScheduleHandlerError {
+    fn from(error: ScheduleError) -> Self {
+        ScheduleHandlerError::Schedule(error)
+    }
+}
+
+/// Lists every conflict-free combination of sections for the requested
+/// courses in the term, best first, one page at a time.
+pub async fn generate_schedules(
+    pool: web::Data<DbPool>,
+    path: web::Path<String>,
+    query: web::Query<ScheduleQuery>,
+    schedule_req: web::Json<ScheduleRequest>,
+) -> HttpResponse {
+    let term_key = path.into_inner();
+    let page = query.page.unwrap_or(1).max(1);
+    let limit = query.limit.unwrap_or(10).clamp(1, 50);
+    let schedule_req = schedule_req.into_inner();
+    if let Err(errors) = schedule_req.validate() {
+        log::error!("Validation errors: {:?}", errors);
+        return HttpResponse::BadRequest().json(json!({
+            "status": "error",
+            "message": "Validation failed",
+            "errors": errors
+        }));
+    }
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => {
+            log::error!("Failed to get database connection: {:?}", e);
+            return HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Database connection error"
+            }));
+        }
+    };
+
+    let result = web::block(move || {
+        let term = Term::resolve(&mut conn, &term_key).map_err(|e| match e {
+            DieselError::NotFound => ScheduleHandlerError::TermNotFound,
+            other => ScheduleError::from(other).into(),
+        })?;
+        let constraints = ScheduleConstraints {
+            not_before: schedule_req.not_before,
+            days_off: schedule_req.days_off,
+            preferred_instructors: schedule_req.preferred_instructors,
+            include_full: schedule_req.include_full,
+        };
+        Ok::<_, ScheduleHandlerError>(schedules::generate(
+            &mut conn,
+            &term,
+            &schedule_req.courses,
+            &constraints,
+        )?)
+    })
+    .await;
+
+    match result {
+        Ok(db_result) => match db_result {
+            Ok(generated) => {
+                let total = generated.schedules.len();
+                let schedules: Vec<_> = generated
+                    .schedules
+                    .into_iter()
+                    .skip((page - 1) * limit)
+                    .take(limit)
+                    .collect();
+                HttpResponse::Ok().json(json!({
+                    "status": "success",
+                    "data": {
+                        "courses": generated.courses,
+                        "schedules": schedules,
+                        "total": total,
+                        "truncated": generated.truncated,
+                        "page": page,
+                        "limit": limit
+                    }
+                }))
+            }
+            Err(ScheduleHandlerError::TermNotFound) => HttpResponse::NotFound().json(json!({
+                "status": "error",
+                "message": "Term not found"
+            })),
+            Err(ScheduleHandlerError::Schedule(ScheduleError::CourseNotFound(code))) => {
+                HttpResponse::NotFound().json(json!({
+                    "status": "error",
+                    "message": ScheduleError::CourseNotFound(code).to_string()
+                }))
+            }
+            Err(ScheduleHandlerError::Schedule(ScheduleError::Database(db_err))) => {
+                log::error!("Database error generating schedules: {:?}", db_err);
+                HttpResponse::InternalServerError().json(json!({
+                    "status": "error",
+                    "message": "Failed to generate schedules"
+                }))
+            }
+        },
+        Err(blocking_err) => {
+            log::error!("Blocking error: {:?}", blocking_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Internal server error"
+            }))
+        }
+    }
+}
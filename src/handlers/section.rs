@@ -0,0 +1,587 @@
+use actix_web::{web, HttpResponse};
+use chrono::{NaiveTime, Utc};
+use diesel::prelude::*;
+use diesel::result::{DatabaseErrorKind, Error as DieselError};
+use serde::Deserialize;
+use serde_json::json;
+use validator::{Validate, ValidationError};
+
+use crate::models::instructor::Instructor;
+use crate::models::room::Room;
+use crate::models::section::SectionMeeting;
+use crate::models::{NewSection, NewSectionMeeting, Section, SectionDetail, Term};
+use crate::schema::{rooms, section_meetings, sections};
+use crate::DbPool;
+
+#[derive(Debug, Deserialize)]
+pub struct SectionQuery {
+    pub course_id: Option<i32>,
+    pub instructor_id: Option<i32>,
+    pub page: Option<i64>,
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct SectionRequest {
+    pub course_id: i32,
+    #[validate(length(min = 1, max = 10))]
+    pub section_number: String,
+    #[validate(range(min = 0, max = 1000))]
+    pub capacity: i32,
+    pub instructor_id: Option<i32>,
+    /// Free-text location for sections without a booked room.
+    #[validate(length(max = 50))]
+    pub room: Option<String>,
+    /// The booked room, checked for capacity and double-bookings.
+    pub room_id: Option<i32>,
+    /// Holds the seats for a lottery instead of open enrollment.
+    #[serde(default)]
+    pub lottery: bool,
+    #[validate]
+    #[serde(default)]
+    pub meetings: Vec<MeetingRequest>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+#[validate(schema(function = "validate_meeting_times"))]
+pub struct MeetingRequest {
+    /// ISO 8601 weekday, 1 = Monday through 7 = Sunday.
+    #[validate(range(min = 1, max = 7))]
+    pub day_of_week: i16,
+    pub start_time: NaiveTime,
+    pub end_time: NaiveTime,
+}
+
+fn validate_meeting_times(meeting: &MeetingRequest) -> Result<(), ValidationError> {
+    if meeting.start_time >= meeting.end_time {
+        let mut error = ValidationError::new("meeting_times");
+        error.message = Some("start_time must be before end_time".into());
+        return Err(error);
+    }
+    Ok(())
+}
+
+enum SectionError {
+    TermNotFound,
+    SectionNotFound,
+    CapacityBelowEnrollment(i32),
+    NotAnInstructor(i32),
+    TeachingLoadExceeded { user_id: i32, max_sections: i32 },
+    RoomTooSmall { room_capacity: i32 },
+    RoomDoubleBooked(Box<SectionDetail>),
+    Database(DieselError),
+}
+
+impl From<DieselError> for SectionError {
+    fn from(error: DieselError) -> Self {
+        SectionError::Database(error)
+    }
+}
+
+fn resolve_term(conn: &mut PgConnection, term_key: &str) -> Result<Term, SectionError> {
+    Term::resolve(conn, term_key).map_err(|e| match e {
+        DieselError::NotFound => SectionError::TermNotFound,
+        other => SectionError::Database(other),
+    })
+}
+
+fn find_section(
+    conn: &mut PgConnection,
+    term: &Term,
+    section_id: i32,
+) -> Result<Section, SectionError> {
+    sections::table
+        .filter(sections::id.eq(section_id))
+        .filter(sections::term_id.eq(term.id))
+        .first::<Section>(conn)
+        .optional()?
+        .ok_or(SectionError::SectionNotFound)
+}
+
+/// Rejects assigning the user to another section in the term unless they
+/// have an instructor profile with room under its teaching load.
+fn ensure_can_teach(
+    conn: &mut PgConnection,
+    term: &Term,
+    user_id: i32,
+    section_id: Option<i32>,
+) -> Result<(), SectionError> {
+    let instructor =
+        Instructor::for_user(conn, user_id)?.ok_or(SectionError::NotAnInstructor(user_id))?;
+    if instructor.sections_in_term(conn, term.id, section_id)? >= instructor.max_sections as i64 {
+        return Err(SectionError::TeachingLoadExceeded {
+            user_id,
+            max_sections: instructor.max_sections,
+        });
+    }
+    Ok(())
+}
+
+/// Rejects booking the section's room if the room has fewer seats than the
+/// section or another section in the term meets there at an overlapping
+/// time. Runs after the section's meetings are written; the room row is
+/// locked so concurrent bookings of the same room are checked one at a time.
+fn ensure_room_available(
+    conn: &mut PgConnection,
+    term: &Term,
+    section: &Section,
+) -> Result<(), SectionError> {
+    let Some(room_id) = section.room_id else {
+        return Ok(());
+    };
+    let room = rooms::table
+        .find(room_id)
+        .for_update()
+        .first::<Room>(conn)?;
+    if section.capacity > room.capacity {
+        return Err(SectionError::RoomTooSmall {
+            room_capacity: room.capacity,
+        });
+    }
+
+    let others = sections::table
+        .filter(sections::term_id.eq(term.id))
+        .filter(sections::room_id.eq(room_id))
+        .filter(sections::id.ne(section.id))
+        .order(sections::id.asc())
+        .load::<Section>(conn)?;
+    if others.is_empty() {
+        return Ok(());
+    }
+
+    let meetings = section_meetings::table
+        .filter(section_meetings::section_id.eq(section.id))
+        .load::<SectionMeeting>(conn)?;
+    let clash = SectionDetail::load(conn, others)?
+        .into_iter()
+        .find(|other| {
+            other
+                .meetings
+                .iter()
+                .any(|booked| meetings.iter().any(|meeting| meeting.overlaps(booked)))
+        });
+    match clash {
+        Some(other) => Err(SectionError::RoomDoubleBooked(Box::new(other))),
+        None => Ok(()),
+    }
+}
+
+fn insert_meetings(
+    conn: &mut PgConnection,
+    section_id: i32,
+    meetings: &[MeetingRequest],
+) -> QueryResult<()> {
+    let new_meetings: Vec<NewSectionMeeting> = meetings
+        .iter()
+        .map(|meeting| NewSectionMeeting {
+            section_id,
+            day_of_week: meeting.day_of_week,
+            start_time: meeting.start_time,
+            end_time: meeting.end_time,
+        })
+        .collect();
+
+    diesel::insert_into(section_meetings::table)
+        .values(&new_meetings)
+        .execute(conn)?;
+    Ok(())
+}
+
+pub async fn get_sections(
+    pool: web::Data<DbPool>,
+    path: web::Path<String>,
+    query: web::Query<SectionQuery>,
+) -> HttpResponse {
+    let term_key = path.into_inner();
+    let query = query.into_inner();
+    let page = query.page.unwrap_or(1);
+    let limit = query.limit.unwrap_or(50);
+    let offset = (page - 1) * limit;
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => {
+            log::error!("Failed to get database connection: {:?}", e);
+            return HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Database connection error"
+            }));
+        }
+    };
+
+    let result = web::block(move || {
+        let term = resolve_term(&mut conn, &term_key)?;
+
+        let mut sections_query = sections::table
+            .filter(sections::term_id.eq(term.id))
+            .order((sections::course_id.asc(), sections::section_number.asc()))
+            .into_boxed();
+
+        if let Some(course_id) = query.course_id {
+            sections_query = sections_query.filter(sections::course_id.eq(course_id));
+        }
+        if let Some(instructor_id) = query.instructor_id {
+            sections_query = sections_query.filter(sections::instructor_id.eq(instructor_id));
+        }
+
+        let sections = sections_query
+            .limit(limit)
+            .offset(offset)
+            .load::<Section>(&mut conn)?;
+
+        Ok::<_, SectionError>(SectionDetail::load(&mut conn, sections)?)
+    })
+    .await;
+
+    match result {
+        Ok(db_result) => match db_result {
+            Ok(sections) => HttpResponse::Ok().json(json!({
+                "status": "success",
+                "data": sections
+            })),
+            Err(err) => section_error_response(err, "Failed to fetch sections"),
+        },
+        Err(blocking_err) => {
+            log::error!("Blocking error: {:?}", blocking_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Internal server error"
+            }))
+        }
+    }
+}
+
+pub async fn get_section(
+    pool: web::Data<DbPool>,
+    path: web::Path<(String, i32)>,
+) -> HttpResponse {
+    let (term_key, section_id) = path.into_inner();
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => {
+            log::error!("Failed to get database connection: {:?}", e);
+            return HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Database connection error"
+            }));
+        }
+    };
+
+    let result = web::block(move || {
+        let term = resolve_term(&mut conn, &term_key)?;
+        let section = find_section(&mut conn, &term, section_id)?;
+        Ok::<_, SectionError>(SectionDetail::load(&mut conn, vec![section])?.remove(0))
+    })
+    .await;
+
+    match result {
+        Ok(db_result) => match db_result {
+            Ok(section) => HttpResponse::Ok().json(json!({
+                "status": "success",
+                "data": section
+            })),
+            Err(err) => section_error_response(err, "Failed to fetch section"),
+        },
+        Err(blocking_err) => {
+            log::error!("Blocking error: {:?}", blocking_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Internal server error"
+            }))
+        }
+    }
+}
+
+pub async fn create_section(
+    pool: web::Data<DbPool>,
+    path: web::Path<String>,
+    section_req: web::Json<SectionRequest>,
+) -> HttpResponse {
+    let term_key = path.into_inner();
+    let section_req = section_req.into_inner();
+
+    if let Err(errors) = section_req.validate() {
+        log::error!("Validation errors: {:?}", errors);
+        return HttpResponse::BadRequest().json(json!({
+            "status": "error",
+            "message": "Validation failed",
+            "errors": errors
+        }));
+    }
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => {
+            log::error!("Failed to get database connection: {:?}", e);
+            return HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Database connection error"
+            }));
+        }
+    };
+
+    let result = web::block(move || {
+        conn.transaction::<_, SectionError, _>(|conn| {
+            let term = resolve_term(conn, &term_key)?;
+            if let Some(instructor_id) = section_req.instructor_id {
+                ensure_can_teach(conn, &term, instructor_id, None)?;
+            }
+
+            let section = diesel::insert_into(sections::table)
+                .values(&NewSection {
+                    term_id: term.id,
+                    course_id: section_req.course_id,
+                    section_number: section_req.section_number,
+                    capacity: section_req.capacity,
+                    instructor_id: section_req.instructor_id,
+                    room: section_req.room,
+                    lottery: section_req.lottery,
+                    room_id: section_req.room_id,
+                })
+                .get_result::<Section>(conn)?;
+
+            insert_meetings(conn, section.id, &section_req.meetings)?;
+            ensure_room_available(conn, &term, &section)?;
+
+            Ok(SectionDetail::load(conn, vec![section])?.remove(0))
+        })
+    })
+    .await;
+
+    match result {
+        Ok(db_result) => match db_result {
+            Ok(section) => {
+                log::info!("Successfully created section {}", section.section.id);
+                HttpResponse::Created().json(json!({
+                    "status": "success",
+                    "data": section
+                }))
+            }
+            Err(err) => section_error_response(err, "Failed to create section"),
+        },
+        Err(blocking_err) => {
+            log::error!("Blocking error: {:?}", blocking_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Internal server error"
+            }))
+        }
+    }
+}
+
+/// Replaces a section's details and its whole meeting pattern.
+pub async fn update_section(
+    pool: web::Data<DbPool>,
+    path: web::Path<(String, i32)>,
+    section_req: web::Json<SectionRequest>,
+) -> HttpResponse {
+    let (term_key, section_id) = path.into_inner();
+    let section_req = section_req.into_inner();
+
+    if let Err(errors) = section_req.validate() {
+        log::error!("Validation errors: {:?}", errors);
+        return HttpResponse::BadRequest().json(json!({
+            "status": "error",
+            "message": "Validation failed",
+            "errors": errors
+        }));
+    }
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => {
+            log::error!("Failed to get database connection: {:?}", e);
+            return HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Database connection error"
+            }));
+        }
+    };
+
+    let result = web::block(move || {
+        conn.transaction::<_, SectionError, _>(|conn| {
+            let term = resolve_term(conn, &term_key)?;
+            let existing = find_section(conn, &term, section_id)?;
+            if let Some(instructor_id) = section_req.instructor_id {
+                if existing.instructor_id != Some(instructor_id) {
+                    ensure_can_teach(conn, &term, instructor_id, Some(existing.id))?;
+                }
+            }
+
+            // Guarded like the enrollment seat counter so a concurrent
+            // enrollment cannot leave the section over capacity. Seats held
+            // for outstanding waitlist offers count as taken.
+            let seats_taken = sections::enrolled_count + sections::reserved_count;
+            let section = diesel::update(
+                sections::table
+                    .filter(sections::id.eq(existing.id))
+                    .filter(seats_taken.le(section_req.capacity)),
+            )
+            .set((
+                &NewSection {
+                    term_id: term.id,
+                    course_id: section_req.course_id,
+                    section_number: section_req.section_number,
+                    capacity: section_req.capacity,
+                    instructor_id: section_req.instructor_id,
+                    room: section_req.room,
+                    lottery: section_req.lottery,
+                    room_id: section_req.room_id,
+                },
+                sections::updated_at.eq(Utc::now()),
+            ))
+            .get_result::<Section>(conn)
+            .optional()?
+            .ok_or(SectionError::CapacityBelowEnrollment(
+                existing.enrolled_count + existing.reserved_count,
+            ))?;
+
+            diesel::delete(
+                section_meetings::table.filter(section_meetings::section_id.eq(section.id)),
+            )
+            .execute(conn)?;
+            insert_meetings(conn, section.id, &section_req.meetings)?;
+            ensure_room_available(conn, &term, &section)?;
+
+            Ok(SectionDetail::load(conn, vec![section])?.remove(0))
+        })
+    })
+    .await;
+
+    match result {
+        Ok(db_result) => match db_result {
+            Ok(section) => {
+                log::info!("Successfully updated section {}", section.section.id);
+                HttpResponse::Ok().json(json!({
+                    "status": "success",
+                    "data": section
+                }))
+            }
+            Err(err) => section_error_response(err, "Failed to update section"),
+        },
+        Err(blocking_err) => {
+            log::error!("Blocking error: {:?}", blocking_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Internal server error"
+            }))
+        }
+    }
+}
+
+pub async fn delete_section(
+    pool: web::Data<DbPool>,
+    path: web::Path<(String, i32)>,
+) -> HttpResponse {
+    let (term_key, section_id) = path.into_inner();
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => {
+            log::error!("Failed to get database connection: {:?}", e);
+            return HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Database connection error"
+            }));
+        }
+    };
+
+    let result = web::block(move || {
+        let term = resolve_term(&mut conn, &term_key)?;
+        let section = find_section(&mut conn, &term, section_id)?;
+        diesel::delete(sections::table.find(section.id)).execute(&mut conn)?;
+        Ok::<_, SectionError>(())
+    })
+    .await;
+
+    match result {
+        Ok(db_result) => match db_result {
+            Ok(()) => {
+                log::info!("Successfully deleted section {}", section_id);
+                HttpResponse::Ok().json(json!({
+                    "status": "success",
+                    "message": "Section deleted successfully"
+                }))
+            }
+            Err(err) => section_error_response(err, "Failed to delete section"),
+        },
+        Err(blocking_err) => {
+            log::error!("Blocking error: {:?}", blocking_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Internal server error"
+            }))
+        }
+    }
+}
+
+fn section_error_response(err: SectionError, failure_message: &str) -> HttpResponse {
+    match err {
+        SectionError::TermNotFound => HttpResponse::NotFound().json(json!({
+            "status": "error",
+            "message": "Term not found"
+        })),
+        SectionError::SectionNotFound => HttpResponse::NotFound().json(json!({
+            "status": "error",
+            "message": "Section not found"
+        })),
+        SectionError::CapacityBelowEnrollment(enrolled) => HttpResponse::Conflict().json(json!({
+            "status": "error",
+            "message": format!("Capacity cannot be lower than the {} seats already enrolled or offered", enrolled)
+        })),
+        SectionError::NotAnInstructor(user_id) => HttpResponse::UnprocessableEntity().json(json!({
+            "status": "error",
+            "message": format!("User {} has no instructor profile", user_id)
+        })),
+        SectionError::TeachingLoadExceeded {
+            user_id,
+            max_sections,
+        } => HttpResponse::UnprocessableEntity().json(json!({
+            "status": "error",
+            "message": format!(
+                "User {} already teaches the maximum of {} sections this term",
+                user_id, max_sections
+            )
+        })),
+        SectionError::RoomTooSmall { room_capacity } => {
+            HttpResponse::UnprocessableEntity().json(json!({
+                "status": "error",
+                "message": format!(
+                    "The room only holds {} students, fewer than the section capacity",
+                    room_capacity
+                )
+            }))
+        }
+        SectionError::RoomDoubleBooked(other) => HttpResponse::Conflict().json(json!({
+            "status": "error",
+            "message": format!(
+                "The room is already booked by {} section {} at an overlapping time",
+                other.course_code, other.section.section_number
+            ),
+            "conflicting_section": other
+        })),
+        SectionError::Database(DieselError::DatabaseError(
+            DatabaseErrorKind::UniqueViolation,
+            _,
+        )) => HttpResponse::Conflict().json(json!({
+            "status": "error",
+            "message": "This course already has a section with that number in the term"
+        })),
+        SectionError::Database(DieselError::DatabaseError(
+            DatabaseErrorKind::ForeignKeyViolation,
+            info,
+        )) => HttpResponse::BadRequest().json(json!({
+            "status": "error",
+            "message": "Section references a record that does not exist",
+            "constraint": info.constraint_name()
+        })),
+        SectionError::Database(db_err) => {
+            log::error!("Database error on sections: {:?}", db_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": failure_message
+            }))
+        }
+    }
+}
@@ -0,0 +1,262 @@
+use actix_web::{web, HttpResponse};
+use diesel::prelude::*;
+use diesel::result::Error as DieselError;
+use serde::Serialize;
+use serde_json::json;
+
+use crate::middleware::AuthenticatedUser;
+use crate::models::permission::Permission;
+use crate::models::user::UserSession;
+use crate::models::AuditContext;
+use crate::schema::{audit_logs, user_sessions, users};
+use crate::services::tokens::{self, TokenDenylist};
+use crate::DbPool;
+
+/// A session as listed to its user, flagging the one the request came from.
+#[derive(Debug, Serialize)]
+pub struct SessionResponse {
+    #[serde(flatten)]
+    pub session: UserSession,
+    pub current: bool,
+}
+
+enum SessionError {
+    SessionNotFound,
+    UserNotFound,
+    Database(DieselError),
+}
+
+impl From<DieselError> for SessionError {
+    fn from(error: DieselError) -> Self {
+        SessionError::Database(error)
+    }
+}
+
+/// What a revocation covers.
+enum Revocation {
+    /// One session, which must belong to the caller unless they may manage
+    /// every user's sessions.
+    Session {
+        session_id: i32,
+        caller: AuthenticatedUser,
+    },
+    /// Every active session of the user.
+    AllSessions { user_id: i32 },
+}
+
+/// The caller's active sessions, most recently used first.
+pub async fn get_sessions(user: AuthenticatedUser, pool: web::Data<DbPool>) -> HttpResponse {
+    list_sessions(pool, user.user_id, Some(user.session_id)).await
+}
+
+/// A user's active sessions.
+pub async fn get_user_sessions(
+    user: AuthenticatedUser,
+    pool: web::Data<DbPool>,
+    path: web::Path<i32>,
+) -> HttpResponse {
+    list_sessions(pool, path.into_inner(), Some(user.session_id)).await
+}
+
+/// Revokes one of the caller's sessions; users who manage sessions can
+/// revoke anyone's.
+pub async fn revoke_session(
+    user: AuthenticatedUser,
+    audit: AuditContext,
+    pool: web::Data<DbPool>,
+    denylist: web::Data<TokenDenylist>,
+    path: web::Path<i32>,
+) -> HttpResponse {
+    let revocation = Revocation::Session {
+        session_id: path.into_inner(),
+        caller: user,
+    };
+    revoke(pool, denylist, audit, revocation).await
+}
+
+/// Revokes every session of the caller, including the current one.
+pub async fn revoke_sessions(
+    user: AuthenticatedUser,
+    audit: AuditContext,
+    pool: web::Data<DbPool>,
+    denylist: web::Data<TokenDenylist>,
+) -> HttpResponse {
+    let revocation = Revocation::AllSessions {
+        user_id: user.user_id,
+    };
+    revoke(pool, denylist, audit, revocation).await
+}
+
+/// Revokes every session of a user.
+pub async fn revoke_user_sessions(
+    audit: AuditContext,
+    pool: web::Data<DbPool>,
+    denylist: web::Data<TokenDenylist>,
+    path: web::Path<i32>,
+) -> HttpResponse {
+    let revocation = Revocation::AllSessions {
+        user_id: path.into_inner(),
+    };
+    revoke(pool, denylist, audit, revocation).await
+}
+
+async fn list_sessions(
+    pool: web::Data<DbPool>,
+    user_id: i32,
+    current_session: Option<i32>,
+) -> HttpResponse {
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => {
+            log::error!("Failed to get database connection: {:?}", e);
+            return HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Database connection error"
+            }));
+        }
+    };
+
+    let result = web::block(move || {
+        let exists = diesel::select(diesel::dsl::exists(users::table.find(user_id)))
+            .get_result::<bool>(&mut conn)?;
+        if !exists {
+            return Err(SessionError::UserNotFound);
+        }
+
+        let sessions = tokens::active_sessions()
+            .filter(user_sessions::user_id.eq(user_id))
+            .load::<UserSession>(&mut conn)?;
+        Ok(sessions
+            .into_iter()
+            .map(|session| SessionResponse {
+                current: current_session == Some(session.id),
+                session,
+            })
+            .collect::<Vec<_>>())
+    })
+    .await;
+
+    match result {
+        Ok(db_result) => match db_result {
+            Ok(sessions) => HttpResponse::Ok().json(json!({
+                "status": "success",
+                "data": sessions
+            })),
+            Err(err) => session_error_response(err, "Failed to fetch sessions"),
+        },
+        Err(blocking_err) => {
+            log::error!("Blocking error: {:?}", blocking_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Internal server error"
+            }))
+        }
+    }
+}
+
+// Shared by the revocation endpoints: revokes the sessions, denies their
+// access tokens once committed and records the revocation.
+async fn revoke(
+    pool: web::Data<DbPool>,
+    denylist: web::Data<TokenDenylist>,
+    audit: AuditContext,
+    revocation: Revocation,
+) -> HttpResponse {
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => {
+            log::error!("Failed to get database connection: {:?}", e);
+            return HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Database connection error"
+            }));
+        }
+    };
+
+    let result = web::block(move || {
+        conn.transaction::<_, SessionError, _>(|conn| {
+            let (session_ids, activity) = match revocation {
+                Revocation::Session { session_id, caller } => {
+                    let mut query = tokens::active_sessions()
+                        .filter(user_sessions::id.eq(session_id))
+                        .select(user_sessions::id);
+                    if !caller.has_permission(conn, Permission::SESSIONS_MANAGE)? {
+                        query = query.filter(user_sessions::user_id.eq(caller.user_id));
+                    }
+                    let session_ids = query.load::<i32>(conn)?;
+                    if session_ids.is_empty() {
+                        return Err(SessionError::SessionNotFound);
+                    }
+                    let activity =
+                        audit.activity("session.revoke", "user_session", Some(session_id), None);
+                    (session_ids, activity)
+                }
+                Revocation::AllSessions { user_id } => {
+                    let exists = diesel::select(diesel::dsl::exists(users::table.find(user_id)))
+                        .get_result::<bool>(conn)?;
+                    if !exists {
+                        return Err(SessionError::UserNotFound);
+                    }
+                    let session_ids = tokens::active_sessions()
+                        .filter(user_sessions::user_id.eq(user_id))
+                        .select(user_sessions::id)
+                        .load::<i32>(conn)?;
+                    let activity = audit.activity(
+                        "session.revoke_all",
+                        "user",
+                        Some(user_id),
+                        Some(json!({ "sessions": session_ids })),
+                    );
+                    (session_ids, activity)
+                }
+            };
+
+            let (revoked, denied) = tokens::revoke_sessions(conn, &session_ids)?;
+            diesel::insert_into(audit_logs::table)
+                .values(&activity)
+                .execute(conn)?;
+            Ok((revoked, denied))
+        })
+    })
+    .await;
+
+    match result {
+        Ok(db_result) => match db_result {
+            Ok((revoked, denied)) => {
+                denylist.extend(denied);
+                HttpResponse::Ok().json(json!({
+                    "status": "success",
+                    "data": { "revoked": revoked }
+                }))
+            }
+            Err(err) => session_error_response(err, "Failed to revoke sessions"),
+        },
+        Err(blocking_err) => {
+            log::error!("Blocking error: {:?}", blocking_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Internal server error"
+            }))
+        }
+    }
+}
+
+fn session_error_response(err: SessionError, failure_message: &str) -> HttpResponse {
+    match err {
+        SessionError::SessionNotFound => HttpResponse::NotFound().json(json!({
+            "status": "error",
+            "message": "Session not found"
+        })),
+        SessionError::UserNotFound => HttpResponse::NotFound().json(json!({
+            "status": "error",
+            "message": "User not found"
+        })),
+        SessionError::Database(db_err) => {
+            log::error!("Database error on sessions: {:?}", db_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": failure_message
+            }))
+        }
+    }
+}
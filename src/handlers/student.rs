@@ -1,11 +1,17 @@
 use actix_web::{web, HttpResponse};
+use chrono::Utc;
 use diesel::prelude::*;
+use diesel::result::{DatabaseErrorKind, Error as DieselError};
 use serde::Deserialize;
 use serde_json::json;
 use validator::Validate;
 
-use crate::models::{NewStudent, Student};
+use crate::middleware::AuthenticatedUser;
+use crate::models::student::StudentScope;
+use crate::models::{NewStudent, Student, StudentDetail, UpdateStudent};
 use crate::schema;
+use crate::schema::students;
+use crate::services::grades;
 use crate::DbPool;
 
 #[derive(Debug, Deserialize)]
@@ -16,6 +22,7 @@ pub struct StudentQuery {
 
 pub async fn get_students(
     pool: web::Data<DbPool>,
+    scope: StudentScope,
     query: web::Query<StudentQuery>,
 ) -> HttpResponse {
     let page = query.page.unwrap_or(1);
@@ -34,7 +41,8 @@ pub async fn get_students(
     };
 
     let result = web::block(move || {
-        schema::students::dsl::students
+        scope
+            .students()
             .limit(limit)
             .offset(offset)
             .load::<Student>(&mut *conn)
@@ -69,10 +77,12 @@ pub async fn get_students(
 }
 
 pub async fn create_student(
+    user: AuthenticatedUser,
     pool: web::Data<DbPool>,
     new_student: web::Json<NewStudent>,
 ) -> HttpResponse {
     let new_student = new_student.into_inner();
+    let user_id = Some(user.user_id);
 
     if let Err(errors) = new_student.validate() {
         log::error!("Validation errors: {:?}", errors);
@@ -96,7 +106,11 @@ pub async fn create_student(
 
     let result = web::block(move || {
         diesel::insert_into(schema::students::dsl::students)
-            .values(&new_student)
+            .values((
+                &new_student,
+                students::created_by.eq(user_id),
+                students::updated_by.eq(user_id),
+            ))
             .get_result::<Student>(&mut *conn)
     })
     .await;
@@ -110,6 +124,18 @@ pub async fn create_student(
                     "data": student
                 }))
             }
+            Err(DieselError::DatabaseError(DatabaseErrorKind::UniqueViolation, _)) => {
+                HttpResponse::Conflict().json(json!({
+                    "status": "error",
+                    "message": "A student with this email, phone or user account already exists"
+                }))
+            }
+            Err(DieselError::DatabaseError(DatabaseErrorKind::ForeignKeyViolation, _)) => {
+                HttpResponse::BadRequest().json(json!({
+                    "status": "error",
+                    "message": "Course or user account does not exist"
+                }))
+            }
             Err(db_err) => {
                 log::error!("Database error creating student: {:?}", db_err);
                 HttpResponse::InternalServerError().json(json!({
@@ -127,3 +153,250 @@ pub async fn create_student(
         }
     }
 }
+
+pub async fn get_student(
+    pool: web::Data<DbPool>,
+    scope: StudentScope,
+    path: web::Path<i32>,
+) -> HttpResponse {
+    let student_id = path.into_inner();
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => {
+            log::error!("Failed to get database connection: {:?}", e);
+            return HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Database connection error"
+            }));
+        }
+    };
+
+    let result = web::block(move || {
+        let student = scope
+            .students()
+            .filter(students::id.eq(student_id))
+            .first::<Student>(&mut *conn)?;
+        let standing = grades::student_gpa(&mut conn, student_id)?;
+
+        Ok::<_, DieselError>(StudentDetail {
+            student,
+            gpa: standing.cumulative.gpa,
+            credits_earned: standing.cumulative.credits_earned,
+        })
+    })
+    .await;
+
+    match result {
+        Ok(db_result) => match db_result {
+            Ok(student) => HttpResponse::Ok().json(json!({
+                "status": "success",
+                "data": student
+            })),
+            Err(DieselError::NotFound) => student_not_found(student_id),
+            Err(db_err) => {
+                log::error!("Database error fetching student {}: {:?}", student_id, db_err);
+                HttpResponse::InternalServerError().json(json!({
+                    "status": "error",
+                    "message": "Failed to fetch student"
+                }))
+            }
+        },
+        Err(blocking_err) => {
+            log::error!("Blocking error: {:?}", blocking_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Internal server error"
+            }))
+        }
+    }
+}
+
+pub async fn update_student(
+    user: AuthenticatedUser,
+    pool: web::Data<DbPool>,
+    scope: StudentScope,
+    path: web::Path<i32>,
+    student: web::Json<NewStudent>,
+) -> HttpResponse {
+    let student = student.into_inner();
+
+    if let Err(errors) = student.validate() {
+        log::error!("Validation errors: {:?}", errors);
+        return HttpResponse::BadRequest().json(json!({
+            "status": "error",
+            "message": "Validation failed",
+            "errors": errors
+        }));
+    }
+
+    save_student_changes(user, pool, scope, path.into_inner(), student.into()).await
+}
+
+pub async fn patch_student(
+    user: AuthenticatedUser,
+    pool: web::Data<DbPool>,
+    scope: StudentScope,
+    path: web::Path<i32>,
+    changes: web::Json<UpdateStudent>,
+) -> HttpResponse {
+    let changes = changes.into_inner();
+
+    if let Err(errors) = changes.validate() {
+        log::error!("Validation errors: {:?}", errors);
+        return HttpResponse::BadRequest().json(json!({
+            "status": "error",
+            "message": "Validation failed",
+            "errors": errors
+        }));
+    }
+
+    save_student_changes(user, pool, scope, path.into_inner(), changes).await
+}
+
+pub async fn delete_student(
+    pool: web::Data<DbPool>,
+    scope: StudentScope,
+    path: web::Path<i32>,
+) -> HttpResponse {
+    let student_id = path.into_inner();
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => {
+            log::error!("Failed to get database connection: {:?}", e);
+            return HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Database connection error"
+            }));
+        }
+    };
+
+    let result = web::block(move || {
+        diesel::delete(
+            students::table
+                .find(student_id)
+                .filter(students::id.eq_any(scope.student_ids())),
+        )
+        .execute(&mut *conn)
+    })
+    .await;
+
+    match result {
+        Ok(db_result) => match db_result {
+            Ok(0) => student_not_found(student_id),
+            Ok(_) => {
+                log::info!("Successfully deleted student {}", student_id);
+                HttpResponse::Ok().json(json!({
+                    "status": "success",
+                    "message": "Student deleted successfully"
+                }))
+            }
+            Err(DieselError::DatabaseError(DatabaseErrorKind::ForeignKeyViolation, _)) => {
+                HttpResponse::Conflict().json(json!({
+                    "status": "error",
+                    "message": "Student is still referenced by other records"
+                }))
+            }
+            Err(db_err) => {
+                log::error!("Database error deleting student {}: {:?}", student_id, db_err);
+                HttpResponse::InternalServerError().json(json!({
+                    "status": "error",
+                    "message": "Failed to delete student"
+                }))
+            }
+        },
+        Err(blocking_err) => {
+            log::error!("Blocking error: {:?}", blocking_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Internal server error"
+            }))
+        }
+    }
+}
+
+// Shared by PUT and PATCH: applies an already validated changeset and stamps
+// the audit columns.
+async fn save_student_changes(
+    user: AuthenticatedUser,
+    pool: web::Data<DbPool>,
+    scope: StudentScope,
+    student_id: i32,
+    changes: UpdateStudent,
+) -> HttpResponse {
+    let user_id = Some(user.user_id);
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => {
+            log::error!("Failed to get database connection: {:?}", e);
+            return HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Database connection error"
+            }));
+        }
+    };
+
+    let result = web::block(move || {
+        diesel::update(
+            students::table
+                .find(student_id)
+                .filter(students::id.eq_any(scope.student_ids())),
+        )
+        .set((
+            &changes,
+            students::updated_by.eq(user_id),
+            students::updated_at.eq(Utc::now()),
+        ))
+        .get_result::<Student>(&mut *conn)
+    })
+    .await;
+
+    match result {
+        Ok(db_result) => match db_result {
+            Ok(student) => {
+                log::info!("Successfully updated student: {:?}", student);
+                HttpResponse::Ok().json(json!({
+                    "status": "success",
+                    "data": student
+                }))
+            }
+            Err(DieselError::NotFound) => student_not_found(student_id),
+            Err(DieselError::DatabaseError(DatabaseErrorKind::UniqueViolation, _)) => {
+                HttpResponse::Conflict().json(json!({
+                    "status": "error",
+                    "message": "A student with this email, phone or user account already exists"
+                }))
+            }
+            Err(DieselError::DatabaseError(DatabaseErrorKind::ForeignKeyViolation, _)) => {
+                HttpResponse::BadRequest().json(json!({
+                    "status": "error",
+                    "message": "Course or user account does not exist"
+                }))
+            }
+            Err(db_err) => {
+                log::error!("Database error updating student {}: {:?}", student_id, db_err);
+                HttpResponse::InternalServerError().json(json!({
+                    "status": "error",
+                    "message": "Failed to update student"
+                }))
+            }
+        },
+        Err(blocking_err) => {
+            log::error!("Blocking error: {:?}", blocking_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Internal server error"
+            }))
+        }
+    }
+}
+
+fn student_not_found(student_id: i32) -> HttpResponse {
+    log::warn!("Student {} not found", student_id);
+    HttpResponse::NotFound().json(json!({
+        "status": "error",
+        "message": "Student not found"
+    }))
+}
@@ -0,0 +1,335 @@
+use actix_web::{web, HttpResponse};
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel::result::{DatabaseErrorKind, Error as DieselError};
+use serde_json::json;
+use validator::Validate;
+
+use crate::models::{NewTerm, Term};
+use crate::schema::terms;
+use crate::DbPool;
+
+enum TermError {
+    Overlap(Vec<String>),
+    Database(DieselError),
+}
+
+impl From<DieselError> for TermError {
+    fn from(error: DieselError) -> Self {
+        TermError::Database(error)
+    }
+}
+
+pub async fn get_terms(pool: web::Data<DbPool>) -> HttpResponse {
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => {
+            log::error!("Failed to get database connection: {:?}", e);
+            return HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Database connection error"
+            }));
+        }
+    };
+
+    let result = web::block(move || {
+        terms::table
+            .order(terms::start_date.desc())
+            .load::<Term>(&mut *conn)
+    })
+    .await;
+
+    match result {
+        Ok(db_result) => match db_result {
+            Ok(terms) => HttpResponse::Ok().json(json!({
+                "status": "success",
+                "data": terms
+            })),
+            Err(db_err) => {
+                log::error!("Database error fetching terms: {:?}", db_err);
+                HttpResponse::InternalServerError().json(json!({
+                    "status": "error",
+                    "message": "Failed to fetch terms"
+                }))
+            }
+        },
+        Err(blocking_err) => {
+            log::error!("Blocking error: {:?}", blocking_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Internal server error"
+            }))
+        }
+    }
+}
+
+/// `GET /terms/{term}`; `{term}` is a term code or `current`.
+pub async fn get_term(pool: web::Data<DbPool>, path: web::Path<String>) -> HttpResponse {
+    let term_key = path.into_inner();
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => {
+            log::error!("Failed to get database connection: {:?}", e);
+            return HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Database connection error"
+            }));
+        }
+    };
+
+    let key = term_key.clone();
+    let result = web::block(move || Term::resolve(&mut conn, &key)).await;
+
+    match result {
+        Ok(db_result) => match db_result {
+            Ok(term) => HttpResponse::Ok().json(json!({
+                "status": "success",
+                "data": term
+            })),
+            Err(DieselError::NotFound) => term_not_found(&term_key),
+            Err(db_err) => {
+                log::error!("Database error fetching term {}: {:?}", term_key, db_err);
+                HttpResponse::InternalServerError().json(json!({
+                    "status": "error",
+                    "message": "Failed to fetch term"
+                }))
+            }
+        },
+        Err(blocking_err) => {
+            log::error!("Blocking error: {:?}", blocking_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Internal server error"
+            }))
+        }
+    }
+}
+
+pub async fn create_term(pool: web::Data<DbPool>, new_term: web::Json<NewTerm>) -> HttpResponse {
+    let mut new_term = new_term.into_inner();
+    new_term.code = new_term.code.trim().to_uppercase();
+
+    if let Err(errors) = new_term.validate() {
+        log::error!("Validation errors: {:?}", errors);
+        return HttpResponse::BadRequest().json(json!({
+            "status": "error",
+            "message": "Validation failed",
+            "errors": errors
+        }));
+    }
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => {
+            log::error!("Failed to get database connection: {:?}", e);
+            return HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Database connection error"
+            }));
+        }
+    };
+
+    let result = web::block(move || {
+        conn.transaction::<_, TermError, _>(|conn| {
+            let overlapping =
+                Term::overlapping(conn, new_term.start_date, new_term.end_date, None)?;
+            if !overlapping.is_empty() {
+                return Err(TermError::Overlap(
+                    overlapping.into_iter().map(|term| term.code).collect(),
+                ));
+            }
+
+            Ok(diesel::insert_into(terms::table)
+                .values(&new_term)
+                .get_result::<Term>(conn)?)
+        })
+    })
+    .await;
+
+    match result {
+        Ok(db_result) => match db_result {
+            Ok(term) => {
+                log::info!("Successfully created term: {}", term.code);
+                HttpResponse::Created().json(json!({
+                    "status": "success",
+                    "data": term
+                }))
+            }
+            Err(err) => term_error_response(err, "Failed to create term"),
+        },
+        Err(blocking_err) => {
+            log::error!("Blocking error: {:?}", blocking_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Internal server error"
+            }))
+        }
+    }
+}
+
+pub async fn update_term(
+    pool: web::Data<DbPool>,
+    path: web::Path<String>,
+    term: web::Json<NewTerm>,
+) -> HttpResponse {
+    let term_key = path.into_inner();
+    let mut term = term.into_inner();
+    term.code = term.code.trim().to_uppercase();
+
+    if let Err(errors) = term.validate() {
+        log::error!("Validation errors: {:?}", errors);
+        return HttpResponse::BadRequest().json(json!({
+            "status": "error",
+            "message": "Validation failed",
+            "errors": errors
+        }));
+    }
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => {
+            log::error!("Failed to get database connection: {:?}", e);
+            return HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Database connection error"
+            }));
+        }
+    };
+
+    let key = term_key.clone();
+    let result = web::block(move || {
+        conn.transaction::<_, TermError, _>(|conn| {
+            let existing = Term::resolve(conn, &key)?;
+
+            let overlapping =
+                Term::overlapping(conn, term.start_date, term.end_date, Some(existing.id))?;
+            if !overlapping.is_empty() {
+                return Err(TermError::Overlap(
+                    overlapping.into_iter().map(|term| term.code).collect(),
+                ));
+            }
+
+            Ok(diesel::update(terms::table.find(existing.id))
+                .set((&term, terms::updated_at.eq(Utc::now())))
+                .get_result::<Term>(conn)?)
+        })
+    })
+    .await;
+
+    match result {
+        Ok(db_result) => match db_result {
+            Ok(term) => {
+                log::info!("Successfully updated term: {}", term.code);
+                HttpResponse::Ok().json(json!({
+                    "status": "success",
+                    "data": term
+                }))
+            }
+            Err(TermError::Database(DieselError::NotFound)) => term_not_found(&term_key),
+            Err(err) => term_error_response(err, "Failed to update term"),
+        },
+        Err(blocking_err) => {
+            log::error!("Blocking error: {:?}", blocking_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Internal server error"
+            }))
+        }
+    }
+}
+
+pub async fn delete_term(pool: web::Data<DbPool>, path: web::Path<String>) -> HttpResponse {
+    let term_key = path.into_inner();
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => {
+            log::error!("Failed to get database connection: {:?}", e);
+            return HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Database connection error"
+            }));
+        }
+    };
+
+    let code = term_key.trim().to_uppercase();
+    let result = web::block(move || {
+        diesel::delete(terms::table.filter(terms::code.eq(code))).execute(&mut *conn)
+    })
+    .await;
+
+    match result {
+        Ok(db_result) => match db_result {
+            Ok(0) => term_not_found(&term_key),
+            Ok(_) => {
+                log::info!("Successfully deleted term {}", term_key);
+                HttpResponse::Ok().json(json!({
+                    "status": "success",
+                    "message": "Term deleted successfully"
+                }))
+            }
+            Err(DieselError::DatabaseError(DatabaseErrorKind::ForeignKeyViolation, _)) => {
+                HttpResponse::Conflict().json(json!({
+                    "status": "error",
+                    "message": "Term is still referenced by other records"
+                }))
+            }
+            Err(db_err) => {
+                log::error!("Database error deleting term {}: {:?}", term_key, db_err);
+                HttpResponse::InternalServerError().json(json!({
+                    "status": "error",
+                    "message": "Failed to delete term"
+                }))
+            }
+        },
+        Err(blocking_err) => {
+            log::error!("Blocking error: {:?}", blocking_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Internal server error"
+            }))
+        }
+    }
+}
+
+fn term_error_response(err: TermError, failure_message: &str) -> HttpResponse {
+    match err {
+        TermError::Overlap(codes) => HttpResponse::Conflict().json(json!({
+            "status": "error",
+            "message": "Term dates overlap an existing term",
+            "overlapping_terms": codes
+        })),
+        TermError::Database(DieselError::DatabaseError(DatabaseErrorKind::UniqueViolation, _)) => {
+            HttpResponse::Conflict().json(json!({
+                "status": "error",
+                "message": "A term with this code already exists"
+            }))
+        }
+        // Concurrent writers can slip past the overlap query; the exclusion
+        // constraint still rejects them.
+        TermError::Database(DieselError::DatabaseError(_, info))
+            if info.constraint_name() == Some("terms_no_overlap") =>
+        {
+            HttpResponse::Conflict().json(json!({
+                "status": "error",
+                "message": "Term dates overlap an existing term"
+            }))
+        }
+        TermError::Database(db_err) => {
+            log::error!("Database error saving term: {:?}", db_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": failure_message
+            }))
+        }
+    }
+}
+
+fn term_not_found(term_key: &str) -> HttpResponse {
+    log::warn!("Term {} not found", term_key);
+    HttpResponse::NotFound().json(json!({
+        "status": "error",
+        "message": "Term not found"
+    }))
+}
@@ -0,0 +1,141 @@
+use actix_web::http::header;
+use actix_web::{web, HttpRequest, HttpResponse};
+use diesel::prelude::*;
+use diesel::result::Error as DieselError;
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::middleware::AuthenticatedUser;
+use crate::models::AuditContext;
+use crate::services::transcript::{self, Transcript, TranscriptError};
+use crate::DbPool;
+
+#[derive(Debug, Deserialize)]
+pub struct TranscriptQuery {
+    pub format: Option<String>,
+}
+
+enum TranscriptFormat {
+    Json,
+    Pdf,
+}
+
+impl TranscriptFormat {
+    /// `?format=` wins; otherwise a PDF is returned only when the client
+    /// accepts `application/pdf` and not JSON.
+    fn from_request(req: &HttpRequest, format: Option<&str>) -> Option<TranscriptFormat> {
+        match format.map(|format| format.to_ascii_lowercase()).as_deref() {
+            Some("json") => Some(TranscriptFormat::Json),
+            Some("pdf") => Some(TranscriptFormat::Pdf),
+            Some(_) => None,
+            None => {
+                let accept = req
+                    .headers()
+                    .get(header::ACCEPT)
+                    .and_then(|value| value.to_str().ok())
+                    .unwrap_or_default();
+                if accept.contains("application/pdf") && !accept.contains("application/json") {
+                    Some(TranscriptFormat::Pdf)
+                } else {
+                    Some(TranscriptFormat::Json)
+                }
+            }
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            TranscriptFormat::Json => "json",
+            TranscriptFormat::Pdf => "pdf",
+        }
+    }
+}
+
+enum Issued {
+    Json(Box<Transcript>),
+    Pdf(Vec<u8>),
+}
+
+pub async fn get_transcript(
+    req: HttpRequest,
+    _user: AuthenticatedUser,
+    audit: AuditContext,
+    pool: web::Data<DbPool>,
+    path: web::Path<i32>,
+    query: web::Query<TranscriptQuery>,
+) -> HttpResponse {
+    let student_id = path.into_inner();
+
+    let format = match TranscriptFormat::from_request(&req, query.format.as_deref()) {
+        Some(format) => format,
+        None => {
+            return HttpResponse::BadRequest().json(json!({
+                "status": "error",
+                "message": "Transcript format must be json or pdf"
+            }))
+        }
+    };
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => {
+            log::error!("Failed to get database connection: {:?}", e);
+            return HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Database connection error"
+            }));
+        }
+    };
+
+    let result = web::block(move || {
+        conn.transaction(|conn| transcript::issue(conn, student_id, format.name(), &audit))
+            .map(|issued| match format {
+                TranscriptFormat::Json => Issued::Json(Box::new(issued)),
+                TranscriptFormat::Pdf => Issued::Pdf(transcript::render_pdf(&issued)),
+            })
+    })
+    .await;
+
+    match result {
+        Ok(Ok(Issued::Json(issued))) => HttpResponse::Ok().json(json!({
+            "status": "success",
+            "data": issued
+        })),
+        Ok(Ok(Issued::Pdf(bytes))) => HttpResponse::Ok()
+            .content_type("application/pdf")
+            .insert_header((
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"transcript-{}.pdf\"", student_id),
+            ))
+            .body(bytes),
+        Ok(Err(TranscriptError::Database(DieselError::NotFound))) => {
+            HttpResponse::NotFound().json(json!({
+                "status": "error",
+                "message": "Student not found"
+            }))
+        }
+        Ok(Err(TranscriptError::OnHold(holds))) => HttpResponse::Forbidden().json(json!({
+            "status": "error",
+            "message": "Student has active holds that block transcripts",
+            "holds": holds
+        })),
+        Ok(Err(TranscriptError::Database(db_err))) => {
+            log::error!(
+                "Database error issuing transcript for student {}: {:?}",
+                student_id,
+                db_err
+            );
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Failed to issue transcript"
+            }))
+        }
+        Err(blocking_err) => {
+            log::error!("Blocking error: {:?}", blocking_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Internal server error"
+            }))
+        }
+    }
+}
@@ -0,0 +1,159 @@
+use actix_web::{web, HttpResponse};
+use diesel::prelude::*;
+use serde_json::json;
+
+use crate::handlers::enrollment::registration_error_response;
+use crate::models::{AuditContext, WaitlistEntry, WaitlistPosition};
+use crate::schema::waitlist_entries;
+use crate::services::registration::{self, RegistrationError};
+use crate::services::{credit_load, waitlist};
+use crate::DbPool;
+
+/// The student's open waitlist entries with their place in line.
+pub async fn get_waitlist(pool: web::Data<DbPool>, path: web::Path<i32>) -> HttpResponse {
+    let student_id = path.into_inner();
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => {
+            log::error!("Failed to get database connection: {:?}", e);
+            return HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Database connection error"
+            }));
+        }
+    };
+
+    let result = web::block(move || {
+        registration::find_student(&mut conn, student_id)?;
+
+        let entries = waitlist_entries::table
+            .filter(waitlist_entries::student_id.eq(student_id))
+            .filter(
+                waitlist_entries::status.eq_any([WaitlistEntry::WAITING, WaitlistEntry::OFFERED]),
+            )
+            .order(waitlist_entries::id.asc())
+            .load::<WaitlistEntry>(&mut conn)?;
+
+        entries
+            .into_iter()
+            .map(|entry| {
+                let position = waitlist::position(&mut conn, &entry)?;
+                Ok(WaitlistPosition { entry, position })
+            })
+            .collect::<Result<Vec<_>, RegistrationError>>()
+    })
+    .await;
+
+    match result {
+        Ok(db_result) => match db_result {
+            Ok(entries) => HttpResponse::Ok().json(json!({
+                "status": "success",
+                "data": entries
+            })),
+            Err(err) => registration_error_response(err),
+        },
+        Err(blocking_err) => {
+            log::error!("Blocking error: {:?}", blocking_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Internal server error"
+            }))
+        }
+    }
+}
+
+pub async fn accept_offer(
+    audit: AuditContext,
+    pool: web::Data<DbPool>,
+    path: web::Path<(i32, i32)>,
+) -> HttpResponse {
+    let (student_id, entry_id) = path.into_inner();
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => {
+            log::error!("Failed to get database connection: {:?}", e);
+            return HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Database connection error"
+            }));
+        }
+    };
+
+    let result = web::block(move || {
+        conn.transaction(|conn| {
+            let enrollment = waitlist::accept_offer(conn, student_id, entry_id, &audit)?;
+            let warnings =
+                credit_load::warnings_for_section(conn, student_id, enrollment.section_id)?;
+            Ok::<_, RegistrationError>((enrollment, warnings))
+        })
+    })
+    .await;
+
+    match result {
+        Ok(db_result) => match db_result {
+            Ok((enrollment, warnings)) => {
+                log::info!(
+                    "Student {} accepted waitlist offer for section {}",
+                    enrollment.student_id,
+                    enrollment.section_id
+                );
+                HttpResponse::Created().json(json!({
+                    "status": "success",
+                    "data": enrollment,
+                    "warnings": warnings
+                }))
+            }
+            Err(err) => registration_error_response(err),
+        },
+        Err(blocking_err) => {
+            log::error!("Blocking error: {:?}", blocking_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Internal server error"
+            }))
+        }
+    }
+}
+
+pub async fn leave_waitlist(
+    audit: AuditContext,
+    pool: web::Data<DbPool>,
+    path: web::Path<(i32, i32)>,
+) -> HttpResponse {
+    let (student_id, entry_id) = path.into_inner();
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => {
+            log::error!("Failed to get database connection: {:?}", e);
+            return HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Database connection error"
+            }));
+        }
+    };
+
+    let result = web::block(move || {
+        conn.transaction(|conn| waitlist::leave(conn, student_id, entry_id, &audit))
+    })
+    .await;
+
+    match result {
+        Ok(db_result) => match db_result {
+            Ok(entry) => HttpResponse::Ok().json(json!({
+                "status": "success",
+                "data": entry
+            })),
+            Err(err) => registration_error_response(err),
+        },
+        Err(blocking_err) => {
+            log::error!("Blocking error: {:?}", blocking_err);
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Internal server error"
+            }))
+        }
+    }
+}
@@ -3,7 +3,8 @@ extern crate dotenv;
 
 use actix_cors::Cors;
 use actix_request_identifier::RequestIdentifier;
-use actix_web::{middleware, web, App, HttpServer};
+use actix_web::middleware::{Compress, Logger, NormalizePath};
+use actix_web::{web, App, HttpServer};
 use diesel::pg::PgConnection;
 use diesel::r2d2::{self, ConnectionManager};
 use dotenv::dotenv;
@@ -11,11 +12,21 @@ use env_logger::Env;
 use std::env;
 use std::time::Duration;
 
+mod config;
 mod handlers;
+mod middleware;
 mod models;
 mod schema;
+mod services;
 
-use handlers::{auth, student};
+use handlers::{
+    appointment, audit, auth, cart, course, credit_load, enrollment, grade, hold, instructor,
+    lottery, metrics, program, role, room, schedule, section, session, student, term, transcript,
+    waitlist,
+};
+use middleware::{JwtAuth, PrometheusMetrics, RequirePermission, RequireStudentScope};
+use models::permission::Permission;
+use services::tokens::TokenDenylist;
 
 pub type DbPool = r2d2::Pool<ConnectionManager<PgConnection>>;
 
@@ -43,33 +54,605 @@ async fn async_main() -> std::io::Result<()> {
     let pool = establish_connection_pool();
     log::info!("Database connection pool established");
 
+    tokio::spawn(services::waitlist::run_sweeper(pool.clone()));
+
+    let auth_config = web::Data::new(config::AuthConfig::from_env());
+
+    let denylist = {
+        let mut conn = pool.get().expect("Failed to get a database connection");
+        TokenDenylist::load(&mut conn).expect("Failed to load the token denylist")
+    };
+    tokio::spawn(services::tokens::run_denylist_sync(
+        pool.clone(),
+        denylist.clone(),
+    ));
+    let denylist = web::Data::new(denylist);
+
     HttpServer::new(move || {
         App::new()
             .app_data(web::Data::new(pool.clone()))
+            .app_data(auth_config.clone())
+            .app_data(denylist.clone())
             .wrap(
                 Cors::default()
                     .allowed_origin("http://localhost:3000")
-                    .allowed_methods(vec!["GET", "POST", "PUT", "DELETE"])
+                    .allowed_methods(vec!["GET", "POST", "PUT", "PATCH", "DELETE"])
                     .allowed_headers(vec!["Content-Type", "Authorization"])
                     .max_age(3600),
             )
-            .wrap(middleware::Logger::default())
-            .wrap(middleware::NormalizePath::trim())
-            .wrap(middleware::Compress::default())
+            .wrap(PrometheusMetrics)
+            .wrap(Logger::default())
+            .wrap(NormalizePath::trim())
+            .wrap(Compress::default())
             .wrap(RequestIdentifier::with_uuid())
+            .route("/metrics", web::get().to(metrics::metrics))
             .service(
                 web::scope("/api")
                     .service(
                         web::scope("/auth")
                             .route("/login", web::post().to(auth::login))
+                            .route("/refresh", web::post().to(auth::refresh))
+                            .route("/logout", web::post().to(auth::logout).wrap(JwtAuth))
                             .route("/register", web::post().to(auth::register)),
                     )
                     .service(
-                        web::scope("/v1").service(
-                            web::resource("/students")
-                                .route(web::get().to(student::get_students))
-                                .route(web::post().to(student::create_student)),
-                        ),
+                        web::scope("/v1")
+                            .wrap(JwtAuth)
+                            .service(
+                                web::resource("/me/appointment")
+                                    .route(web::get().to(appointment::get_my_appointment)),
+                            )
+                            .service(
+                                web::resource("/me/sections")
+                                    .route(web::get().to(instructor::get_my_sections)),
+                            )
+                            .service(
+                                web::resource("/students")
+                                    .route(
+                                        web::get().to(student::get_students).wrap(
+                                            RequirePermission::new(Permission::STUDENTS_READ),
+                                        ),
+                                    )
+                                    .route(
+                                        web::post().to(student::create_student).wrap(
+                                            RequirePermission::new(Permission::STUDENTS_WRITE),
+                                        ),
+                                    ),
+                            )
+                            .service(
+                                web::scope("/students/{id}")
+                                    .wrap(RequireStudentScope)
+                                    .service(
+                                        web::resource("")
+                                            .route(web::get().to(student::get_student).wrap(
+                                                RequirePermission::new(Permission::STUDENTS_READ),
+                                            ))
+                                            .route(web::put().to(student::update_student).wrap(
+                                                RequirePermission::new(Permission::STUDENTS_WRITE),
+                                            ))
+                                            .route(web::patch().to(student::patch_student).wrap(
+                                                RequirePermission::new(Permission::STUDENTS_WRITE),
+                                            ))
+                                            .route(web::delete().to(student::delete_student).wrap(
+                                                RequirePermission::new(Permission::STUDENTS_WRITE),
+                                            )),
+                                    )
+                                    .service(
+                                        web::resource("/enrollments")
+                                            .route(web::get().to(enrollment::get_enrollments).wrap(
+                                                RequirePermission::new(Permission::STUDENTS_READ),
+                                            ))
+                                            .route(
+                                                web::post().to(enrollment::create_enrollment).wrap(
+                                                    RequirePermission::new(
+                                                        Permission::ENROLLMENTS_WRITE,
+                                                    ),
+                                                ),
+                                            ),
+                                    )
+                                    .service(web::resource("/enrollments/{enrollment_id}").route(
+                                        web::delete().to(enrollment::drop_enrollment).wrap(
+                                            RequirePermission::new(Permission::ENROLLMENTS_WRITE),
+                                        ),
+                                    ))
+                                    .service(
+                                        web::resource("/enrollments/{enrollment_id}/swap").route(
+                                            web::post().to(enrollment::swap_enrollment).wrap(
+                                                RequirePermission::new(
+                                                    Permission::ENROLLMENTS_WRITE,
+                                                ),
+                                            ),
+                                        ),
+                                    )
+                                    .service(web::resource("/gpa").route(
+                                        web::get().to(grade::get_student_gpa).wrap(
+                                            RequirePermission::new(Permission::STUDENTS_READ),
+                                        ),
+                                    ))
+                                    .service(web::resource("/transcript").route(
+                                        web::get().to(transcript::get_transcript).wrap(
+                                            RequirePermission::new(Permission::STUDENTS_READ),
+                                        ),
+                                    ))
+                                    .service(
+                                        web::resource("/programs")
+                                            .route(
+                                                web::get().to(program::get_student_programs).wrap(
+                                                    RequirePermission::new(
+                                                        Permission::STUDENTS_READ,
+                                                    ),
+                                                ),
+                                            )
+                                            .route(web::post().to(program::declare_program).wrap(
+                                                RequirePermission::new(Permission::STUDENTS_WRITE),
+                                            )),
+                                    )
+                                    .service(web::resource("/programs/{program_id}").route(
+                                        web::delete().to(program::undeclare_program).wrap(
+                                            RequirePermission::new(Permission::STUDENTS_WRITE),
+                                        ),
+                                    ))
+                                    .service(web::resource("/degree-audit").route(
+                                        web::get().to(program::get_degree_audit).wrap(
+                                            RequirePermission::new(Permission::STUDENTS_READ),
+                                        ),
+                                    ))
+                                    .service(web::resource("/appointment").route(
+                                        web::get().to(appointment::get_student_appointment).wrap(
+                                            RequirePermission::new(Permission::STUDENTS_READ),
+                                        ),
+                                    ))
+                                    .service(
+                                        web::resource("/holds")
+                                            .route(web::get().to(hold::get_holds).wrap(
+                                                RequirePermission::new(Permission::STUDENTS_READ),
+                                            ))
+                                            .route(web::post().to(hold::create_hold)),
+                                    )
+                                    .service(
+                                        web::resource("/holds/{hold_id}")
+                                            .route(web::put().to(hold::update_hold))
+                                            .route(web::delete().to(hold::delete_hold)),
+                                    )
+                                    .service(
+                                        web::resource("/cart")
+                                            .route(web::get().to(cart::get_cart).wrap(
+                                                RequirePermission::new(Permission::STUDENTS_READ),
+                                            ))
+                                            .route(web::post().to(cart::add_cart_item).wrap(
+                                                RequirePermission::new(
+                                                    Permission::ENROLLMENTS_WRITE,
+                                                ),
+                                            )),
+                                    )
+                                    .service(web::resource("/cart/validate").route(
+                                        web::post().to(cart::validate_cart).wrap(
+                                            RequirePermission::new(Permission::ENROLLMENTS_WRITE),
+                                        ),
+                                    ))
+                                    .service(web::resource("/cart/submit").route(
+                                        web::post().to(cart::submit_cart).wrap(
+                                            RequirePermission::new(Permission::ENROLLMENTS_WRITE),
+                                        ),
+                                    ))
+                                    .service(web::resource("/cart/{item_id}").route(
+                                        web::delete().to(cart::remove_cart_item).wrap(
+                                            RequirePermission::new(Permission::ENROLLMENTS_WRITE),
+                                        ),
+                                    ))
+                                    .service(
+                                        web::resource("/lottery-requests")
+                                            .route(
+                                                web::get().to(lottery::get_lottery_requests).wrap(
+                                                    RequirePermission::new(
+                                                        Permission::STUDENTS_READ,
+                                                    ),
+                                                ),
+                                            )
+                                            .route(
+                                                web::put()
+                                                    .to(lottery::update_lottery_requests)
+                                                    .wrap(RequirePermission::new(
+                                                        Permission::ENROLLMENTS_WRITE,
+                                                    )),
+                                            ),
+                                    )
+                                    .service(web::resource("/credit-load").route(
+                                        web::get().to(credit_load::get_credit_load).wrap(
+                                            RequirePermission::new(Permission::STUDENTS_READ),
+                                        ),
+                                    ))
+                                    .service(
+                                        web::resource("/overloads")
+                                            .route(web::get().to(credit_load::get_overloads).wrap(
+                                                RequirePermission::new(Permission::STUDENTS_READ),
+                                            ))
+                                            .route(
+                                                web::post().to(credit_load::approve_overload).wrap(
+                                                    RequirePermission::new(
+                                                        Permission::OVERLOADS_APPROVE,
+                                                    ),
+                                                ),
+                                            ),
+                                    )
+                                    .service(web::resource("/overloads/{overload_id}").route(
+                                        web::delete().to(credit_load::revoke_overload).wrap(
+                                            RequirePermission::new(Permission::OVERLOADS_APPROVE),
+                                        ),
+                                    ))
+                                    .service(web::resource("/waitlist").route(
+                                        web::get().to(waitlist::get_waitlist).wrap(
+                                            RequirePermission::new(Permission::STUDENTS_READ),
+                                        ),
+                                    ))
+                                    .service(web::resource("/waitlist/{entry_id}").route(
+                                        web::delete().to(waitlist::leave_waitlist).wrap(
+                                            RequirePermission::new(Permission::ENROLLMENTS_WRITE),
+                                        ),
+                                    ))
+                                    .service(web::resource("/waitlist/{entry_id}/accept").route(
+                                        web::post().to(waitlist::accept_offer).wrap(
+                                            RequirePermission::new(Permission::ENROLLMENTS_WRITE),
+                                        ),
+                                    )),
+                            )
+                            .service(
+                                web::resource("/credit-limits")
+                                    .route(web::get().to(credit_load::get_credit_limits))
+                                    .route(web::post().to(credit_load::create_credit_limit).wrap(
+                                        RequirePermission::new(Permission::CREDIT_LIMITS_MANAGE),
+                                    )),
+                            )
+                            .service(
+                                web::resource("/credit-limits/{id}")
+                                    .route(web::put().to(credit_load::update_credit_limit).wrap(
+                                        RequirePermission::new(Permission::CREDIT_LIMITS_MANAGE),
+                                    ))
+                                    .route(
+                                        web::delete().to(credit_load::delete_credit_limit).wrap(
+                                            RequirePermission::new(
+                                                Permission::CREDIT_LIMITS_MANAGE,
+                                            ),
+                                        ),
+                                    ),
+                            )
+                            .service(
+                                web::resource("/programs")
+                                    .route(web::get().to(program::get_programs))
+                                    .route(
+                                        web::post().to(program::create_program).wrap(
+                                            RequirePermission::new(Permission::PROGRAMS_MANAGE),
+                                        ),
+                                    ),
+                            )
+                            .service(
+                                web::resource("/programs/{id}")
+                                    .route(web::get().to(program::get_program))
+                                    .route(
+                                        web::put().to(program::update_program).wrap(
+                                            RequirePermission::new(Permission::PROGRAMS_MANAGE),
+                                        ),
+                                    )
+                                    .route(
+                                        web::delete().to(program::delete_program).wrap(
+                                            RequirePermission::new(Permission::PROGRAMS_MANAGE),
+                                        ),
+                                    ),
+                            )
+                            .service(
+                                web::resource("/courses")
+                                    .route(web::get().to(course::get_courses))
+                                    .route(
+                                        web::post().to(course::create_course).wrap(
+                                            RequirePermission::new(Permission::COURSES_MANAGE),
+                                        ),
+                                    ),
+                            )
+                            .service(
+                                web::resource("/courses/{id}")
+                                    .route(web::get().to(course::get_course))
+                                    .route(
+                                        web::put().to(course::update_course).wrap(
+                                            RequirePermission::new(Permission::COURSES_MANAGE),
+                                        ),
+                                    )
+                                    .route(
+                                        web::patch().to(course::patch_course).wrap(
+                                            RequirePermission::new(Permission::COURSES_MANAGE),
+                                        ),
+                                    )
+                                    .route(
+                                        web::delete().to(course::delete_course).wrap(
+                                            RequirePermission::new(Permission::COURSES_MANAGE),
+                                        ),
+                                    ),
+                            )
+                            .service(
+                                web::resource("/courses/{id}/requisites/check").route(
+                                    web::get().to(course::check_requisites).wrap(
+                                        RequirePermission::new(Permission::REGISTRATION_MANAGE),
+                                    ),
+                                ),
+                            )
+                            .service(
+                                web::resource("/instructors")
+                                    .route(web::get().to(instructor::get_instructors))
+                                    .route(web::post().to(instructor::create_instructor).wrap(
+                                        RequirePermission::new(Permission::INSTRUCTORS_MANAGE),
+                                    )),
+                            )
+                            .service(
+                                web::resource("/instructors/{id}")
+                                    .route(web::get().to(instructor::get_instructor))
+                                    .route(web::put().to(instructor::update_instructor).wrap(
+                                        RequirePermission::new(Permission::INSTRUCTORS_MANAGE),
+                                    ))
+                                    .route(web::delete().to(instructor::delete_instructor).wrap(
+                                        RequirePermission::new(Permission::INSTRUCTORS_MANAGE),
+                                    )),
+                            )
+                            .service(
+                                web::resource("/instructors/{id}/sections")
+                                    .route(web::get().to(instructor::get_instructor_sections)),
+                            )
+                            .service(
+                                web::resource("/audit-logs").route(
+                                    web::get()
+                                        .to(audit::get_audit_logs)
+                                        .wrap(RequirePermission::new(Permission::AUDIT_READ)),
+                                ),
+                            )
+                            .service(
+                                web::resource("/buildings")
+                                    .route(web::get().to(room::get_buildings))
+                                    .route(
+                                        web::post()
+                                            .to(room::create_building)
+                                            .wrap(RequirePermission::new(Permission::ROOMS_MANAGE)),
+                                    ),
+                            )
+                            .service(
+                                web::resource("/buildings/{id}")
+                                    .route(web::get().to(room::get_building))
+                                    .route(
+                                        web::put()
+                                            .to(room::update_building)
+                                            .wrap(RequirePermission::new(Permission::ROOMS_MANAGE)),
+                                    )
+                                    .route(
+                                        web::delete()
+                                            .to(room::delete_building)
+                                            .wrap(RequirePermission::new(Permission::ROOMS_MANAGE)),
+                                    ),
+                            )
+                            .service(
+                                web::resource("/permissions").route(
+                                    web::get()
+                                        .to(role::get_permissions)
+                                        .wrap(RequirePermission::new(Permission::ROLES_MANAGE)),
+                                ),
+                            )
+                            .service(
+                                web::resource("/roles")
+                                    .route(
+                                        web::get()
+                                            .to(role::get_roles)
+                                            .wrap(RequirePermission::new(Permission::ROLES_MANAGE)),
+                                    )
+                                    .route(
+                                        web::post()
+                                            .to(role::create_role)
+                                            .wrap(RequirePermission::new(Permission::ROLES_MANAGE)),
+                                    ),
+                            )
+                            .service(
+                                web::resource("/roles/{id}")
+                                    .route(
+                                        web::get()
+                                            .to(role::get_role)
+                                            .wrap(RequirePermission::new(Permission::ROLES_MANAGE)),
+                                    )
+                                    .route(
+                                        web::put()
+                                            .to(role::update_role)
+                                            .wrap(RequirePermission::new(Permission::ROLES_MANAGE)),
+                                    )
+                                    .route(
+                                        web::delete()
+                                            .to(role::delete_role)
+                                            .wrap(RequirePermission::new(Permission::ROLES_MANAGE)),
+                                    ),
+                            )
+                            .service(
+                                web::resource("/roles/{id}/permissions").route(
+                                    web::put()
+                                        .to(role::update_role_permissions)
+                                        .wrap(RequirePermission::new(Permission::ROLES_MANAGE)),
+                                ),
+                            )
+                            .service(
+                                web::resource("/rooms")
+                                    .route(web::get().to(room::get_rooms))
+                                    .route(
+                                        web::post()
+                                            .to(room::create_room)
+                                            .wrap(RequirePermission::new(Permission::ROOMS_MANAGE)),
+                                    ),
+                            )
+                            .service(
+                                web::resource("/rooms/{id}")
+                                    .route(web::get().to(room::get_room))
+                                    .route(
+                                        web::put()
+                                            .to(room::update_room)
+                                            .wrap(RequirePermission::new(Permission::ROOMS_MANAGE)),
+                                    )
+                                    .route(
+                                        web::delete()
+                                            .to(room::delete_room)
+                                            .wrap(RequirePermission::new(Permission::ROOMS_MANAGE)),
+                                    ),
+                            )
+                            .service(
+                                web::resource("/sessions")
+                                    .route(web::get().to(session::get_sessions))
+                                    .route(web::delete().to(session::revoke_sessions)),
+                            )
+                            .service(
+                                web::resource("/sessions/{id}")
+                                    .route(web::delete().to(session::revoke_session)),
+                            )
+                            .service(
+                                web::resource("/users/{id}/role").route(
+                                    web::put()
+                                        .to(role::update_user_role)
+                                        .wrap(RequirePermission::new(Permission::ROLES_MANAGE)),
+                                ),
+                            )
+                            .service(
+                                web::resource("/users/{id}/sessions")
+                                    .route(
+                                        web::get().to(session::get_user_sessions).wrap(
+                                            RequirePermission::new(Permission::SESSIONS_MANAGE),
+                                        ),
+                                    )
+                                    .route(
+                                        web::delete().to(session::revoke_user_sessions).wrap(
+                                            RequirePermission::new(Permission::SESSIONS_MANAGE),
+                                        ),
+                                    ),
+                            )
+                            .service(
+                                web::resource("/terms")
+                                    .route(web::get().to(term::get_terms))
+                                    .route(
+                                        web::post()
+                                            .to(term::create_term)
+                                            .wrap(RequirePermission::new(Permission::TERMS_MANAGE)),
+                                    ),
+                            )
+                            .service(
+                                web::resource("/terms/{term}")
+                                    .route(web::get().to(term::get_term))
+                                    .route(
+                                        web::put()
+                                            .to(term::update_term)
+                                            .wrap(RequirePermission::new(Permission::TERMS_MANAGE)),
+                                    )
+                                    .route(
+                                        web::delete()
+                                            .to(term::delete_term)
+                                            .wrap(RequirePermission::new(Permission::TERMS_MANAGE)),
+                                    ),
+                            )
+                            .service(
+                                web::resource("/terms/{term}/registration-windows")
+                                    .route(web::get().to(appointment::get_windows))
+                                    .route(web::post().to(appointment::create_window).wrap(
+                                        RequirePermission::new(Permission::REGISTRATION_MANAGE),
+                                    )),
+                            )
+                            .service(
+                                web::resource("/terms/{term}/registration-windows/{id}")
+                                    .route(web::put().to(appointment::update_window).wrap(
+                                        RequirePermission::new(Permission::REGISTRATION_MANAGE),
+                                    ))
+                                    .route(web::delete().to(appointment::delete_window).wrap(
+                                        RequirePermission::new(Permission::REGISTRATION_MANAGE),
+                                    )),
+                            )
+                            .service(
+                                web::resource("/terms/{term}/appointment-overrides")
+                                    .route(web::get().to(appointment::get_overrides).wrap(
+                                        RequirePermission::new(Permission::REGISTRATION_MANAGE),
+                                    ))
+                                    .route(web::post().to(appointment::create_override).wrap(
+                                        RequirePermission::new(Permission::REGISTRATION_MANAGE),
+                                    )),
+                            )
+                            .service(
+                                web::resource("/terms/{term}/appointment-overrides/{id}").route(
+                                    web::delete().to(appointment::delete_override).wrap(
+                                        RequirePermission::new(Permission::REGISTRATION_MANAGE),
+                                    ),
+                                ),
+                            )
+                            .service(
+                                web::resource("/terms/{term}/lottery-runs")
+                                    .route(web::get().to(lottery::get_lottery_runs).wrap(
+                                        RequirePermission::new(Permission::REGISTRATION_MANAGE),
+                                    ))
+                                    .route(web::post().to(lottery::create_lottery_run).wrap(
+                                        RequirePermission::new(Permission::REGISTRATION_MANAGE),
+                                    )),
+                            )
+                            .service(
+                                web::resource("/terms/{term}/lottery-runs/{id}").route(
+                                    web::get().to(lottery::get_lottery_run).wrap(
+                                        RequirePermission::new(Permission::REGISTRATION_MANAGE),
+                                    ),
+                                ),
+                            )
+                            .service(
+                                web::resource("/terms/{term}/room-utilization").route(
+                                    web::get()
+                                        .to(room::get_room_utilization)
+                                        .wrap(RequirePermission::new(Permission::ROOMS_MANAGE)),
+                                ),
+                            )
+                            .service(
+                                web::resource("/terms/{term}/schedules")
+                                    .route(web::post().to(schedule::generate_schedules)),
+                            )
+                            .service(
+                                web::resource("/terms/{term}/sections")
+                                    .route(web::get().to(section::get_sections))
+                                    .route(
+                                        web::post().to(section::create_section).wrap(
+                                            RequirePermission::new(Permission::SECTIONS_MANAGE),
+                                        ),
+                                    ),
+                            )
+                            .service(
+                                web::resource("/terms/{term}/sections/{id}")
+                                    .route(web::get().to(section::get_section))
+                                    .route(
+                                        web::put().to(section::update_section).wrap(
+                                            RequirePermission::new(Permission::SECTIONS_MANAGE),
+                                        ),
+                                    )
+                                    .route(
+                                        web::delete().to(section::delete_section).wrap(
+                                            RequirePermission::new(Permission::SECTIONS_MANAGE),
+                                        ),
+                                    ),
+                            )
+                            .service(
+                                web::resource("/terms/{term}/sections/{id}/grades")
+                                    .route(web::get().to(grade::get_section_grades))
+                                    .route(
+                                        web::put()
+                                            .to(grade::post_section_grades)
+                                            .wrap(RequirePermission::new(Permission::GRADES_POST)),
+                                    ),
+                            )
+                            .service(
+                                web::resource("/grade-scales")
+                                    .route(web::get().to(grade::get_grade_scales))
+                                    .route(web::post().to(grade::create_grade_scale).wrap(
+                                        RequirePermission::new(Permission::GRADE_SCALES_MANAGE),
+                                    )),
+                            )
+                            .service(
+                                web::resource("/grade-scales/{id}")
+                                    .route(web::get().to(grade::get_grade_scale))
+                                    .route(web::put().to(grade::update_grade_scale).wrap(
+                                        RequirePermission::new(Permission::GRADE_SCALES_MANAGE),
+                                    ))
+                                    .route(web::delete().to(grade::delete_grade_scale).wrap(
+                                        RequirePermission::new(Permission::GRADE_SCALES_MANAGE),
+                                    )),
+                            ),
                     ),
             )
     })
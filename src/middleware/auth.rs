@@ -1,24 +1,83 @@
-use actix_web::error::ErrorUnauthorized;
+use actix_web::dev::Payload;
+use actix_web::error::InternalError;
 use actix_web::http::header;
 use actix_web::{
     dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
-    Error, HttpMessage,
+    web, Error, FromRequest, HttpMessage, HttpRequest, HttpResponse, HttpResponseBuilder,
 };
-use futures_util::future::LocalBoxFuture;
-use jsonwebtoken::{decode, Algorithm, DecodingKey, Validation};
-use serde::{Deserialize, Serialize};
+use diesel::prelude::*;
+use futures::future::LocalBoxFuture;
+use serde_json::json;
 use std::env;
 use std::future::{ready, Ready};
 
-#[derive(Debug, Serialize, Deserialize)]
-struct Claims {
-    sub: String,  // Subject (user ID)
-    exp: usize,   // Expiration time
-    role: String, // User role
-    #[serde(skip_serializing_if = "Option::is_none")]
-    aud: Option<String>, // Optional audience
+use crate::models::permission::Permission;
+use crate::models::student::StudentScope;
+use crate::models::user::{Claims, User};
+use crate::models::AuditContext;
+use crate::services::tokens::TokenDenylist;
+use crate::DbPool;
+
+/// Secret that access tokens are signed and verified with.
+pub fn jwt_secret() -> String {
+    env::var("JWT_SECRET").unwrap_or_else(|_| "default_secret".to_string())
+}
+
+/// Ends the request with the API's JSON error body.
+pub(crate) fn json_error(mut response: HttpResponseBuilder, message: String) -> Error {
+    let body = response.json(json!({
+        "status": "error",
+        "message": message
+    }));
+    InternalError::from_response(message, body).into()
 }
 
+fn unauthorized(message: &'static str) -> Error {
+    json_error(HttpResponse::Unauthorized(), message.to_string())
+}
+
+/// Verifies the bearer token in the request headers.
+fn bearer_claims(headers: &header::HeaderMap) -> Result<Claims, &'static str> {
+    let auth_header = headers
+        .get(header::AUTHORIZATION)
+        .ok_or("No authorization header found")?;
+    let auth_str = auth_header
+        .to_str()
+        .map_err(|_| "Invalid authorization header")?;
+    let token = auth_str
+        .strip_prefix("Bearer ")
+        .ok_or("Invalid authorization scheme")?
+        .trim();
+    if token.is_empty() {
+        return Err("Empty token");
+    }
+    User::verify_token(token, &jwt_secret()).map_err(|_| "Invalid token")
+}
+
+/// Verifies the bearer token and checks that it has not been revoked.
+fn verified_claims(req: &HttpRequest) -> Result<Claims, &'static str> {
+    let claims = bearer_claims(req.headers())?;
+    let denylist = req
+        .app_data::<web::Data<TokenDenylist>>()
+        .expect("the token denylist is registered as app data");
+    if denylist.contains(&claims.jti) {
+        return Err("Token has been revoked");
+    }
+    Ok(claims)
+}
+
+/// The claims `JwtAuth` stored on the request, or those of its bearer token
+/// on routes the middleware does not wrap, held to the same checks.
+pub fn request_claims(req: &HttpRequest) -> Result<Claims, &'static str> {
+    if let Some(claims) = req.extensions().get::<Claims>() {
+        return Ok(claims.clone());
+    }
+    verified_claims(req)
+}
+
+/// Rejects requests without a valid access token, or with one that has been
+/// revoked, and stores its claims in the request extensions for handlers and
+/// extractors.
 pub struct JwtAuth;
 
 impl<S, B> Transform<S, ServiceRequest> for JwtAuth
@@ -55,56 +114,120 @@ where
     forward_ready!(service);
 
     fn call(&self, req: ServiceRequest) -> Self::Future {
-        // Get the JWT secret key from environment
-        let jwt_secret = match env::var("JWT_SECRET") {
-            Ok(s) => s,
-            Err(_) => {
-                return Box::pin(async { Err(ErrorUnauthorized("Server configuration error")) });
+        match verified_claims(req.request()) {
+            Ok(claims) => {
+                req.extensions_mut().insert(claims);
+                let fut = self.service.call(req);
+                Box::pin(async move {
+                    let res = fut.await?;
+                    Ok(res)
+                })
             }
-        };
+            Err(message) => Box::pin(async move { Err(unauthorized(message)) }),
+        }
+    }
+}
 
-        // Get the Authorization header
-        let auth_header = match req.headers().get(header::AUTHORIZATION) {
-            Some(h) => h,
-            None => {
-                return Box::pin(async { Err(ErrorUnauthorized("No authorization header found")) });
-            }
-        };
+/// The user behind the request's access token. Handlers take it as an
+/// argument; the request is rejected with `401` when there is no valid token.
+#[derive(Debug, Clone)]
+pub struct AuthenticatedUser {
+    pub user_id: i32,
+    pub role: String,
+    /// The session the access token was issued to.
+    pub session_id: i32,
+}
 
-        // Extract the token
-        let auth_str = match auth_header.to_str() {
-            Ok(s) => s,
-            Err(_) => {
-                return Box::pin(async { Err(ErrorUnauthorized("Invalid authorization header")) });
-            }
-        };
+impl AuthenticatedUser {
+    /// Whether the user's role has been granted the permission, for checks
+    /// that depend on the data a request touches rather than its route.
+    pub fn has_permission(&self, conn: &mut PgConnection, permission: &str) -> QueryResult<bool> {
+        Permission::granted(conn, &self.role, permission)
+    }
+}
 
-        if !auth_str.starts_with("Bearer ") {
-            return Box::pin(async { Err(ErrorUnauthorized("Invalid authorization scheme")) });
+impl From<Claims> for AuthenticatedUser {
+    fn from(claims: Claims) -> Self {
+        AuthenticatedUser {
+            user_id: claims.sub,
+            role: claims.role,
+            session_id: claims.sid,
         }
+    }
+}
 
-        let token = auth_str[7..].trim();
-        if token.is_empty() {
-            return Box::pin(async { Err(ErrorUnauthorized("Empty token")) });
-        }
+impl FromRequest for AuthenticatedUser {
+    type Error = Error;
+    type Future = Ready<Result<Self, Self::Error>>;
 
-        // Validate the token
-        let validation = Validation::new(Algorithm::HS256);
-        match decode::<Claims>(
-            token,
-            &DecodingKey::from_secret(jwt_secret.as_bytes()),
-            &validation,
-        ) {
-            Ok(token_data) => {
-                // Set claims info in request extensions
-                req.extensions_mut().insert(token_data.claims);
-                let fut = self.service.call(req);
-                Box::pin(async move {
-                    let res = fut.await?;
-                    Ok(res)
-                })
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        ready(
+            request_claims(req)
+                .map(AuthenticatedUser::from)
+                .map_err(unauthorized),
+        )
+    }
+}
+
+impl FromRequest for StudentScope {
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        let claims = request_claims(req);
+        let pool = req
+            .app_data::<web::Data<DbPool>>()
+            .cloned()
+            .expect("the database pool is registered as app data");
+
+        Box::pin(async move {
+            let claims = claims.map_err(unauthorized)?;
+            let scope = web::block(move || {
+                let mut conn = pool.get().map_err(|e| e.to_string())?;
+                StudentScope::for_caller(&mut conn, &claims.role, claims.sub)
+                    .map_err(|e| e.to_string())
+            })
+            .await;
+
+            match scope {
+                Ok(Ok(scope)) => Ok(scope),
+                Ok(Err(e)) => {
+                    log::error!("Failed to resolve the student scope: {}", e);
+                    Err(json_error(
+                        HttpResponse::InternalServerError(),
+                        "Failed to check student access".to_string(),
+                    ))
+                }
+                Err(blocking_err) => {
+                    log::error!("Blocking error: {:?}", blocking_err);
+                    Err(json_error(
+                        HttpResponse::InternalServerError(),
+                        "Internal server error".to_string(),
+                    ))
+                }
             }
-            Err(_) => Box::pin(async { Err(ErrorUnauthorized("Invalid token")) }),
-        }
+        })
+    }
+}
+
+/// Caller identity and origin for audit log entries. Never rejects a request:
+/// on routes outside `JwtAuth`, such as login, there is simply no user.
+impl FromRequest for AuditContext {
+    type Error = Error;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        ready(Ok(AuditContext {
+            user_id: request_claims(req).ok().map(|claims| claims.sub),
+            ip_address: req
+                .connection_info()
+                .realip_remote_addr()
+                .map(|addr| addr.to_string()),
+            user_agent: req
+                .headers()
+                .get(header::USER_AGENT)
+                .and_then(|value| value.to_str().ok())
+                .map(|value| value.to_string()),
+        }))
     }
 }
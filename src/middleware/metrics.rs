@@ -1,10 +1,9 @@
 use crate::config::prometheus::{HTTP_REQUEST_COUNTER, HTTP_REQUEST_DURATION};
-use actix_web::http::StatusCode;
 use actix_web::{
     dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
-    Error, HttpMessage,
+    Error,
 };
-use futures_util::future::LocalBoxFuture;
+use futures::future::LocalBoxFuture;
 use std::future::{ready, Ready};
 use std::time::Instant;
 
@@ -45,7 +44,6 @@ where
 
     fn call(&self, req: ServiceRequest) -> Self::Future {
         let start = Instant::now();
-        let path = req.path().to_string();
 
         let fut = self.service.call(req);
 
@@ -53,6 +51,12 @@ where
             let res = fut.await?;
             let elapsed = start.elapsed().as_secs_f64();
             let status = res.status().as_u16().to_string();
+            // Label by route pattern rather than path so ids do not each get
+            // their own series
+            let path = res
+                .request()
+                .match_pattern()
+                .unwrap_or_else(|| "unmatched".to_string());
 
             // Record request count by endpoint and status
             HTTP_REQUEST_COUNTER
@@ -1,4 +1,9 @@
 pub mod auth;
 pub mod metrics;
+pub mod permission;
+pub mod student_scope;
 
+pub use auth::{AuthenticatedUser, JwtAuth};
 pub use metrics::PrometheusMetrics;
+pub use permission::RequirePermission;
+pub use student_scope::RequireStudentScope;
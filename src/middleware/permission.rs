@@ -0,0 +1,136 @@
+use actix_web::{
+    dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
+    web, Error, HttpResponse, HttpResponseBuilder,
+};
+use futures::future::LocalBoxFuture;
+use serde_json::json;
+use std::future::{ready, Ready};
+use std::rc::Rc;
+
+use crate::middleware::auth::{json_error, request_claims, AuthenticatedUser};
+use crate::models::permission::Permission;
+use crate::DbPool;
+
+/// Route guard that lets a request through only if the caller's role has
+/// been granted the permission. Wrap it around routes behind `JwtAuth`:
+///
+/// `web::get().to(handler).wrap(RequirePermission::new(Permission::STUDENTS_READ))`
+pub struct RequirePermission {
+    permission: &'static str,
+}
+
+impl RequirePermission {
+    pub fn new(permission: &'static str) -> Self {
+        RequirePermission { permission }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for RequirePermission
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = RequirePermissionMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(RequirePermissionMiddleware {
+            service: Rc::new(service),
+            permission: self.permission,
+        }))
+    }
+}
+
+pub struct RequirePermissionMiddleware<S> {
+    service: Rc<S>,
+    permission: &'static str,
+}
+
+impl<S, B> Service<ServiceRequest> for RequirePermissionMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let service = Rc::clone(&self.service);
+        let permission = self.permission;
+
+        Box::pin(async move {
+            let claims = request_claims(req.request())
+                .map_err(|message| json_error(HttpResponse::Unauthorized(), message.to_string()))?;
+            let pool = req
+                .app_data::<web::Data<DbPool>>()
+                .cloned()
+                .expect("the database pool is registered as app data");
+
+            check(pool, claims.role, permission)
+                .await
+                .map_err(|(response, message)| json_error(response, message))?;
+            service.call(req).await
+        })
+    }
+}
+
+/// Checks that the role has been granted the permission, returning the
+/// response to answer with when it has not.
+async fn check(
+    pool: web::Data<DbPool>,
+    role: String,
+    permission: &'static str,
+) -> Result<(), (HttpResponseBuilder, String)> {
+    let granted = web::block(move || {
+        let mut conn = pool.get().map_err(|e| e.to_string())?;
+        Permission::granted(&mut conn, &role, permission).map_err(|e| e.to_string())
+    })
+    .await;
+
+    match granted {
+        Ok(Ok(true)) => Ok(()),
+        Ok(Ok(false)) => Err((
+            HttpResponse::Forbidden(),
+            format!("Requires the {} permission", permission),
+        )),
+        Ok(Err(e)) => {
+            log::error!("Failed to check permission {}: {}", permission, e);
+            Err((
+                HttpResponse::InternalServerError(),
+                "Failed to check permissions".to_string(),
+            ))
+        }
+        Err(blocking_err) => {
+            log::error!("Blocking error: {:?}", blocking_err);
+            Err((
+                HttpResponse::InternalServerError(),
+                "Internal server error".to_string(),
+            ))
+        }
+    }
+}
+
+/// The same check as `RequirePermission`, for handlers where it depends on
+/// the request body, such as an override flag.
+pub async fn require_permission(
+    pool: &web::Data<DbPool>,
+    user: &AuthenticatedUser,
+    permission: &'static str,
+) -> Result<(), HttpResponse> {
+    check(pool.clone(), user.role.clone(), permission)
+        .await
+        .map_err(|(mut response, message)| {
+            response.json(json!({
+                "status": "error",
+                "message": message
+            }))
+        })
+}
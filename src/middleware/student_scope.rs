@@ -0,0 +1,105 @@
+use actix_web::{
+    dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
+    web, Error, HttpResponse,
+};
+use futures::future::LocalBoxFuture;
+use std::future::{ready, Ready};
+use std::rc::Rc;
+
+use crate::middleware::auth::{json_error, request_claims};
+use crate::models::student::StudentScope;
+use crate::DbPool;
+
+/// Scope guard that lets a request for `/students/{id}` or anything below it
+/// through only if the student is within the caller's `StudentScope`. Others
+/// are answered with `404`, as if the student did not exist:
+///
+/// `web::scope("/students/{id}").wrap(RequireStudentScope)`
+pub struct RequireStudentScope;
+
+impl<S, B> Transform<S, ServiceRequest> for RequireStudentScope
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = RequireStudentScopeMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(RequireStudentScopeMiddleware {
+            service: Rc::new(service),
+        }))
+    }
+}
+
+pub struct RequireStudentScopeMiddleware<S> {
+    service: Rc<S>,
+}
+
+impl<S, B> Service<ServiceRequest> for RequireStudentScopeMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let service = Rc::clone(&self.service);
+
+        Box::pin(async move {
+            let claims = request_claims(req.request())
+                .map_err(|message| json_error(HttpResponse::Unauthorized(), message.to_string()))?;
+            let Some(student_id) = req
+                .match_info()
+                .get("id")
+                .and_then(|id| id.parse::<i32>().ok())
+            else {
+                return Err(not_found());
+            };
+            let pool = req
+                .app_data::<web::Data<DbPool>>()
+                .cloned()
+                .expect("the database pool is registered as app data");
+
+            let visible = web::block(move || {
+                let mut conn = pool.get().map_err(|e| e.to_string())?;
+                StudentScope::for_caller(&mut conn, &claims.role, claims.sub)
+                    .and_then(|scope| scope.contains(&mut conn, student_id))
+                    .map_err(|e| e.to_string())
+            })
+            .await;
+
+            match visible {
+                Ok(Ok(true)) => service.call(req).await,
+                Ok(Ok(false)) => Err(not_found()),
+                Ok(Err(e)) => {
+                    log::error!("Failed to check the scope of student {}: {}", student_id, e);
+                    Err(json_error(
+                        HttpResponse::InternalServerError(),
+                        "Failed to check student access".to_string(),
+                    ))
+                }
+                Err(blocking_err) => {
+                    log::error!("Blocking error: {:?}", blocking_err);
+                    Err(json_error(
+                        HttpResponse::InternalServerError(),
+                        "Internal server error".to_string(),
+                    ))
+                }
+            }
+        })
+    }
+}
+
+fn not_found() -> Error {
+    json_error(HttpResponse::NotFound(), "Student not found".to_string())
+}
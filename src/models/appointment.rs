@@ -0,0 +1,71 @@
+use crate::models::student::Student;
+use crate::models::term::Term;
+use crate::schema::{appointment_overrides, registration_windows};
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// When registration for a term opens to one group of students: those with
+/// at least `min_credits` earned, those in `cohort`, or everyone when
+/// neither is set.
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Identifiable, Associations)]
+#[diesel(belongs_to(Term))]
+#[diesel(table_name = registration_windows)]
+pub struct RegistrationWindow {
+    pub id: i32,
+    pub term_id: i32,
+    pub name: String,
+    pub opens_at: DateTime<Utc>,
+    pub min_credits: Option<i32>,
+    pub cohort: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Insertable, AsChangeset)]
+#[diesel(table_name = registration_windows)]
+#[diesel(treat_none_as_null = true)]
+pub struct NewRegistrationWindow {
+    pub term_id: i32,
+    pub name: String,
+    pub opens_at: DateTime<Utc>,
+    pub min_credits: Option<i32>,
+    pub cohort: Option<String>,
+}
+
+impl RegistrationWindow {
+    /// Whether a student with `credits_earned` in `cohort` falls in the
+    /// window.
+    pub fn admits(&self, credits_earned: i32, cohort: Option<&str>) -> bool {
+        match (self.min_credits, &self.cohort) {
+            (Some(min_credits), _) => credits_earned >= min_credits,
+            (None, Some(window_cohort)) => cohort == Some(window_cohort.as_str()),
+            (None, None) => true,
+        }
+    }
+}
+
+/// A fixed registration appointment for one student in one term.
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Identifiable, Associations)]
+#[diesel(belongs_to(Student))]
+#[diesel(belongs_to(Term))]
+#[diesel(table_name = appointment_overrides)]
+pub struct AppointmentOverride {
+    pub id: i32,
+    pub student_id: i32,
+    pub term_id: i32,
+    pub opens_at: DateTime<Utc>,
+    pub reason: String,
+    pub created_by: Option<i32>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = appointment_overrides)]
+pub struct NewAppointmentOverride {
+    pub student_id: i32,
+    pub term_id: i32,
+    pub opens_at: DateTime<Utc>,
+    pub reason: String,
+    pub created_by: Option<i32>,
+}
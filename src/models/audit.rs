@@ -39,8 +39,12 @@ impl FromSql<Jsonb, Pg> for Json {
     fn from_sql(
         bytes: <Pg as diesel::backend::Backend>::RawValue<'_>,
     ) -> deserialize::Result<Self> {
-        let value = serde_json::from_slice(bytes.as_bytes())?;
-        Ok(Json(value))
+        // Skip the JSONB version byte written by `to_sql`
+        let bytes = bytes.as_bytes();
+        match bytes.split_first() {
+            Some((1, json)) => Ok(Json(serde_json::from_slice(json)?)),
+            _ => Err("Unsupported JSONB encoding version".into()),
+        }
     }
 }
 
@@ -91,3 +95,32 @@ impl AuditLog {
         }
     }
 }
+
+/// Who performed a request and from where, carried into every audit entry the
+/// request writes.
+#[derive(Debug, Clone, Default)]
+pub struct AuditContext {
+    pub user_id: Option<i32>,
+    pub ip_address: Option<String>,
+    pub user_agent: Option<String>,
+}
+
+impl AuditContext {
+    pub fn activity(
+        &self,
+        action: &str,
+        entity_type: &str,
+        entity_id: Option<i32>,
+        details: Option<JsonValue>,
+    ) -> NewAuditLog {
+        AuditLog::new_activity(
+            self.user_id,
+            action,
+            entity_type,
+            entity_id,
+            details,
+            self.ip_address.clone(),
+            self.user_agent.clone(),
+        )
+    }
+}
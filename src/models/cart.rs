@@ -0,0 +1,54 @@
+use crate::models::section::{Section, SectionDetail};
+use crate::models::student::Student;
+use crate::schema::{cart_items, sections};
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// A section in a student's registration cart.
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Identifiable, Associations)]
+#[diesel(belongs_to(Student))]
+#[diesel(belongs_to(Section))]
+#[diesel(table_name = cart_items)]
+pub struct CartItem {
+    pub id: i32,
+    pub student_id: i32,
+    pub section_id: i32,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = cart_items)]
+pub struct NewCartItem {
+    pub student_id: i32,
+    pub section_id: i32,
+}
+
+/// A cart item with the section it holds.
+#[derive(Debug, Serialize)]
+pub struct CartItemDetail {
+    #[serde(flatten)]
+    pub item: CartItem,
+    pub section: SectionDetail,
+}
+
+impl CartItemDetail {
+    pub fn load(conn: &mut PgConnection, items: Vec<CartItem>) -> QueryResult<Vec<CartItemDetail>> {
+        let section_ids: Vec<i32> = items.iter().map(|item| item.section_id).collect();
+        let sections = sections::table
+            .filter(sections::id.eq_any(section_ids))
+            .load::<Section>(conn)?;
+        let details = SectionDetail::load(conn, sections)?;
+
+        Ok(items
+            .into_iter()
+            .filter_map(|item| {
+                let section = details
+                    .iter()
+                    .find(|detail| detail.section.id == item.section_id)?
+                    .clone();
+                Some(CartItemDetail { item, section })
+            })
+            .collect())
+    }
+}
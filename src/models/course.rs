@@ -0,0 +1,120 @@
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use serde::{Deserialize, Deserializer, Serialize};
+use validator::{Validate, ValidationError};
+use crate::models::requisite::Requirement;
+use crate::schema::courses;
+
+#[derive(Debug, Serialize, Deserialize, Queryable, Identifiable)]
+#[diesel(table_name = courses)]
+pub struct Course {
+    pub id: i32,
+    pub code: String,
+    pub title: String,
+    pub credits: i32,
+    pub department: String,
+    pub description: Option<String>,
+    pub is_active: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub prerequisites: Option<String>,
+    pub corequisites: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Validate, Insertable)]
+#[diesel(table_name = courses)]
+pub struct NewCourse {
+    #[validate(length(min = 2, max = 20))]
+    pub code: String,
+    #[validate(length(min = 1, max = 255))]
+    pub title: String,
+    #[validate(range(min = 0, max = 30))]
+    pub credits: i32,
+    #[validate(length(min = 1, max = 100))]
+    pub department: String,
+    pub description: Option<String>,
+    #[serde(default = "default_active")]
+    pub is_active: bool,
+    #[validate(custom = "validate_requisites")]
+    pub prerequisites: Option<String>,
+    #[validate(custom = "validate_requisites")]
+    pub corequisites: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Validate, AsChangeset)]
+#[diesel(table_name = courses)]
+pub struct UpdateCourse {
+    #[validate(length(min = 2, max = 20))]
+    pub code: Option<String>,
+    #[validate(length(min = 1, max = 255))]
+    pub title: Option<String>,
+    #[validate(range(min = 0, max = 30))]
+    pub credits: Option<i32>,
+    #[validate(length(min = 1, max = 100))]
+    pub department: Option<String>,
+    /// `Some(None)` clears the description; an absent field leaves it
+    /// unchanged.
+    #[serde(default, deserialize_with = "deserialize_some")]
+    pub description: Option<Option<String>>,
+    pub is_active: Option<bool>,
+    /// `Some(None)` clears the rule; an absent field leaves it unchanged.
+    #[serde(default, deserialize_with = "deserialize_some")]
+    #[validate(custom = "validate_requisites")]
+    pub prerequisites: Option<Option<String>>,
+    #[serde(default, deserialize_with = "deserialize_some")]
+    #[validate(custom = "validate_requisites")]
+    pub corequisites: Option<Option<String>>,
+}
+
+fn default_active() -> bool {
+    true
+}
+
+fn validate_requisites(expression: &str) -> Result<(), ValidationError> {
+    Requirement::parse(expression).map(|_| ()).map_err(|err| {
+        let mut error = ValidationError::new("requisite_expression");
+        error.message = Some(err.to_string().into());
+        error
+    })
+}
+
+/// Distinguishes an explicit `null` from a missing field.
+fn deserialize_some<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    T::deserialize(deserializer).map(Some)
+}
+
+/// Rewrites a requisite expression in canonical form, so stored rules read
+/// the same regardless of how they were typed. Blank text means no rule;
+/// invalid expressions are left alone for validation to reject.
+pub fn normalize_requisites(expression: Option<String>) -> Option<String> {
+    let text = expression.filter(|text| !text.trim().is_empty())?;
+    match Requirement::parse(&text) {
+        Ok(requirement) => Some(requirement.to_string()),
+        Err(_) => Some(text),
+    }
+}
+
+/// Catalog codes are stored trimmed and upper-cased so "cs101" and "CS101 "
+/// resolve to the same course.
+pub fn normalize_code(code: &str) -> String {
+    code.trim().to_uppercase()
+}
+
+impl From<NewCourse> for UpdateCourse {
+    fn from(course: NewCourse) -> Self {
+        UpdateCourse {
+            code: Some(course.code),
+            title: Some(course.title),
+            credits: Some(course.credits),
+            department: Some(course.department),
+            description: Some(course.description),
+            is_active: Some(course.is_active),
+            prerequisites: Some(course.prerequisites),
+            corequisites: Some(course.corequisites),
+        }
+    }
+}
@@ -0,0 +1,93 @@
+use crate::models::student::{validate_level, validate_standing, Student};
+use crate::models::term::Term;
+use crate::schema::{credit_limits, credit_overloads};
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use validator::{Validate, ValidationError};
+
+/// Credit load limits for one student level, and optionally one academic
+/// standing. A row without a standing covers every standing at that level
+/// that has no row of its own.
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Identifiable)]
+#[diesel(table_name = credit_limits)]
+pub struct CreditLimit {
+    pub id: i32,
+    pub level: String,
+    pub academic_standing: Option<String>,
+    pub min_credits: i32,
+    pub max_credits: i32,
+    pub full_time_credits: i32,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, Validate, Insertable, AsChangeset)]
+#[diesel(table_name = credit_limits)]
+#[diesel(treat_none_as_null = true)]
+#[validate(schema(function = "validate_credit_range"))]
+pub struct NewCreditLimit {
+    #[validate(custom = "validate_level")]
+    pub level: String,
+    #[validate(custom = "validate_standing")]
+    pub academic_standing: Option<String>,
+    #[validate(range(min = 0, max = 100))]
+    pub min_credits: i32,
+    #[validate(range(min = 0, max = 100))]
+    pub max_credits: i32,
+    #[validate(range(min = 0, max = 100))]
+    pub full_time_credits: i32,
+}
+
+fn validate_credit_range(limit: &NewCreditLimit) -> Result<(), ValidationError> {
+    if limit.min_credits <= limit.full_time_credits && limit.full_time_credits <= limit.max_credits
+    {
+        return Ok(());
+    }
+    let mut error = ValidationError::new("credit_range");
+    error.message =
+        Some("credits must satisfy min_credits <= full_time_credits <= max_credits".into());
+    Err(error)
+}
+
+impl CreditLimit {
+    /// The limits for a student: the row for their level and standing, else
+    /// the level's default row. `None` when neither exists.
+    pub fn for_student(conn: &mut PgConnection, student: &Student) -> QueryResult<Option<Self>> {
+        credit_limits::table
+            .filter(credit_limits::level.eq(&student.level))
+            .filter(
+                credit_limits::academic_standing
+                    .eq(&student.academic_standing)
+                    .or(credit_limits::academic_standing.is_null()),
+            )
+            .order(credit_limits::academic_standing.asc().nulls_last())
+            .first::<CreditLimit>(conn)
+            .optional()
+    }
+}
+
+/// An advisor-approved maximum above the usual limit for one term.
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Identifiable, Associations)]
+#[diesel(belongs_to(Student))]
+#[diesel(belongs_to(Term))]
+#[diesel(table_name = credit_overloads)]
+pub struct CreditOverload {
+    pub id: i32,
+    pub student_id: i32,
+    pub term_id: i32,
+    pub max_credits: i32,
+    pub reason: String,
+    pub approved_by: Option<i32>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = credit_overloads)]
+pub struct NewCreditOverload {
+    pub student_id: i32,
+    pub term_id: i32,
+    pub max_credits: i32,
+    pub reason: String,
+    pub approved_by: Option<i32>,
+}
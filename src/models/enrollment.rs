@@ -0,0 +1,117 @@
+use crate::models::section::{Section, SectionDetail};
+use crate::models::student::Student;
+use crate::schema::{enrollments, sections, students};
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Identifiable, Associations)]
+#[diesel(belongs_to(Student))]
+#[diesel(belongs_to(Section))]
+#[diesel(table_name = enrollments)]
+pub struct Enrollment {
+    pub id: i32,
+    pub student_id: i32,
+    pub section_id: i32,
+    pub status: String,
+    pub enrolled_at: DateTime<Utc>,
+    pub dropped_at: Option<DateTime<Utc>>,
+    pub created_by: Option<i32>,
+    pub updated_by: Option<i32>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub grade: Option<String>,
+    pub grade_points: Option<f64>,
+    pub credit_earned: bool,
+    pub graded_at: Option<DateTime<Utc>>,
+    pub graded_by: Option<i32>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = enrollments)]
+pub struct NewEnrollment {
+    pub student_id: i32,
+    pub section_id: i32,
+    pub status: String,
+    pub created_by: Option<i32>,
+    pub updated_by: Option<i32>,
+}
+
+impl Enrollment {
+    /// Holds a seat in the section.
+    pub const ENROLLED: &'static str = "enrolled";
+    /// Removed before the add/drop deadline; leaves no mark on the record.
+    pub const DROPPED: &'static str = "dropped";
+    /// Removed after add/drop but before the withdrawal deadline.
+    pub const WITHDRAWN: &'static str = "withdrawn";
+
+    pub fn is_active(&self) -> bool {
+        self.status == Enrollment::ENROLLED
+    }
+}
+
+/// An enrollment together with the section it is for.
+#[derive(Debug, Serialize)]
+pub struct EnrollmentDetail {
+    #[serde(flatten)]
+    pub enrollment: Enrollment,
+    pub section: SectionDetail,
+}
+
+impl EnrollmentDetail {
+    pub fn load(
+        conn: &mut PgConnection,
+        enrollments: Vec<Enrollment>,
+    ) -> QueryResult<Vec<EnrollmentDetail>> {
+        let section_ids: Vec<i32> = enrollments.iter().map(|e| e.section_id).collect();
+        let sections = sections::table
+            .filter(sections::id.eq_any(section_ids))
+            .load::<Section>(conn)?;
+        let details = SectionDetail::load(conn, sections)?;
+
+        Ok(enrollments
+            .into_iter()
+            .filter_map(|enrollment| {
+                let section = details
+                    .iter()
+                    .find(|detail| detail.section.id == enrollment.section_id)?
+                    .clone();
+                Some(EnrollmentDetail {
+                    enrollment,
+                    section,
+                })
+            })
+            .collect())
+    }
+}
+
+/// A section's active enrollment with the student it belongs to.
+#[derive(Debug, Serialize)]
+pub struct RosterEntry {
+    #[serde(flatten)]
+    pub enrollment: Enrollment,
+    pub student_name: String,
+    pub student_email: String,
+}
+
+impl RosterEntry {
+    pub fn load_for_section(
+        conn: &mut PgConnection,
+        section_id: i32,
+    ) -> QueryResult<Vec<RosterEntry>> {
+        Ok(enrollments::table
+            .inner_join(students::table)
+            .filter(enrollments::section_id.eq(section_id))
+            .filter(enrollments::status.eq(Enrollment::ENROLLED))
+            .order(students::name.asc())
+            .select((enrollments::all_columns, students::name, students::email))
+            .load::<(Enrollment, String, String)>(conn)?
+            .into_iter()
+            .map(|(enrollment, student_name, student_email)| RosterEntry {
+                enrollment,
+                student_name,
+                student_email,
+            })
+            .collect())
+    }
+}
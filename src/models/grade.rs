@@ -0,0 +1,95 @@
+use crate::schema::{grade_scale_entries, grade_scales};
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Identifiable)]
+#[diesel(table_name = grade_scales)]
+pub struct GradeScale {
+    pub id: i32,
+    pub name: String,
+    pub description: Option<String>,
+    pub is_default: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Insertable, AsChangeset)]
+#[diesel(table_name = grade_scales)]
+pub struct NewGradeScale {
+    pub name: String,
+    pub description: Option<String>,
+    pub is_default: bool,
+}
+
+/// One grade on a scale. `points` is `None` for grades that do not count
+/// toward GPA, such as pass/fail and incomplete.
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Identifiable, Associations)]
+#[diesel(belongs_to(GradeScale))]
+#[diesel(table_name = grade_scale_entries)]
+pub struct GradeScaleEntry {
+    pub id: i32,
+    pub grade_scale_id: i32,
+    pub grade: String,
+    pub points: Option<f64>,
+    pub earns_credit: bool,
+    pub is_final: bool,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = grade_scale_entries)]
+pub struct NewGradeScaleEntry {
+    pub grade_scale_id: i32,
+    pub grade: String,
+    pub points: Option<f64>,
+    pub earns_credit: bool,
+    pub is_final: bool,
+}
+
+/// A grade scale with its grades, best first.
+#[derive(Debug, Serialize)]
+pub struct GradeScaleDetail {
+    #[serde(flatten)]
+    pub scale: GradeScale,
+    pub entries: Vec<GradeScaleEntry>,
+}
+
+impl GradeScaleDetail {
+    pub fn load(
+        conn: &mut PgConnection,
+        scales: Vec<GradeScale>,
+    ) -> QueryResult<Vec<GradeScaleDetail>> {
+        let entries = GradeScaleEntry::belonging_to(&scales)
+            .order((
+                grade_scale_entries::points.desc().nulls_last(),
+                grade_scale_entries::id.asc(),
+            ))
+            .load::<GradeScaleEntry>(conn)?
+            .grouped_by(&scales);
+
+        Ok(scales
+            .into_iter()
+            .zip(entries)
+            .map(|(scale, entries)| GradeScaleDetail { scale, entries })
+            .collect())
+    }
+
+    /// The scale new grades are posted against.
+    pub fn load_default(conn: &mut PgConnection) -> QueryResult<Option<GradeScaleDetail>> {
+        let scale = grade_scales::table
+            .filter(grade_scales::is_default.eq(true))
+            .first::<GradeScale>(conn)
+            .optional()?;
+
+        match scale {
+            Some(scale) => Ok(GradeScaleDetail::load(conn, vec![scale])?.pop()),
+            None => Ok(None),
+        }
+    }
+
+    pub fn entry(&self, grade: &str) -> Option<&GradeScaleEntry> {
+        self.entries
+            .iter()
+            .find(|entry| entry.grade.eq_ignore_ascii_case(grade.trim()))
+    }
+}
@@ -0,0 +1,119 @@
+use crate::models::permission::Permission;
+use crate::models::student::Student;
+use crate::schema::holds;
+use chrono::{DateTime, NaiveDate, Utc};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// A hold an office has placed on a student. It is in effect from
+/// `starts_on` through `ends_on`, or indefinitely when there is no end date.
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Identifiable, Associations)]
+#[diesel(belongs_to(Student))]
+#[diesel(table_name = holds)]
+pub struct Hold {
+    pub id: i32,
+    pub student_id: i32,
+    pub kind: String,
+    pub reason: String,
+    pub office: String,
+    pub starts_on: NaiveDate,
+    pub ends_on: Option<NaiveDate>,
+    pub blocks_registration: bool,
+    pub blocks_transcript: bool,
+    pub blocks_graduation: bool,
+    pub created_by: Option<i32>,
+    pub updated_by: Option<i32>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = holds)]
+pub struct NewHold {
+    pub student_id: i32,
+    pub kind: String,
+    pub reason: String,
+    pub office: String,
+    pub starts_on: NaiveDate,
+    pub ends_on: Option<NaiveDate>,
+    pub blocks_registration: bool,
+    pub blocks_transcript: bool,
+    pub blocks_graduation: bool,
+    pub created_by: Option<i32>,
+    pub updated_by: Option<i32>,
+}
+
+#[derive(Debug, AsChangeset)]
+#[diesel(table_name = holds)]
+#[diesel(treat_none_as_null = true)]
+pub struct UpdateHold {
+    pub kind: String,
+    pub reason: String,
+    pub office: String,
+    pub starts_on: NaiveDate,
+    pub ends_on: Option<NaiveDate>,
+    pub blocks_registration: bool,
+    pub blocks_transcript: bool,
+    pub blocks_graduation: bool,
+    pub updated_by: Option<i32>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Actions a hold can block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HoldAction {
+    Registration,
+    Transcript,
+    Graduation,
+}
+
+impl Hold {
+    pub const FINANCIAL: &'static str = "financial";
+    pub const ADVISING: &'static str = "advising";
+    pub const DISCIPLINARY: &'static str = "disciplinary";
+    pub const IMMUNIZATION: &'static str = "immunization";
+    pub const KINDS: [&'static str; 4] = [
+        Hold::FINANCIAL,
+        Hold::ADVISING,
+        Hold::DISCIPLINARY,
+        Hold::IMMUNIZATION,
+    ];
+
+    /// The permission needed to place and lift holds of `kind`. Each office
+    /// is granted the one for its own kind.
+    pub fn permission(kind: &str) -> Option<&'static str> {
+        match kind {
+            Hold::FINANCIAL => Some(Permission::HOLDS_FINANCIAL),
+            Hold::ADVISING => Some(Permission::HOLDS_ADVISING),
+            Hold::DISCIPLINARY => Some(Permission::HOLDS_DISCIPLINARY),
+            Hold::IMMUNIZATION => Some(Permission::HOLDS_IMMUNIZATION),
+            _ => None,
+        }
+    }
+
+    pub fn is_active_on(&self, date: NaiveDate) -> bool {
+        self.starts_on <= date && self.ends_on.is_none_or(|ends_on| date <= ends_on)
+    }
+
+    /// Holds on the student in effect on `date` that block `action`.
+    pub fn active_for(
+        conn: &mut PgConnection,
+        student_id: i32,
+        action: HoldAction,
+        date: NaiveDate,
+    ) -> QueryResult<Vec<Hold>> {
+        let query = holds::table
+            .filter(holds::student_id.eq(student_id))
+            .filter(holds::starts_on.le(date))
+            .filter(holds::ends_on.is_null().or(holds::ends_on.ge(date)))
+            .into_boxed();
+        let query = match action {
+            HoldAction::Registration => query.filter(holds::blocks_registration),
+            HoldAction::Transcript => query.filter(holds::blocks_transcript),
+            HoldAction::Graduation => query.filter(holds::blocks_graduation),
+        };
+        query
+            .order((holds::starts_on.asc(), holds::id.asc()))
+            .load::<Hold>(conn)
+    }
+}
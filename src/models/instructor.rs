@@ -0,0 +1,94 @@
+use crate::models::user::User;
+use crate::schema::{instructors, sections, users};
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// A faculty profile. Sections name the instructor by `user_id`.
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Identifiable, Associations)]
+#[diesel(belongs_to(User))]
+#[diesel(table_name = instructors)]
+pub struct Instructor {
+    pub id: i32,
+    pub user_id: i32,
+    pub department: String,
+    pub title: String,
+    pub office: Option<String>,
+    pub max_sections: i32,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Insertable, AsChangeset)]
+#[diesel(table_name = instructors)]
+#[diesel(treat_none_as_null = true)]
+pub struct NewInstructor {
+    pub user_id: i32,
+    pub department: String,
+    pub title: String,
+    pub office: Option<String>,
+    pub max_sections: i32,
+}
+
+/// An instructor with the name and email of their user account.
+#[derive(Debug, Serialize)]
+pub struct InstructorDetail {
+    #[serde(flatten)]
+    pub instructor: Instructor,
+    pub first_name: Option<String>,
+    pub last_name: Option<String>,
+    pub email: String,
+}
+
+impl Instructor {
+    pub fn for_user(conn: &mut PgConnection, user_id: i32) -> QueryResult<Option<Instructor>> {
+        instructors::table
+            .filter(instructors::user_id.eq(user_id))
+            .first::<Instructor>(conn)
+            .optional()
+    }
+
+    /// Sections the instructor teaches in the term, other than `excluding`.
+    pub fn sections_in_term(
+        &self,
+        conn: &mut PgConnection,
+        term_id: i32,
+        excluding: Option<i32>,
+    ) -> QueryResult<i64> {
+        sections::table
+            .filter(sections::instructor_id.eq(self.user_id))
+            .filter(sections::term_id.eq(term_id))
+            .filter(sections::id.ne(excluding.unwrap_or(0)))
+            .count()
+            .get_result(conn)
+    }
+}
+
+impl InstructorDetail {
+    pub fn load(
+        conn: &mut PgConnection,
+        instructors: Vec<Instructor>,
+    ) -> QueryResult<Vec<InstructorDetail>> {
+        let user_ids: Vec<i32> = instructors.iter().map(|i| i.user_id).collect();
+        let accounts: Vec<(i32, Option<String>, Option<String>, String)> = users::table
+            .filter(users::id.eq_any(user_ids))
+            .select((users::id, users::first_name, users::last_name, users::email))
+            .load(conn)?;
+
+        Ok(instructors
+            .into_iter()
+            .filter_map(|instructor| {
+                let (_, first_name, last_name, email) = accounts
+                    .iter()
+                    .find(|(id, ..)| *id == instructor.user_id)?
+                    .clone();
+                Some(InstructorDetail {
+                    instructor,
+                    first_name,
+                    last_name,
+                    email,
+                })
+            })
+            .collect())
+    }
+}
@@ -0,0 +1,68 @@
+use crate::models::section::Section;
+use crate::models::student::Student;
+use crate::models::term::Term;
+use crate::schema::{lottery_requests, lottery_runs};
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// A student's ranked choice of a lottery section; `rank` 1 is their first
+/// choice. `status` records what the lottery run did with it.
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Identifiable, Associations)]
+#[diesel(belongs_to(Student))]
+#[diesel(belongs_to(Section))]
+#[diesel(table_name = lottery_requests)]
+pub struct LotteryRequest {
+    pub id: i32,
+    pub student_id: i32,
+    pub section_id: i32,
+    pub rank: i32,
+    pub status: String,
+    pub lottery_run_id: Option<i32>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = lottery_requests)]
+pub struct NewLotteryRequest {
+    pub student_id: i32,
+    pub section_id: i32,
+    pub rank: i32,
+}
+
+impl LotteryRequest {
+    /// Waiting for the lottery to run.
+    pub const PENDING: &'static str = "pending";
+    /// Won a seat and was enrolled.
+    pub const ENROLLED: &'static str = "enrolled";
+    /// The section filled up first; the student joined its waitlist.
+    pub const WAITLISTED: &'static str = "waitlisted";
+    /// Not allocated, because the student was ineligible or won another
+    /// section of the course.
+    pub const UNSUCCESSFUL: &'static str = "unsuccessful";
+}
+
+/// A completed lottery with the inputs needed to reproduce it and the
+/// resulting report.
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Identifiable, Associations)]
+#[diesel(belongs_to(Term))]
+#[diesel(table_name = lottery_runs)]
+pub struct LotteryRun {
+    pub id: i32,
+    pub term_id: i32,
+    pub seed: i64,
+    pub weights: serde_json::Value,
+    pub report: serde_json::Value,
+    pub run_by: Option<i32>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = lottery_runs)]
+pub struct NewLotteryRun {
+    pub term_id: i32,
+    pub seed: i64,
+    pub weights: serde_json::Value,
+    pub report: serde_json::Value,
+    pub run_by: Option<i32>,
+}
@@ -1,9 +1,38 @@
+pub mod appointment;
 pub mod audit;
+pub mod cart;
+pub mod course;
+pub mod credit_load;
+pub mod enrollment;
+pub mod grade;
+pub mod hold;
+pub mod instructor;
+pub mod lottery;
+pub mod permission;
+pub mod program;
+pub mod requisite;
 pub mod role;
+pub mod room;
+pub mod section;
 pub mod student;
+pub mod term;
 pub mod user;
+pub mod waitlist;
 
-pub use student::{NewStudent, Student};
+pub use audit::AuditContext;
+pub use course::{Course, NewCourse, UpdateCourse};
+pub use enrollment::{Enrollment, EnrollmentDetail, NewEnrollment, RosterEntry};
+pub use grade::{GradeScale, GradeScaleDetail, NewGradeScale, NewGradeScaleEntry};
+pub use hold::{Hold, HoldAction};
+pub use program::{
+    DeclaredProgram, NewProgram, NewProgramRequirement, Program, ProgramDetail, ProgramRequirement,
+    RequirementDetail,
+};
+pub use requisite::Requirement;
+pub use section::{NewSection, NewSectionMeeting, Section, SectionDetail};
+pub use student::{NewStudent, Student, StudentDetail, UpdateStudent};
+pub use term::{NewTerm, Term};
+pub use waitlist::{NewWaitlistEntry, WaitlistEntry, WaitlistPosition};
 // Keep these imports commented out until they're used
 // pub use user::{User, NewUser, UserToken};
 // pub use role::{Role, NewRole};
@@ -0,0 +1,97 @@
+use crate::models::role::Role;
+use crate::schema::{permissions, role_permissions, roles};
+use chrono::{DateTime, Utc};
+use diesel::dsl::exists;
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Identifiable)]
+#[diesel(table_name = permissions)]
+pub struct Permission {
+    pub id: i32,
+    pub name: String,
+    pub description: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = role_permissions)]
+pub struct NewRolePermission {
+    pub role_id: i32,
+    pub permission_id: i32,
+}
+
+/// A role with the names of the permissions granted to it.
+#[derive(Debug, Serialize)]
+pub struct RoleDetail {
+    #[serde(flatten)]
+    pub role: Role,
+    pub permissions: Vec<String>,
+}
+
+impl Permission {
+    pub const STUDENTS_READ: &'static str = "students.read";
+    pub const STUDENTS_READ_ALL: &'static str = "students.read_all";
+    pub const STUDENTS_WRITE: &'static str = "students.write";
+    pub const GRADES_POST: &'static str = "grades.post";
+    pub const GRADES_MANAGE: &'static str = "grades.manage";
+    pub const GRADE_SCALES_MANAGE: &'static str = "grade_scales.manage";
+    pub const AUDIT_READ: &'static str = "audit.read";
+    pub const COURSES_MANAGE: &'static str = "courses.manage";
+    pub const TERMS_MANAGE: &'static str = "terms.manage";
+    pub const SECTIONS_MANAGE: &'static str = "sections.manage";
+    pub const PROGRAMS_MANAGE: &'static str = "programs.manage";
+    pub const CREDIT_LIMITS_MANAGE: &'static str = "credit_limits.manage";
+    pub const OVERLOADS_APPROVE: &'static str = "overloads.approve";
+    pub const ROOMS_MANAGE: &'static str = "rooms.manage";
+    pub const INSTRUCTORS_MANAGE: &'static str = "instructors.manage";
+    pub const REGISTRATION_MANAGE: &'static str = "registration.manage";
+    pub const ENROLLMENTS_WRITE: &'static str = "enrollments.write";
+    pub const ENROLLMENTS_OVERRIDE: &'static str = "enrollments.override";
+    pub const ROLES_MANAGE: &'static str = "roles.manage";
+    pub const SESSIONS_MANAGE: &'static str = "sessions.manage";
+    pub const HOLDS_FINANCIAL: &'static str = "holds.financial";
+    pub const HOLDS_ADVISING: &'static str = "holds.advising";
+    pub const HOLDS_DISCIPLINARY: &'static str = "holds.disciplinary";
+    pub const HOLDS_IMMUNIZATION: &'static str = "holds.immunization";
+
+    /// Whether the role with this name has been granted the permission.
+    pub fn granted(
+        conn: &mut PgConnection,
+        role_name: &str,
+        permission: &str,
+    ) -> QueryResult<bool> {
+        diesel::select(exists(
+            role_permissions::table
+                .inner_join(roles::table)
+                .inner_join(permissions::table)
+                .filter(roles::name.eq(role_name))
+                .filter(permissions::name.eq(permission)),
+        ))
+        .get_result(conn)
+    }
+}
+
+impl RoleDetail {
+    pub fn load(conn: &mut PgConnection, roles: Vec<Role>) -> QueryResult<Vec<RoleDetail>> {
+        let role_ids: Vec<i32> = roles.iter().map(|role| role.id).collect();
+        let grants: Vec<(i32, String)> = role_permissions::table
+            .inner_join(permissions::table)
+            .filter(role_permissions::role_id.eq_any(role_ids))
+            .order(permissions::name.asc())
+            .select((role_permissions::role_id, permissions::name))
+            .load(conn)?;
+
+        Ok(roles
+            .into_iter()
+            .map(|role| RoleDetail {
+                permissions: grants
+                    .iter()
+                    .filter(|(role_id, _)| *role_id == role.id)
+                    .map(|(_, name)| name.clone())
+                    .collect(),
+                role,
+            })
+            .collect())
+    }
+}
@@ -0,0 +1,201 @@
+use std::collections::HashMap;
+
+use crate::models::student::Student;
+use crate::schema::{
+    courses, program_requirement_courses, program_requirements, programs, student_programs,
+};
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Identifiable)]
+#[diesel(table_name = programs)]
+pub struct Program {
+    pub id: i32,
+    pub code: String,
+    pub name: String,
+    pub kind: String,
+    pub department: String,
+    pub description: Option<String>,
+    pub is_active: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Insertable, AsChangeset)]
+#[diesel(table_name = programs)]
+pub struct NewProgram {
+    pub code: String,
+    pub name: String,
+    pub kind: String,
+    pub department: String,
+    pub description: Option<String>,
+    pub is_active: bool,
+}
+
+impl Program {
+    pub const MAJOR: &'static str = "major";
+    pub const MINOR: &'static str = "minor";
+    pub const KINDS: [&'static str; 2] = [Program::MAJOR, Program::MINOR];
+}
+
+/// One block of a program's requirements, audited in `position` order.
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Identifiable, Associations)]
+#[diesel(belongs_to(Program))]
+#[diesel(table_name = program_requirements)]
+pub struct ProgramRequirement {
+    pub id: i32,
+    pub program_id: i32,
+    pub position: i32,
+    pub name: String,
+    pub kind: String,
+    pub min_credits: Option<i32>,
+    pub min_gpa: Option<f64>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = program_requirements)]
+pub struct NewProgramRequirement {
+    pub program_id: i32,
+    pub position: i32,
+    pub name: String,
+    pub kind: String,
+    pub min_credits: Option<i32>,
+    pub min_gpa: Option<f64>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = program_requirement_courses)]
+pub struct NewProgramRequirementCourse {
+    pub requirement_id: i32,
+    pub course_id: i32,
+}
+
+impl ProgramRequirement {
+    /// Every listed course must be passed.
+    pub const REQUIRED_COURSES: &'static str = "required_courses";
+    /// At least `min_credits` from the listed courses.
+    pub const CHOOSE_CREDITS: &'static str = "choose_credits";
+    /// GPA of at least `min_gpa`, over the listed courses or all coursework.
+    pub const MIN_GPA: &'static str = "min_gpa";
+    /// At least `min_credits` earned at this institution, from the listed
+    /// courses or all coursework.
+    pub const RESIDENCY_CREDITS: &'static str = "residency_credits";
+    pub const KINDS: [&'static str; 4] = [
+        ProgramRequirement::REQUIRED_COURSES,
+        ProgramRequirement::CHOOSE_CREDITS,
+        ProgramRequirement::MIN_GPA,
+        ProgramRequirement::RESIDENCY_CREDITS,
+    ];
+}
+
+/// A requirement block with the codes of the courses it draws on.
+#[derive(Debug, Serialize)]
+pub struct RequirementDetail {
+    #[serde(flatten)]
+    pub requirement: ProgramRequirement,
+    pub courses: Vec<String>,
+}
+
+/// A program with its requirement blocks in order.
+#[derive(Debug, Serialize)]
+pub struct ProgramDetail {
+    #[serde(flatten)]
+    pub program: Program,
+    pub requirements: Vec<RequirementDetail>,
+}
+
+impl ProgramDetail {
+    pub fn load(
+        conn: &mut PgConnection,
+        programs: Vec<Program>,
+    ) -> QueryResult<Vec<ProgramDetail>> {
+        let requirements = ProgramRequirement::belonging_to(&programs)
+            .order((
+                program_requirements::program_id.asc(),
+                program_requirements::position.asc(),
+            ))
+            .load::<ProgramRequirement>(conn)?;
+
+        let requirement_ids: Vec<i32> = requirements
+            .iter()
+            .map(|requirement| requirement.id)
+            .collect();
+        let mut codes: HashMap<i32, Vec<String>> = HashMap::new();
+        for (requirement_id, code) in program_requirement_courses::table
+            .inner_join(courses::table)
+            .filter(program_requirement_courses::requirement_id.eq_any(&requirement_ids))
+            .order(courses::code.asc())
+            .select((program_requirement_courses::requirement_id, courses::code))
+            .load::<(i32, String)>(conn)?
+        {
+            codes.entry(requirement_id).or_default().push(code);
+        }
+
+        let requirements = requirements.grouped_by(&programs);
+        Ok(programs
+            .into_iter()
+            .zip(requirements)
+            .map(|(program, requirements)| ProgramDetail {
+                program,
+                requirements: requirements
+                    .into_iter()
+                    .map(|requirement| RequirementDetail {
+                        courses: codes.remove(&requirement.id).unwrap_or_default(),
+                        requirement,
+                    })
+                    .collect(),
+            })
+            .collect())
+    }
+}
+
+/// A student's declaration of a major or minor.
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Identifiable, Associations)]
+#[diesel(belongs_to(Student))]
+#[diesel(belongs_to(Program))]
+#[diesel(table_name = student_programs)]
+pub struct StudentProgram {
+    pub id: i32,
+    pub student_id: i32,
+    pub program_id: i32,
+    pub declared_at: DateTime<Utc>,
+    pub created_by: Option<i32>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = student_programs)]
+pub struct NewStudentProgram {
+    pub student_id: i32,
+    pub program_id: i32,
+    pub created_by: Option<i32>,
+}
+
+/// A declared program as shown on a student's record.
+#[derive(Debug, Serialize)]
+pub struct DeclaredProgram {
+    #[serde(flatten)]
+    pub declaration: StudentProgram,
+    pub program: Program,
+}
+
+impl DeclaredProgram {
+    /// Majors before minors, then in the order they were declared.
+    pub fn load_for_student(
+        conn: &mut PgConnection,
+        student_id: i32,
+    ) -> QueryResult<Vec<DeclaredProgram>> {
+        Ok(student_programs::table
+            .inner_join(programs::table)
+            .filter(student_programs::student_id.eq(student_id))
+            .order((programs::kind.asc(), student_programs::declared_at.asc()))
+            .select((student_programs::all_columns, programs::all_columns))
+            .load::<(StudentProgram, Program)>(conn)?
+            .into_iter()
+            .map(|(declaration, program)| DeclaredProgram {
+                declaration,
+                program,
+            })
+            .collect())
+    }
+}
@@ -0,0 +1,370 @@
+use std::fmt;
+
+use thiserror::Error;
+
+use crate::models::course::normalize_code;
+
+/// A parsed prerequisite or corequisite expression such as
+/// `(CS101 AND MATH110) OR CS100 with grade >= C`. AND binds tighter than OR,
+/// and a grade condition applies to the course directly before it. Minimum
+/// grades are compared by points on the default grade scale.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Requirement {
+    Course {
+        code: String,
+        min_grade: Option<String>,
+    },
+    All(Vec<Requirement>),
+    Any(Vec<Requirement>),
+}
+
+#[derive(Debug, Error)]
+#[error("{message} at position {position}")]
+pub struct ParseError {
+    pub position: usize,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Open,
+    Close,
+    AtLeast,
+    Word(String),
+}
+
+const KEYWORDS: [&str; 4] = ["AND", "OR", "WITH", "GRADE"];
+
+fn is_keyword(word: &str) -> bool {
+    KEYWORDS
+        .iter()
+        .any(|keyword| keyword.eq_ignore_ascii_case(word))
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | '+')
+}
+
+fn tokenize(input: &str) -> Result<Vec<(usize, Token)>, ParseError> {
+    let mut tokens = Vec::new();
+    let mut chars = input.char_indices().peekable();
+
+    while let Some(&(position, c)) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '(' => {
+                chars.next();
+                tokens.push((position, Token::Open));
+            }
+            ')' => {
+                chars.next();
+                tokens.push((position, Token::Close));
+            }
+            '>' => {
+                chars.next();
+                if chars.next_if(|&(_, c)| c == '=').is_none() {
+                    return Err(ParseError {
+                        position,
+                        message: "expected '>='".to_string(),
+                    });
+                }
+                tokens.push((position, Token::AtLeast));
+            }
+            c if is_word_char(c) => {
+                let mut word = String::new();
+                while let Some((_, c)) = chars.next_if(|&(_, c)| is_word_char(c)) {
+                    word.push(c);
+                }
+                tokens.push((position, Token::Word(word)));
+            }
+            other => {
+                return Err(ParseError {
+                    position,
+                    message: format!("unexpected character '{}'", other),
+                })
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<(usize, Token)>,
+    pos: usize,
+    end: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|(_, token)| token)
+    }
+
+    fn error(&self, message: &str) -> ParseError {
+        ParseError {
+            position: self
+                .tokens
+                .get(self.pos)
+                .map_or(self.end, |(position, _)| *position),
+            message: message.to_string(),
+        }
+    }
+
+    fn keyword(&mut self, keyword: &str) -> bool {
+        let matched =
+            matches!(self.peek(), Some(Token::Word(word)) if word.eq_ignore_ascii_case(keyword));
+        if matched {
+            self.pos += 1;
+        }
+        matched
+    }
+
+    fn expect(&mut self, token: Token, message: &str) -> Result<(), ParseError> {
+        if self.peek() != Some(&token) {
+            return Err(self.error(message));
+        }
+        self.pos += 1;
+        Ok(())
+    }
+
+    fn any(&mut self) -> Result<Requirement, ParseError> {
+        let mut options = vec![self.all()?];
+        while self.keyword("OR") {
+            options.push(self.all()?);
+        }
+        Ok(Requirement::group(options, false))
+    }
+
+    fn all(&mut self) -> Result<Requirement, ParseError> {
+        let mut parts = vec![self.term()?];
+        while self.keyword("AND") {
+            parts.push(self.term()?);
+        }
+        Ok(Requirement::group(parts, true))
+    }
+
+    fn term(&mut self) -> Result<Requirement, ParseError> {
+        match self.peek().cloned() {
+            Some(Token::Open) => {
+                self.pos += 1;
+                let inner = self.any()?;
+                self.expect(Token::Close, "expected ')'")?;
+                Ok(inner)
+            }
+            Some(Token::Word(word)) if !is_keyword(&word) => {
+                self.pos += 1;
+                let min_grade = if self.keyword("WITH") {
+                    if !self.keyword("GRADE") {
+                        return Err(self.error("expected 'grade'"));
+                    }
+                    self.expect(Token::AtLeast, "expected '>='")?;
+                    match self.peek().cloned() {
+                        Some(Token::Word(grade)) if !is_keyword(&grade) => {
+                            self.pos += 1;
+                            Some(grade.to_uppercase())
+                        }
+                        _ => return Err(self.error("expected a grade")),
+                    }
+                } else {
+                    None
+                };
+                Ok(Requirement::Course {
+                    code: normalize_code(&word),
+                    min_grade,
+                })
+            }
+            _ => Err(self.error("expected a course code or '('")),
+        }
+    }
+}
+
+impl Requirement {
+    pub fn parse(input: &str) -> Result<Requirement, ParseError> {
+        let mut parser = Parser {
+            tokens: tokenize(input)?,
+            pos: 0,
+            end: input.len(),
+        };
+        let requirement = parser.any()?;
+        if parser.pos < parser.tokens.len() {
+            return Err(parser.error("unexpected input"));
+        }
+        Ok(requirement)
+    }
+
+    /// The grades named in `with grade >= X` conditions, in order.
+    pub fn min_grades(&self) -> Vec<&str> {
+        match self {
+            Requirement::Course { min_grade, .. } => min_grade.as_deref().into_iter().collect(),
+            Requirement::All(items) | Requirement::Any(items) => {
+                items.iter().flat_map(Requirement::min_grades).collect()
+            }
+        }
+    }
+
+    /// Builds an AND (`all`) or OR group, unwrapping single items and merging
+    /// nested groups of the same kind.
+    fn group(items: Vec<Requirement>, all: bool) -> Requirement {
+        let mut items: Vec<Requirement> = items
+            .into_iter()
+            .flat_map(|item| match item {
+                Requirement::All(inner) if all => inner,
+                Requirement::Any(inner) if !all => inner,
+                other => vec![other],
+            })
+            .collect();
+
+        if items.len() == 1 {
+            items.remove(0)
+        } else if all {
+            Requirement::All(items)
+        } else {
+            Requirement::Any(items)
+        }
+    }
+}
+
+fn write_group(f: &mut fmt::Formatter<'_>, items: &[Requirement], separator: &str) -> fmt::Result {
+    for (i, item) in items.iter().enumerate() {
+        if i > 0 {
+            f.write_str(separator)?;
+        }
+        match item {
+            Requirement::Course { .. } => write!(f, "{}", item)?,
+            _ => write!(f, "({})", item)?,
+        }
+    }
+    Ok(())
+}
+
+/// Canonical form: upper-case codes and keywords, groups parenthesised only
+/// where needed.
+impl fmt::Display for Requirement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Requirement::Course {
+                code,
+                min_grade: Some(grade),
+            } => write!(f, "{} with grade >= {}", code, grade),
+            Requirement::Course {
+                code,
+                min_grade: None,
+            } => f.write_str(code),
+            Requirement::All(items) => write_group(f, items, " AND "),
+            Requirement::Any(items) => write_group(f, items, " OR "),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn course(code: &str) -> Requirement {
+        Requirement::Course {
+            code: code.to_string(),
+            min_grade: None,
+        }
+    }
+
+    fn graded(code: &str, grade: &str) -> Requirement {
+        Requirement::Course {
+            code: code.to_string(),
+            min_grade: Some(grade.to_string()),
+        }
+    }
+
+    #[test]
+    fn and_binds_tighter_than_or() {
+        assert_eq!(
+            Requirement::parse("CS101 AND MATH110 OR CS100").unwrap(),
+            Requirement::Any(vec![
+                Requirement::All(vec![course("CS101"), course("MATH110")]),
+                course("CS100"),
+            ])
+        );
+        assert_eq!(
+            Requirement::parse("CS100 OR CS101 AND MATH110").unwrap(),
+            Requirement::Any(vec![
+                course("CS100"),
+                Requirement::All(vec![course("CS101"), course("MATH110")]),
+            ])
+        );
+    }
+
+    #[test]
+    fn parentheses_override_precedence() {
+        assert_eq!(
+            Requirement::parse("CS101 AND (MATH110 OR CS100)").unwrap(),
+            Requirement::All(vec![
+                course("CS101"),
+                Requirement::Any(vec![course("MATH110"), course("CS100")]),
+            ])
+        );
+    }
+
+    #[test]
+    fn nested_groups_of_the_same_kind_are_merged() {
+        assert_eq!(
+            Requirement::parse("((CS101)) AND (MATH110 AND CS100)").unwrap(),
+            Requirement::All(vec![course("CS101"), course("MATH110"), course("CS100")])
+        );
+    }
+
+    #[test]
+    fn grade_threshold_applies_to_the_preceding_course() {
+        assert_eq!(
+            Requirement::parse("cs101 with grade >= c+ or CS100").unwrap(),
+            Requirement::Any(vec![graded("CS101", "C+"), course("CS100")])
+        );
+        assert_eq!(
+            Requirement::parse("(CS101 AND MATH110 WITH GRADE >= B) OR CS100 with grade >= A")
+                .unwrap()
+                .min_grades(),
+            vec!["B", "A"]
+        );
+    }
+
+    #[test]
+    fn displays_in_canonical_form() {
+        let requirement =
+            Requirement::parse("(cs101 and math110 with grade>=b) or (cs100 or cs099)").unwrap();
+        assert_eq!(
+            requirement.to_string(),
+            "(CS101 AND MATH110 with grade >= B) OR CS100 OR CS099"
+        );
+        assert_eq!(
+            Requirement::parse(&requirement.to_string()).unwrap(),
+            requirement
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_input() {
+        let cases = [
+            ("", 0, "expected a course code or '('"),
+            ("CS101 AND", 9, "expected a course code or '('"),
+            ("AND CS101", 0, "expected a course code or '('"),
+            ("(CS101 OR CS100", 15, "expected ')'"),
+            ("CS101)", 5, "unexpected input"),
+            ("CS101 CS100", 6, "unexpected input"),
+            ("CS101 with >= C", 11, "expected 'grade'"),
+            ("CS101 with grade C", 17, "expected '>='"),
+            ("CS101 with grade >= ", 20, "expected a grade"),
+            ("CS101 with grade > C", 17, "expected '>='"),
+            ("CS101 & CS100", 6, "unexpected character '&'"),
+        ];
+
+        for (input, position, message) in cases {
+            let err = Requirement::parse(input).unwrap_err();
+            assert_eq!(
+                (err.position, err.message.as_str()),
+                (position, message),
+                "{:?}",
+                input
+            );
+        }
+    }
+}
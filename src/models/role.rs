@@ -14,8 +14,9 @@ pub struct Role {
     pub updated_at: DateTime<Utc>,
 }
 
-#[derive(Debug, Deserialize, Validate, Insertable)]
+#[derive(Debug, Deserialize, Validate, Insertable, AsChangeset)]
 #[diesel(table_name = roles)]
+#[diesel(treat_none_as_null = true)]
 pub struct NewRole {
     #[validate(length(min = 1, max = 50))]
     pub name: String,
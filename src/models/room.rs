@@ -0,0 +1,91 @@
+use crate::schema::{buildings, room_features, rooms};
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Identifiable)]
+#[diesel(table_name = buildings)]
+pub struct Building {
+    pub id: i32,
+    pub code: String,
+    pub name: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Insertable, AsChangeset)]
+#[diesel(table_name = buildings)]
+pub struct NewBuilding {
+    pub code: String,
+    pub name: String,
+}
+
+/// A bookable room. Sections meet in it through `sections.room_id`.
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Identifiable, Associations)]
+#[diesel(belongs_to(Building))]
+#[diesel(table_name = rooms)]
+pub struct Room {
+    pub id: i32,
+    pub building_id: i32,
+    pub room_number: String,
+    pub capacity: i32,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Insertable, AsChangeset)]
+#[diesel(table_name = rooms)]
+pub struct NewRoom {
+    pub building_id: i32,
+    pub room_number: String,
+    pub capacity: i32,
+}
+
+#[derive(Debug, Clone, Queryable, Insertable, Identifiable, Associations)]
+#[diesel(belongs_to(Room))]
+#[diesel(table_name = room_features)]
+#[diesel(primary_key(room_id, feature))]
+pub struct RoomFeature {
+    pub room_id: i32,
+    pub feature: String,
+}
+
+/// A room as returned by the API: the room, its building and its features.
+#[derive(Debug, Clone, Serialize)]
+pub struct RoomDetail {
+    #[serde(flatten)]
+    pub room: Room,
+    pub building_code: String,
+    pub building_name: String,
+    pub features: Vec<String>,
+}
+
+impl RoomDetail {
+    pub fn load(conn: &mut PgConnection, rooms: Vec<Room>) -> QueryResult<Vec<RoomDetail>> {
+        let features = RoomFeature::belonging_to(&rooms)
+            .order(room_features::feature.asc())
+            .load::<RoomFeature>(conn)?
+            .grouped_by(&rooms);
+
+        let building_ids: Vec<i32> = rooms.iter().map(|room| room.building_id).collect();
+        let buildings = buildings::table
+            .filter(buildings::id.eq_any(building_ids))
+            .load::<Building>(conn)?;
+
+        Ok(rooms
+            .into_iter()
+            .zip(features)
+            .filter_map(|(room, features)| {
+                let building = buildings
+                    .iter()
+                    .find(|building| building.id == room.building_id)?;
+                Some(RoomDetail {
+                    building_code: building.code.clone(),
+                    building_name: building.name.clone(),
+                    features: features.into_iter().map(|f| f.feature).collect(),
+                    room,
+                })
+            })
+            .collect())
+    }
+}
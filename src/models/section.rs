@@ -0,0 +1,164 @@
+use chrono::{DateTime, NaiveTime, Utc};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use crate::models::course::Course;
+use crate::models::term::Term;
+use crate::schema::{courses, section_meetings, sections};
+
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Identifiable, Associations)]
+#[diesel(belongs_to(Term))]
+#[diesel(belongs_to(Course))]
+#[diesel(table_name = sections)]
+pub struct Section {
+    pub id: i32,
+    pub term_id: i32,
+    pub course_id: i32,
+    pub section_number: String,
+    pub capacity: i32,
+    pub instructor_id: Option<i32>,
+    pub room: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub enrolled_count: i32,
+    pub reserved_count: i32,
+    pub lottery: bool,
+    pub room_id: Option<i32>,
+}
+
+#[derive(Debug, Insertable, AsChangeset)]
+#[diesel(table_name = sections)]
+pub struct NewSection {
+    pub term_id: i32,
+    pub course_id: i32,
+    pub section_number: String,
+    pub capacity: i32,
+    pub instructor_id: Option<i32>,
+    pub room: Option<String>,
+    pub lottery: bool,
+    pub room_id: Option<i32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Identifiable, Associations)]
+#[diesel(belongs_to(Section))]
+#[diesel(table_name = section_meetings)]
+pub struct SectionMeeting {
+    pub id: i32,
+    pub section_id: i32,
+    pub day_of_week: i16,
+    pub start_time: NaiveTime,
+    pub end_time: NaiveTime,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = section_meetings)]
+pub struct NewSectionMeeting {
+    pub section_id: i32,
+    pub day_of_week: i16,
+    pub start_time: NaiveTime,
+    pub end_time: NaiveTime,
+}
+
+impl SectionMeeting {
+    /// Whether the two meetings share any time on the same weekday. Meetings
+    /// that are back to back do not overlap.
+    pub fn overlaps(&self, other: &SectionMeeting) -> bool {
+        self.day_of_week == other.day_of_week
+            && self.start_time < other.end_time
+            && other.start_time < self.end_time
+    }
+}
+
+impl Section {
+    pub fn seats_available(&self) -> i32 {
+        (self.capacity - self.enrolled_count - self.reserved_count).max(0)
+    }
+}
+
+/// A section as returned by the API: the offering, its course code and its
+/// weekly meetings.
+#[derive(Debug, Clone, Serialize)]
+pub struct SectionDetail {
+    #[serde(flatten)]
+    pub section: Section,
+    pub seats_available: i32,
+    pub course_code: String,
+    pub meetings: Vec<SectionMeeting>,
+}
+
+impl SectionDetail {
+    pub fn load(conn: &mut PgConnection, sections: Vec<Section>) -> QueryResult<Vec<SectionDetail>> {
+        let meetings = SectionMeeting::belonging_to(&sections)
+            .order((section_meetings::day_of_week.asc(), section_meetings::start_time.asc()))
+            .load::<SectionMeeting>(conn)?
+            .grouped_by(&sections);
+
+        let course_ids: Vec<i32> = sections.iter().map(|section| section.course_id).collect();
+        let course_codes: Vec<(i32, String)> = courses::table
+            .filter(courses::id.eq_any(course_ids))
+            .select((courses::id, courses::code))
+            .load(conn)?;
+
+        Ok(sections
+            .into_iter()
+            .zip(meetings)
+            .map(|(section, meetings)| {
+                let course_code = course_codes
+                    .iter()
+                    .find(|(id, _)| *id == section.course_id)
+                    .map(|(_, code)| code.clone())
+                    .unwrap_or_default();
+
+                SectionDetail {
+                    seats_available: section.seats_available(),
+                    section,
+                    course_code,
+                    meetings,
+                }
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn meeting(day_of_week: i16, start: &str, end: &str) -> SectionMeeting {
+        SectionMeeting {
+            id: 0,
+            section_id: 0,
+            day_of_week,
+            start_time: NaiveTime::parse_from_str(start, "%H:%M").unwrap(),
+            end_time: NaiveTime::parse_from_str(end, "%H:%M").unwrap(),
+        }
+    }
+
+    #[test]
+    fn meetings_sharing_time_on_the_same_day_overlap() {
+        let lecture = meeting(1, "09:00", "10:30");
+        assert!(lecture.overlaps(&meeting(1, "10:00", "11:00")));
+        assert!(lecture.overlaps(&meeting(1, "08:00", "09:30")));
+        assert!(lecture.overlaps(&meeting(1, "09:15", "09:45")));
+        assert!(lecture.overlaps(&meeting(1, "08:00", "12:00")));
+        assert!(lecture.overlaps(&lecture));
+    }
+
+    #[test]
+    fn overlap_is_symmetric() {
+        let a = meeting(3, "13:00", "14:00");
+        let b = meeting(3, "13:30", "15:00");
+        assert!(a.overlaps(&b) && b.overlaps(&a));
+    }
+
+    #[test]
+    fn back_to_back_meetings_do_not_overlap() {
+        let lecture = meeting(2, "09:00", "10:00");
+        assert!(!lecture.overlaps(&meeting(2, "10:00", "11:00")));
+        assert!(!lecture.overlaps(&meeting(2, "08:00", "09:00")));
+    }
+
+    #[test]
+    fn meetings_on_different_days_do_not_overlap() {
+        assert!(!meeting(1, "09:00", "10:00").overlaps(&meeting(2, "09:00", "10:00")));
+    }
+}
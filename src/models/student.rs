@@ -1,7 +1,10 @@
 use serde::{Deserialize, Serialize};
-use diesel::{Queryable, Insertable};
-use crate::schema::students;
-use validator::Validate;
+use diesel::prelude::*;
+use diesel::pg::Pg;
+use crate::schema::{enrollments, sections, students};
+use crate::models::enrollment::Enrollment;
+use crate::models::permission::Permission;
+use validator::{Validate, ValidationError};
 use chrono::{DateTime, Utc};
 
 #[derive(Debug, Queryable, Serialize)]
@@ -10,11 +13,39 @@ pub struct Student {
     pub name: String,
     pub phone: String,
     pub email: String,
-    pub course: String,
     pub created_by: Option<i32>,
     pub updated_by: Option<i32>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    pub course_id: i32,
+    pub level: String,
+    pub academic_standing: String,
+    pub user_id: Option<i32>,
+    pub cohort: Option<String>,
+    pub advisor_id: Option<i32>,
+}
+
+impl Student {
+    pub const LEVELS: [&'static str; 2] = ["undergraduate", "graduate"];
+    pub const STANDINGS: [&'static str; 3] = ["good", "warning", "probation"];
+}
+
+pub(crate) fn validate_level(level: &str) -> Result<(), ValidationError> {
+    if Student::LEVELS.contains(&level) {
+        return Ok(());
+    }
+    let mut error = ValidationError::new("student_level");
+    error.message = Some("level must be undergraduate or graduate".into());
+    Err(error)
+}
+
+pub(crate) fn validate_standing(standing: &str) -> Result<(), ValidationError> {
+    if Student::STANDINGS.contains(&standing) {
+        return Ok(());
+    }
+    let mut error = ValidationError::new("academic_standing");
+    error.message = Some("academic_standing must be good, warning or probation".into());
+    Err(error)
 }
 
 #[derive(Debug, Insertable, Deserialize, Validate)]
@@ -29,5 +60,175 @@ pub struct NewStudent {
     #[validate(email)]
     pub email: String,
     
-    pub course: String,
-} 
\ No newline at end of file
+    pub course_id: i32,
+
+    // Left out, these take the column defaults: undergraduate in good
+    // standing.
+    #[validate(custom = "validate_level")]
+    pub level: Option<String>,
+
+    #[validate(custom = "validate_standing")]
+    pub academic_standing: Option<String>,
+
+    // The user account the student signs in with, if they have one.
+    pub user_id: Option<i32>,
+
+    #[validate(length(min = 1, max = 50))]
+    pub cohort: Option<String>,
+
+    // The advisor's user account.
+    pub advisor_id: Option<i32>,
+}
+
+// Partial update used by PATCH; absent fields are left untouched and present
+// ones are held to the same rules as `NewStudent`.
+#[derive(Debug, AsChangeset, Deserialize, Validate)]
+#[diesel(table_name = students)]
+pub struct UpdateStudent {
+    #[validate(length(min = 2))]
+    pub name: Option<String>,
+
+    #[validate(length(min = 10, max = 15))]
+    pub phone: Option<String>,
+
+    #[validate(email)]
+    pub email: Option<String>,
+
+    pub course_id: Option<i32>,
+
+    #[validate(custom = "validate_level")]
+    pub level: Option<String>,
+
+    #[validate(custom = "validate_standing")]
+    pub academic_standing: Option<String>,
+
+    pub user_id: Option<i32>,
+
+    #[validate(length(min = 1, max = 50))]
+    pub cohort: Option<String>,
+
+    pub advisor_id: Option<i32>,
+}
+
+impl From<NewStudent> for UpdateStudent {
+    fn from(student: NewStudent) -> Self {
+        UpdateStudent {
+            name: Some(student.name),
+            phone: Some(student.phone),
+            email: Some(student.email),
+            course_id: Some(student.course_id),
+            level: student.level,
+            academic_standing: student.academic_standing,
+            user_id: student.user_id,
+            cohort: student.cohort,
+            advisor_id: student.advisor_id,
+        }
+    }
+}
+
+// Single-student response: the record plus GPA and earned credits from
+// posted grades.
+#[derive(Debug, Serialize)]
+pub struct StudentDetail {
+    #[serde(flatten)]
+    pub student: Student,
+    pub gpa: Option<f64>,
+    pub credits_earned: i32,
+}
+
+/// The student records a caller may see. Student queries made on behalf of a
+/// request start from `StudentScope::students` so the rules live here rather
+/// than in each handler; handlers take the scope as an argument.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StudentScope {
+    /// Every student.
+    All,
+    /// Students whose advisor is this user.
+    Advisees(i32),
+    /// Students enrolled in sections this user teaches.
+    Taught(i32),
+    /// The student record linked to this user's account.
+    Own(i32),
+}
+
+impl StudentScope {
+    /// Roles granted `students.read_all` see every student. Otherwise advisors
+    /// see their advisees, instructors their enrolled students and any other
+    /// role, including one added or renamed later, only the caller's own
+    /// record.
+    pub fn for_role(role: &str, user_id: i32, read_all: bool) -> Self {
+        if read_all {
+            return StudentScope::All;
+        }
+        match role {
+            "advisor" => StudentScope::Advisees(user_id),
+            "instructor" => StudentScope::Taught(user_id),
+            _ => StudentScope::Own(user_id),
+        }
+    }
+
+    /// The scope of a caller, looking up whether their role may read every
+    /// student.
+    pub fn for_caller(conn: &mut PgConnection, role: &str, user_id: i32) -> QueryResult<Self> {
+        let read_all = Permission::granted(conn, role, Permission::STUDENTS_READ_ALL)?;
+        Ok(StudentScope::for_role(role, user_id, read_all))
+    }
+
+    /// The students within the scope.
+    pub fn students(self) -> students::BoxedQuery<'static, Pg> {
+        let query = students::table.into_boxed();
+        match self {
+            StudentScope::All => query,
+            StudentScope::Advisees(user_id) => query.filter(students::advisor_id.eq(user_id)),
+            StudentScope::Taught(user_id) => query.filter(
+                students::id.eq_any(
+                    enrollments::table
+                        .inner_join(sections::table)
+                        .filter(sections::instructor_id.eq(user_id))
+                        .filter(enrollments::status.eq(Enrollment::ENROLLED))
+                        .select(enrollments::student_id),
+                ),
+            ),
+            StudentScope::Own(user_id) => query.filter(students::user_id.eq(user_id)),
+        }
+    }
+
+    /// Ids of the students within the scope, for filtering updates and
+    /// deletes.
+    pub fn student_ids(self) -> students::BoxedQuery<'static, Pg, diesel::sql_types::Integer> {
+        self.students().select(students::id)
+    }
+
+    /// Whether the student is within the scope.
+    pub fn contains(self, conn: &mut PgConnection, student_id: i32) -> QueryResult<bool> {
+        diesel::select(diesel::dsl::exists(
+            self.student_ids().filter(students::id.eq(student_id)),
+        ))
+        .get_result(conn)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_all_grants_every_student() {
+        for role in ["admin", "advisor", "instructor", "user", "bursar"] {
+            assert_eq!(StudentScope::for_role(role, 7, true), StudentScope::All);
+        }
+    }
+
+    #[test]
+    fn advisors_and_instructors_are_limited_to_their_students() {
+        assert_eq!(StudentScope::for_role("advisor", 7, false), StudentScope::Advisees(7));
+        assert_eq!(StudentScope::for_role("instructor", 7, false), StudentScope::Taught(7));
+    }
+
+    #[test]
+    fn other_roles_only_see_their_own_record() {
+        for role in ["user", "bursar", "dean", "registrar", "Advisor", ""] {
+            assert_eq!(StudentScope::for_role(role, 7, false), StudentScope::Own(7));
+        }
+    }
+}
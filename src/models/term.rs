@@ -0,0 +1,132 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use validator::{Validate, ValidationError};
+use crate::schema::terms;
+
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Identifiable)]
+#[diesel(table_name = terms)]
+pub struct Term {
+    pub id: i32,
+    pub code: String,
+    pub name: String,
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    pub registration_opens: NaiveDate,
+    pub registration_closes: NaiveDate,
+    pub add_drop_deadline: NaiveDate,
+    pub withdrawal_deadline: NaiveDate,
+    pub census_date: NaiveDate,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, Validate, Insertable, AsChangeset)]
+#[diesel(table_name = terms)]
+#[validate(schema(function = "validate_term_dates"))]
+pub struct NewTerm {
+    #[validate(length(min = 2, max = 20))]
+    pub code: String,
+    #[validate(length(min = 1, max = 100))]
+    pub name: String,
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    pub registration_opens: NaiveDate,
+    pub registration_closes: NaiveDate,
+    pub add_drop_deadline: NaiveDate,
+    pub withdrawal_deadline: NaiveDate,
+    pub census_date: NaiveDate,
+}
+
+/// Mirrors the `terms_dates_ordered` check constraint so callers get a
+/// readable validation error instead of a database failure.
+fn validate_term_dates(term: &NewTerm) -> Result<(), ValidationError> {
+    let ordered = [
+        (term.start_date < term.end_date, "start_date must be before end_date"),
+        (
+            term.registration_opens <= term.registration_closes,
+            "registration_opens must not be after registration_closes",
+        ),
+        (
+            term.registration_closes <= term.end_date,
+            "registration_closes must not be after end_date",
+        ),
+        (
+            term.start_date <= term.add_drop_deadline,
+            "add_drop_deadline must not be before start_date",
+        ),
+        (
+            term.add_drop_deadline <= term.withdrawal_deadline,
+            "withdrawal_deadline must not be before add_drop_deadline",
+        ),
+        (
+            term.withdrawal_deadline <= term.end_date,
+            "withdrawal_deadline must not be after end_date",
+        ),
+        (
+            term.start_date <= term.census_date && term.census_date <= term.end_date,
+            "census_date must fall within the term",
+        ),
+    ];
+
+    match ordered.iter().find(|(ok, _)| !ok) {
+        Some((_, message)) => {
+            let mut error = ValidationError::new("term_dates");
+            error.message = Some((*message).into());
+            Err(error)
+        }
+        None => Ok(()),
+    }
+}
+
+impl Term {
+    /// The term in session on `today`, or the next one to start when the
+    /// calendar is between terms.
+    pub fn current(conn: &mut PgConnection, today: NaiveDate) -> QueryResult<Option<Term>> {
+        let in_session = terms::table
+            .filter(terms::start_date.le(today))
+            .filter(terms::end_date.ge(today))
+            .first::<Term>(conn)
+            .optional()?;
+
+        match in_session {
+            Some(term) => Ok(Some(term)),
+            None => terms::table
+                .filter(terms::start_date.gt(today))
+                .order(terms::start_date.asc())
+                .first::<Term>(conn)
+                .optional(),
+        }
+    }
+
+    /// Looks a term up by code, accepting `current` as an alias for
+    /// [`Term::current`] so routes like `/terms/current/...` work.
+    pub fn resolve(conn: &mut PgConnection, key: &str) -> QueryResult<Term> {
+        if key.eq_ignore_ascii_case("current") {
+            return Term::current(conn, Utc::now().date_naive())?
+                .ok_or(diesel::result::Error::NotFound);
+        }
+
+        terms::table
+            .filter(terms::code.eq(key.trim().to_uppercase()))
+            .first::<Term>(conn)
+    }
+
+    /// Other terms whose dates intersect `[start_date, end_date]`.
+    pub fn overlapping(
+        conn: &mut PgConnection,
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+        exclude_id: Option<i32>,
+    ) -> QueryResult<Vec<Term>> {
+        terms::table
+            .filter(terms::start_date.le(end_date))
+            .filter(terms::end_date.ge(start_date))
+            .filter(terms::id.ne(exclude_id.unwrap_or(0)))
+            .load::<Term>(conn)
+    }
+
+    pub fn is_registration_open(&self, today: NaiveDate) -> bool {
+        self.registration_opens <= today && today <= self.registration_closes
+    }
+}
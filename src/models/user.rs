@@ -1,12 +1,14 @@
 use crate::models::role::Role;
-use crate::schema::{user_tokens, users};
+use crate::schema::{user_sessions, user_tokens, users};
 use bcrypt::{hash, verify, DEFAULT_COST};
 use chrono::{DateTime, Utc};
 use diesel::prelude::*;
 use jsonwebtoken::{
     decode, encode, errors::Error as JwtError, DecodingKey, EncodingKey, Header, Validation,
 };
+use rand::RngCore;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use validator::Validate;
 
 #[derive(Debug, Serialize, Deserialize, Queryable, Identifiable, Associations)]
@@ -41,21 +43,93 @@ pub struct NewUser {
     pub role_id: i32,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/// Access token claims, issued by `User::generate_token` and checked by
+/// `middleware::auth::JwtAuth`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Claims {
     pub sub: i32, // user id
     pub exp: usize,
     pub role: String,
+    pub jti: String,
+    pub sid: i32, // session id
 }
 
-#[derive(Debug, Serialize, Deserialize, Queryable, Identifiable)]
+/// One sign-in of a user, from login until it expires or is revoked. Its
+/// refresh tokens and the access tokens issued with them belong to it.
+#[derive(Debug, Serialize, Deserialize, Queryable, Identifiable, Associations)]
+#[diesel(belongs_to(User))]
+#[diesel(table_name = user_sessions)]
+pub struct UserSession {
+    pub id: i32,
+    pub user_id: i32,
+    pub user_agent: Option<String>,
+    pub ip_address: Option<String>,
+    pub created_at: DateTime<Utc>,
+    // Last time the session's refresh token was used
+    pub last_used_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = user_sessions)]
+pub struct NewUserSession {
+    pub user_id: i32,
+    pub user_agent: Option<String>,
+    pub ip_address: Option<String>,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// A refresh token. Only the hash of the token handed to the client is
+/// stored; every token issued from one login shares its session, and the
+/// access token issued alongside it is recorded so it can be denied.
+#[derive(Debug, Serialize, Deserialize, Queryable, Identifiable, Associations)]
+#[diesel(belongs_to(UserSession, foreign_key = session_id))]
 #[diesel(table_name = user_tokens)]
 pub struct UserToken {
     pub id: i32,
     pub user_id: i32,
-    pub token: String,
+    #[serde(skip_serializing)]
+    pub token_hash: String,
     pub expires_at: DateTime<Utc>,
     pub created_at: DateTime<Utc>,
+    // Set when the token is exchanged for a new one
+    pub rotated_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
+    pub session_id: i32,
+    pub access_jti: Option<String>,
+    pub access_expires_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = user_tokens)]
+pub struct NewUserToken {
+    pub user_id: i32,
+    pub token_hash: String,
+    pub expires_at: DateTime<Utc>,
+    pub session_id: i32,
+    pub access_jti: Option<String>,
+    pub access_expires_at: Option<DateTime<Utc>>,
+}
+
+impl UserToken {
+    /// A new opaque refresh token: 32 random bytes, hex encoded.
+    pub fn generate() -> String {
+        let mut bytes = [0u8; 32];
+        rand::thread_rng().fill_bytes(&mut bytes);
+        bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+    }
+
+    /// The hash a refresh token is stored and looked up by.
+    pub fn hash(token: &str) -> String {
+        format!("{:x}", Sha256::digest(token.as_bytes()))
+    }
+
+    /// Whether the token has already been exchanged for a new one.
+    /// Presenting a spent token again counts as reuse.
+    pub fn is_spent(&self) -> bool {
+        self.rotated_at.is_some()
+    }
 }
 
 impl User {
@@ -71,12 +145,16 @@ impl User {
         &self,
         secret: &str,
         role_name: &str,
+        session_id: i32,
+        jti: &str,
         expiration: usize,
     ) -> Result<String, JwtError> {
         let claims = Claims {
             sub: self.id,
             exp: expiration,
             role: role_name.to_string(),
+            jti: jti.to_string(),
+            sid: session_id,
         };
 
         encode(
@@ -0,0 +1,56 @@
+use crate::models::section::Section;
+use crate::models::student::Student;
+use crate::schema::waitlist_entries;
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Identifiable, Associations)]
+#[diesel(belongs_to(Student))]
+#[diesel(belongs_to(Section))]
+#[diesel(table_name = waitlist_entries)]
+pub struct WaitlistEntry {
+    pub id: i32,
+    pub student_id: i32,
+    pub section_id: i32,
+    pub status: String,
+    pub auto_enroll: bool,
+    pub offered_at: Option<DateTime<Utc>>,
+    pub offer_expires_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = waitlist_entries)]
+pub struct NewWaitlistEntry {
+    pub student_id: i32,
+    pub section_id: i32,
+    pub auto_enroll: bool,
+}
+
+impl WaitlistEntry {
+    /// In line for a seat.
+    pub const WAITING: &'static str = "waiting";
+    /// Holding a reserved seat until `offer_expires_at`.
+    pub const OFFERED: &'static str = "offered";
+    /// Converted into an enrollment.
+    pub const ENROLLED: &'static str = "enrolled";
+    /// Let an offer lapse and was skipped.
+    pub const EXPIRED: &'static str = "expired";
+    /// Left the waitlist or became ineligible.
+    pub const CANCELLED: &'static str = "cancelled";
+
+    pub fn is_open(&self) -> bool {
+        self.status == WaitlistEntry::WAITING || self.status == WaitlistEntry::OFFERED
+    }
+}
+
+/// A waitlist entry with the student's current place in line; `position` is
+/// `None` once the entry is no longer waiting.
+#[derive(Debug, Serialize)]
+pub struct WaitlistPosition {
+    #[serde(flatten)]
+    pub entry: WaitlistEntry,
+    pub position: Option<i64>,
+}
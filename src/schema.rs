@@ -1,5 +1,17 @@
 // @generated automatically by Diesel CLI.
 
+diesel::table! {
+    appointment_overrides (id) {
+        id -> Int4,
+        student_id -> Int4,
+        term_id -> Int4,
+        opens_at -> Timestamptz,
+        reason -> Text,
+        created_by -> Nullable<Int4>,
+        created_at -> Timestamptz,
+    }
+}
+
 diesel::table! {
     audit_logs (id) {
         id -> Int4,
@@ -17,6 +29,253 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    buildings (id) {
+        id -> Int4,
+        #[max_length = 10]
+        code -> Varchar,
+        #[max_length = 100]
+        name -> Varchar,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    cart_items (id) {
+        id -> Int4,
+        student_id -> Int4,
+        section_id -> Int4,
+        created_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    courses (id) {
+        id -> Int4,
+        #[max_length = 20]
+        code -> Varchar,
+        #[max_length = 255]
+        title -> Varchar,
+        credits -> Int4,
+        #[max_length = 100]
+        department -> Varchar,
+        description -> Nullable<Text>,
+        is_active -> Bool,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+        prerequisites -> Nullable<Text>,
+        corequisites -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    credit_limits (id) {
+        id -> Int4,
+        #[max_length = 20]
+        level -> Varchar,
+        #[max_length = 20]
+        academic_standing -> Nullable<Varchar>,
+        min_credits -> Int4,
+        max_credits -> Int4,
+        full_time_credits -> Int4,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    credit_overloads (id) {
+        id -> Int4,
+        student_id -> Int4,
+        term_id -> Int4,
+        max_credits -> Int4,
+        reason -> Text,
+        approved_by -> Nullable<Int4>,
+        created_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    enrollments (id) {
+        id -> Int4,
+        student_id -> Int4,
+        section_id -> Int4,
+        #[max_length = 20]
+        status -> Varchar,
+        enrolled_at -> Timestamptz,
+        dropped_at -> Nullable<Timestamptz>,
+        created_by -> Nullable<Int4>,
+        updated_by -> Nullable<Int4>,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+        #[max_length = 5]
+        grade -> Nullable<Varchar>,
+        grade_points -> Nullable<Float8>,
+        credit_earned -> Bool,
+        graded_at -> Nullable<Timestamptz>,
+        graded_by -> Nullable<Int4>,
+    }
+}
+
+diesel::table! {
+    grade_scale_entries (id) {
+        id -> Int4,
+        grade_scale_id -> Int4,
+        #[max_length = 5]
+        grade -> Varchar,
+        points -> Nullable<Float8>,
+        earns_credit -> Bool,
+        is_final -> Bool,
+    }
+}
+
+diesel::table! {
+    grade_scales (id) {
+        id -> Int4,
+        #[max_length = 100]
+        name -> Varchar,
+        description -> Nullable<Text>,
+        is_default -> Bool,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    holds (id) {
+        id -> Int4,
+        student_id -> Int4,
+        #[max_length = 20]
+        kind -> Varchar,
+        reason -> Text,
+        #[max_length = 100]
+        office -> Varchar,
+        starts_on -> Date,
+        ends_on -> Nullable<Date>,
+        blocks_registration -> Bool,
+        blocks_transcript -> Bool,
+        blocks_graduation -> Bool,
+        created_by -> Nullable<Int4>,
+        updated_by -> Nullable<Int4>,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    instructors (id) {
+        id -> Int4,
+        user_id -> Int4,
+        #[max_length = 100]
+        department -> Varchar,
+        #[max_length = 100]
+        title -> Varchar,
+        #[max_length = 100]
+        office -> Nullable<Varchar>,
+        max_sections -> Int4,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    lottery_requests (id) {
+        id -> Int4,
+        student_id -> Int4,
+        section_id -> Int4,
+        rank -> Int4,
+        #[max_length = 20]
+        status -> Varchar,
+        lottery_run_id -> Nullable<Int4>,
+        created_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    lottery_runs (id) {
+        id -> Int4,
+        term_id -> Int4,
+        seed -> Int8,
+        weights -> Jsonb,
+        report -> Jsonb,
+        run_by -> Nullable<Int4>,
+        created_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    permissions (id) {
+        id -> Int4,
+        #[max_length = 100]
+        name -> Varchar,
+        description -> Nullable<Text>,
+        created_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    program_requirement_courses (requirement_id, course_id) {
+        requirement_id -> Int4,
+        course_id -> Int4,
+    }
+}
+
+diesel::table! {
+    program_requirements (id) {
+        id -> Int4,
+        program_id -> Int4,
+        position -> Int4,
+        #[max_length = 255]
+        name -> Varchar,
+        #[max_length = 20]
+        kind -> Varchar,
+        min_credits -> Nullable<Int4>,
+        min_gpa -> Nullable<Float8>,
+    }
+}
+
+diesel::table! {
+    programs (id) {
+        id -> Int4,
+        #[max_length = 20]
+        code -> Varchar,
+        #[max_length = 255]
+        name -> Varchar,
+        #[max_length = 10]
+        kind -> Varchar,
+        #[max_length = 100]
+        department -> Varchar,
+        description -> Nullable<Text>,
+        is_active -> Bool,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    registration_windows (id) {
+        id -> Int4,
+        term_id -> Int4,
+        #[max_length = 100]
+        name -> Varchar,
+        opens_at -> Timestamptz,
+        min_credits -> Nullable<Int4>,
+        #[max_length = 50]
+        cohort -> Nullable<Varchar>,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    role_permissions (role_id, permission_id) {
+        role_id -> Int4,
+        permission_id -> Int4,
+        granted_at -> Timestamptz,
+    }
+}
+
 diesel::table! {
     roles (id) {
         id -> Int4,
@@ -28,17 +287,118 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    room_features (room_id, feature) {
+        room_id -> Int4,
+        #[max_length = 30]
+        feature -> Varchar,
+    }
+}
+
+diesel::table! {
+    rooms (id) {
+        id -> Int4,
+        building_id -> Int4,
+        #[max_length = 20]
+        room_number -> Varchar,
+        capacity -> Int4,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    section_meetings (id) {
+        id -> Int4,
+        section_id -> Int4,
+        day_of_week -> Int2,
+        start_time -> Time,
+        end_time -> Time,
+    }
+}
+
+diesel::table! {
+    sections (id) {
+        id -> Int4,
+        term_id -> Int4,
+        course_id -> Int4,
+        #[max_length = 10]
+        section_number -> Varchar,
+        capacity -> Int4,
+        instructor_id -> Nullable<Int4>,
+        #[max_length = 50]
+        room -> Nullable<Varchar>,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+        enrolled_count -> Int4,
+        reserved_count -> Int4,
+        lottery -> Bool,
+        room_id -> Nullable<Int4>,
+    }
+}
+
+diesel::table! {
+    student_programs (id) {
+        id -> Int4,
+        student_id -> Int4,
+        program_id -> Int4,
+        declared_at -> Timestamptz,
+        created_by -> Nullable<Int4>,
+    }
+}
+
 diesel::table! {
     students (id) {
         id -> Int4,
         name -> Varchar,
         phone -> Varchar,
         email -> Varchar,
-        course -> Varchar,
         created_by -> Nullable<Int4>,
         updated_by -> Nullable<Int4>,
         created_at -> Timestamptz,
         updated_at -> Timestamptz,
+        course_id -> Int4,
+        #[max_length = 20]
+        level -> Varchar,
+        #[max_length = 20]
+        academic_standing -> Varchar,
+        user_id -> Nullable<Int4>,
+        #[max_length = 50]
+        cohort -> Nullable<Varchar>,
+        advisor_id -> Nullable<Int4>,
+    }
+}
+
+diesel::table! {
+    terms (id) {
+        id -> Int4,
+        #[max_length = 20]
+        code -> Varchar,
+        #[max_length = 100]
+        name -> Varchar,
+        start_date -> Date,
+        end_date -> Date,
+        registration_opens -> Date,
+        registration_closes -> Date,
+        add_drop_deadline -> Date,
+        withdrawal_deadline -> Date,
+        census_date -> Date,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    user_sessions (id) {
+        id -> Int4,
+        user_id -> Int4,
+        user_agent -> Nullable<Text>,
+        #[max_length = 45]
+        ip_address -> Nullable<Varchar>,
+        created_at -> Timestamptz,
+        last_used_at -> Timestamptz,
+        expires_at -> Timestamptz,
+        revoked_at -> Nullable<Timestamptz>,
     }
 }
 
@@ -47,9 +407,15 @@ diesel::table! {
         id -> Int4,
         user_id -> Int4,
         #[max_length = 255]
-        token -> Varchar,
+        token_hash -> Varchar,
         expires_at -> Timestamptz,
         created_at -> Timestamptz,
+        rotated_at -> Nullable<Timestamptz>,
+        revoked_at -> Nullable<Timestamptz>,
+        session_id -> Int4,
+        #[max_length = 36]
+        access_jti -> Nullable<Varchar>,
+        access_expires_at -> Nullable<Timestamptz>,
     }
 }
 
@@ -74,14 +440,95 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    waitlist_entries (id) {
+        id -> Int4,
+        student_id -> Int4,
+        section_id -> Int4,
+        #[max_length = 20]
+        status -> Varchar,
+        auto_enroll -> Bool,
+        offered_at -> Nullable<Timestamptz>,
+        offer_expires_at -> Nullable<Timestamptz>,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+    }
+}
+
+diesel::joinable!(appointment_overrides -> students (student_id));
+diesel::joinable!(appointment_overrides -> terms (term_id));
+diesel::joinable!(appointment_overrides -> users (created_by));
 diesel::joinable!(audit_logs -> users (user_id));
+diesel::joinable!(cart_items -> sections (section_id));
+diesel::joinable!(cart_items -> students (student_id));
+diesel::joinable!(credit_overloads -> students (student_id));
+diesel::joinable!(credit_overloads -> terms (term_id));
+diesel::joinable!(credit_overloads -> users (approved_by));
+diesel::joinable!(enrollments -> sections (section_id));
+diesel::joinable!(enrollments -> students (student_id));
+diesel::joinable!(grade_scale_entries -> grade_scales (grade_scale_id));
+diesel::joinable!(holds -> students (student_id));
+diesel::joinable!(instructors -> users (user_id));
+diesel::joinable!(lottery_requests -> lottery_runs (lottery_run_id));
+diesel::joinable!(lottery_requests -> sections (section_id));
+diesel::joinable!(lottery_requests -> students (student_id));
+diesel::joinable!(lottery_runs -> terms (term_id));
+diesel::joinable!(lottery_runs -> users (run_by));
+diesel::joinable!(program_requirement_courses -> courses (course_id));
+diesel::joinable!(program_requirement_courses -> program_requirements (requirement_id));
+diesel::joinable!(program_requirements -> programs (program_id));
+diesel::joinable!(registration_windows -> terms (term_id));
+diesel::joinable!(role_permissions -> permissions (permission_id));
+diesel::joinable!(role_permissions -> roles (role_id));
+diesel::joinable!(room_features -> rooms (room_id));
+diesel::joinable!(rooms -> buildings (building_id));
+diesel::joinable!(section_meetings -> sections (section_id));
+diesel::joinable!(sections -> courses (course_id));
+diesel::joinable!(sections -> rooms (room_id));
+diesel::joinable!(sections -> terms (term_id));
+diesel::joinable!(sections -> users (instructor_id));
+diesel::joinable!(student_programs -> programs (program_id));
+diesel::joinable!(student_programs -> students (student_id));
+diesel::joinable!(student_programs -> users (created_by));
+diesel::joinable!(students -> courses (course_id));
+diesel::joinable!(user_sessions -> users (user_id));
+diesel::joinable!(user_tokens -> user_sessions (session_id));
 diesel::joinable!(user_tokens -> users (user_id));
 diesel::joinable!(users -> roles (role_id));
+diesel::joinable!(waitlist_entries -> sections (section_id));
+diesel::joinable!(waitlist_entries -> students (student_id));
 
 diesel::allow_tables_to_appear_in_same_query!(
+    appointment_overrides,
     audit_logs,
+    buildings,
+    cart_items,
+    courses,
+    credit_limits,
+    credit_overloads,
+    enrollments,
+    grade_scale_entries,
+    grade_scales,
+    holds,
+    instructors,
+    lottery_requests,
+    lottery_runs,
+    permissions,
+    program_requirement_courses,
+    program_requirements,
+    programs,
+    registration_windows,
+    role_permissions,
     roles,
+    room_features,
+    rooms,
+    section_meetings,
+    sections,
+    student_programs,
     students,
+    terms,
+    user_sessions,
     user_tokens,
     users,
+    waitlist_entries,
 );
@@ -0,0 +1,113 @@
+//! Priority registration appointments. Registration for a term opens to each
+//! student at their appointment: a per-student override if one was set,
+//! otherwise the earliest registration window they qualify for by credits
+//! earned or cohort. Students no window admits wait until the last window has
+//! opened, so they never register ahead of those with priority; without any
+//! windows everyone starts at the term's `registration_opens` date.
+//! Registration closes for everyone with the term's `registration_closes`
+//! date.
+
+use chrono::{DateTime, NaiveDate, NaiveTime, Utc};
+use diesel::prelude::*;
+use serde::Serialize;
+
+use crate::models::appointment::{AppointmentOverride, RegistrationWindow};
+use crate::models::{Student, Term};
+use crate::schema::{appointment_overrides, registration_windows};
+use crate::services::grades;
+use crate::services::registration::RegistrationError;
+
+/// When a student may register for a term and what that is based on.
+#[derive(Debug, Serialize)]
+pub struct Appointment {
+    pub student_id: i32,
+    pub term_code: String,
+    pub opens_at: DateTime<Utc>,
+    pub closes_on: NaiveDate,
+    pub credits_earned: i32,
+    pub cohort: Option<String>,
+    pub window: Option<RegistrationWindow>,
+    #[serde(rename = "override")]
+    pub appointment_override: Option<AppointmentOverride>,
+    pub is_open: bool,
+}
+
+pub fn for_student(
+    conn: &mut PgConnection,
+    student: &Student,
+    term: &Term,
+) -> QueryResult<Appointment> {
+    let credits_earned = grades::student_gpa(conn, student.id)?
+        .cumulative
+        .credits_earned;
+
+    let appointment_override = appointment_overrides::table
+        .filter(appointment_overrides::student_id.eq(student.id))
+        .filter(appointment_overrides::term_id.eq(term.id))
+        .first::<AppointmentOverride>(conn)
+        .optional()?;
+
+    let windows = registration_windows::table
+        .filter(registration_windows::term_id.eq(term.id))
+        .order((
+            registration_windows::opens_at.asc(),
+            registration_windows::id.asc(),
+        ))
+        .load::<RegistrationWindow>(conn)?;
+
+    let window = match appointment_override {
+        Some(_) => None,
+        None => windows
+            .iter()
+            .find(|window| window.admits(credits_earned, student.cohort.as_deref()))
+            .cloned(),
+    };
+
+    // Students no window admits wait for the last window to open, and never
+    // start before the term's own registration date.
+    let registration_opens = term.registration_opens.and_time(NaiveTime::MIN).and_utc();
+    let unassigned_opens_at = windows.last().map_or(registration_opens, |last| {
+        last.opens_at.max(registration_opens)
+    });
+
+    let opens_at = appointment_override
+        .as_ref()
+        .map(|appointment_override| appointment_override.opens_at)
+        .or(window.as_ref().map(|window| window.opens_at))
+        .unwrap_or(unassigned_opens_at);
+    let now = Utc::now();
+
+    Ok(Appointment {
+        student_id: student.id,
+        term_code: term.code.clone(),
+        opens_at,
+        closes_on: term.registration_closes,
+        credits_earned,
+        cohort: student.cohort.clone(),
+        window,
+        appointment_override,
+        is_open: opens_at <= now && now.date_naive() <= term.registration_closes,
+    })
+}
+
+/// Fails unless the student's appointment has arrived and registration for
+/// the term has not closed.
+pub(crate) fn ensure_open(
+    conn: &mut PgConnection,
+    student: &Student,
+    term: &Term,
+) -> Result<(), RegistrationError> {
+    let now = Utc::now();
+    if now.date_naive() > term.registration_closes {
+        return Err(RegistrationError::RegistrationClosed(term.code.clone()));
+    }
+
+    let appointment = for_student(conn, student, term)?;
+    if now < appointment.opens_at {
+        return Err(RegistrationError::BeforeAppointment {
+            term: term.code.clone(),
+            opens_at: appointment.opens_at,
+        });
+    }
+    Ok(())
+}
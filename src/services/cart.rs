@@ -0,0 +1,195 @@
+//! Registration carts. A student collects sections for a term, checks them
+//! all at once and then submits them as one batch: either every section is
+//! enrolled or none is. Both steps run the same rules as a single
+//! enrollment, one section after another, so a later section is checked
+//! against the earlier ones (conflicts between cart sections, credits added
+//! by the whole cart). A section missing only a corequisite that comes later
+//! in the cart is tried again once the rest has been placed.
+
+use diesel::prelude::*;
+use diesel::result::Error as DieselError;
+use serde_json::json;
+use thiserror::Error;
+
+use crate::models::cart::CartItem;
+use crate::models::{AuditContext, Enrollment, Term};
+use crate::schema::{audit_logs, cart_items, sections};
+use crate::services::registration::{self, Overrides, RegistrationError};
+
+#[derive(Debug, Error)]
+pub enum CartError {
+    #[error("Cart item not found")]
+    ItemNotFound,
+
+    #[error("The section is already in the cart")]
+    AlreadyInCart,
+
+    #[error("The cart has no sections for term {0}")]
+    Empty(String),
+
+    #[error("Not every section in the cart can be enrolled; nothing was submitted")]
+    Rejected(Vec<CartCheck>),
+
+    #[error(transparent)]
+    Registration(#[from] RegistrationError),
+}
+
+impl From<DieselError> for CartError {
+    fn from(error: DieselError) -> Self {
+        CartError::Registration(error.into())
+    }
+}
+
+/// The result of running the enrollment checks for one cart item.
+#[derive(Debug)]
+pub struct CartCheck {
+    pub item: CartItem,
+    pub result: Result<Enrollment, RegistrationError>,
+}
+
+impl CartCheck {
+    pub fn passed(&self) -> bool {
+        self.result.is_ok()
+    }
+
+    /// Whether the section failed only because a corequisite is not taken
+    /// yet, which another section of the cart may still provide.
+    fn awaits_corequisite(&self) -> bool {
+        matches!(
+            &self.result,
+            Err(RegistrationError::RequisitesNotMet(report))
+                if report.prerequisites.as_ref().is_none_or(|clause| clause.satisfied)
+        )
+    }
+}
+
+/// The student's cart items for sections in the term, oldest first.
+pub fn items(conn: &mut PgConnection, student_id: i32, term: &Term) -> QueryResult<Vec<CartItem>> {
+    cart_items::table
+        .inner_join(sections::table)
+        .filter(cart_items::student_id.eq(student_id))
+        .filter(sections::term_id.eq(term.id))
+        .order(cart_items::id.asc())
+        .select(cart_items::all_columns)
+        .load::<CartItem>(conn)
+}
+
+/// Tries to enroll each item in its own savepoint, so a failing section is
+/// undone while the ones that passed stay in place for the next check.
+/// Sections waiting on a corequisite are retried until a round places
+/// nothing new. Database errors abort the whole attempt.
+fn attempt(
+    conn: &mut PgConnection,
+    student_id: i32,
+    items: Vec<CartItem>,
+    audit: &AuditContext,
+) -> Result<Vec<CartCheck>, RegistrationError> {
+    let mut checks = Vec::with_capacity(items.len());
+    for item in items {
+        let result = try_enroll(conn, student_id, &item, audit)?;
+        checks.push(CartCheck { item, result });
+    }
+
+    loop {
+        let mut placed = false;
+        for check in checks.iter_mut().filter(|check| check.awaits_corequisite()) {
+            check.result = try_enroll(conn, student_id, &check.item, audit)?;
+            placed |= check.passed();
+        }
+        if !placed {
+            return Ok(checks);
+        }
+    }
+}
+
+/// Enrolls one item in a savepoint. The inner result is the item's outcome;
+/// the outer one carries database errors.
+fn try_enroll(
+    conn: &mut PgConnection,
+    student_id: i32,
+    item: &CartItem,
+    audit: &AuditContext,
+) -> Result<Result<Enrollment, RegistrationError>, RegistrationError> {
+    let result = conn.transaction(|conn| {
+        registration::enroll(
+            conn,
+            student_id,
+            item.section_id,
+            Overrides::default(),
+            audit,
+        )
+    });
+    match result {
+        Err(RegistrationError::Database(err)) => Err(err.into()),
+        result => Ok(result),
+    }
+}
+
+/// Runs every registration check for the cart without keeping anything:
+/// the enrollments made along the way are rolled back.
+pub fn validate(
+    conn: &mut PgConnection,
+    student_id: i32,
+    term: &Term,
+    audit: &AuditContext,
+) -> Result<Vec<CartCheck>, CartError> {
+    registration::find_student(conn, student_id)?;
+    let items = items(conn, student_id, term)?;
+    if items.is_empty() {
+        return Err(CartError::Empty(term.code.clone()));
+    }
+
+    let mut checks = Vec::new();
+    let rolled_back = conn.transaction::<(), RegistrationError, _>(|conn| {
+        checks = attempt(conn, student_id, items, audit)?;
+        Err(DieselError::RollbackTransaction.into())
+    });
+    match rolled_back {
+        Err(RegistrationError::Database(DieselError::RollbackTransaction)) => Ok(checks),
+        Err(err) => Err(err.into()),
+        Ok(()) => unreachable!("the validation transaction always rolls back"),
+    }
+}
+
+/// Enrolls the student in every section of the cart and empties it. Fails
+/// with the per-section results if any section does not pass; the caller's
+/// transaction must then be rolled back so none of the enrollments stick.
+pub fn submit(
+    conn: &mut PgConnection,
+    student_id: i32,
+    term: &Term,
+    audit: &AuditContext,
+) -> Result<Vec<Enrollment>, CartError> {
+    registration::find_student(conn, student_id)?;
+    let items = items(conn, student_id, term)?;
+    if items.is_empty() {
+        return Err(CartError::Empty(term.code.clone()));
+    }
+
+    let checks = attempt(conn, student_id, items, audit)?;
+    if !checks.iter().all(CartCheck::passed) {
+        return Err(CartError::Rejected(checks));
+    }
+
+    let item_ids: Vec<i32> = checks.iter().map(|check| check.item.id).collect();
+    let enrollments: Vec<Enrollment> = checks
+        .into_iter()
+        .filter_map(|check| check.result.ok())
+        .collect();
+
+    diesel::delete(cart_items::table.filter(cart_items::id.eq_any(item_ids))).execute(conn)?;
+
+    diesel::insert_into(audit_logs::table)
+        .values(&audit.activity(
+            "cart.submit",
+            "student",
+            Some(student_id),
+            Some(json!({
+                "term": term.code,
+                "enrollments": enrollments.iter().map(|e| e.id).collect::<Vec<_>>(),
+            })),
+        ))
+        .execute(conn)?;
+
+    Ok(enrollments)
+}
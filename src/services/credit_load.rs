@@ -0,0 +1,255 @@
+//! Per-term credit loads. Adding a course may not take a student above the
+//! maximum for their level and academic standing unless an advisor has
+//! approved an overload for the term; dropping one may not leave them below
+//! the minimum, short of dropping everything. Loads under full time are
+//! allowed but come back with a warning.
+
+use diesel::prelude::*;
+use serde::Serialize;
+
+use crate::models::credit_load::{CreditLimit, CreditOverload};
+use crate::models::{Course, Enrollment, Section, Student, Term};
+use crate::schema::{courses, credit_overloads, enrollments, sections, students, terms};
+use crate::services::registration::RegistrationError;
+
+/// A student's registered credits for a term against the limits that apply.
+#[derive(Debug, Serialize)]
+pub struct CreditLoad {
+    pub student_id: i32,
+    pub term_code: String,
+    pub credits: i32,
+    pub min_credits: Option<i32>,
+    pub max_credits: Option<i32>,
+    pub full_time_credits: Option<i32>,
+    pub overload: Option<CreditOverload>,
+    pub warnings: Vec<String>,
+}
+
+/// Credits the student is enrolled in for the term.
+pub fn term_credits(conn: &mut PgConnection, student_id: i32, term_id: i32) -> QueryResult<i32> {
+    let total = enrollments::table
+        .inner_join(sections::table.inner_join(courses::table))
+        .filter(enrollments::student_id.eq(student_id))
+        .filter(enrollments::status.eq(Enrollment::ENROLLED))
+        .filter(sections::term_id.eq(term_id))
+        .select(diesel::dsl::sum(courses::credits))
+        .first::<Option<i64>>(conn)?;
+    Ok(total.unwrap_or(0) as i32)
+}
+
+fn find_overload(
+    conn: &mut PgConnection,
+    student_id: i32,
+    term_id: i32,
+) -> QueryResult<Option<CreditOverload>> {
+    credit_overloads::table
+        .filter(credit_overloads::student_id.eq(student_id))
+        .filter(credit_overloads::term_id.eq(term_id))
+        .first::<CreditOverload>(conn)
+        .optional()
+}
+
+/// The highest load allowed: the limit's maximum, raised by an approved
+/// overload. An overload never lowers it, even after the limit is raised past
+/// it, and without a limit there is no maximum to raise.
+fn effective_max(limit: Option<&CreditLimit>, overload: Option<&CreditOverload>) -> Option<i32> {
+    limit.map(|limit| {
+        overload.map_or(limit.max_credits, |overload| {
+            overload.max_credits.max(limit.max_credits)
+        })
+    })
+}
+
+pub fn load(conn: &mut PgConnection, student: &Student, term: &Term) -> QueryResult<CreditLoad> {
+    let credits = term_credits(conn, student.id, term.id)?;
+    let limit = CreditLimit::for_student(conn, student)?;
+    let overload = find_overload(conn, student.id, term.id)?;
+
+    let mut warnings = Vec::new();
+    if let Some(limit) = &limit {
+        if credits < limit.full_time_credits {
+            warnings.push(format!(
+                "{} credits in {} is below the full-time load of {}",
+                credits, term.code, limit.full_time_credits
+            ));
+        }
+    }
+
+    Ok(CreditLoad {
+        student_id: student.id,
+        term_code: term.code.clone(),
+        credits,
+        min_credits: limit.as_ref().map(|limit| limit.min_credits),
+        max_credits: effective_max(limit.as_ref(), overload.as_ref()),
+        full_time_credits: limit.as_ref().map(|limit| limit.full_time_credits),
+        overload,
+        warnings,
+    })
+}
+
+/// Warnings about the student's load in the section's term after a change.
+pub fn warnings_for_section(
+    conn: &mut PgConnection,
+    student_id: i32,
+    section_id: i32,
+) -> QueryResult<Vec<String>> {
+    let student = students::table.find(student_id).first::<Student>(conn)?;
+    let (_, term) = sections::table
+        .inner_join(terms::table)
+        .filter(sections::id.eq(section_id))
+        .first::<(Section, Term)>(conn)?;
+    Ok(load(conn, &student, &term)?.warnings)
+}
+
+fn course_credits(conn: &mut PgConnection, section: &Section) -> QueryResult<i32> {
+    courses::table
+        .find(section.course_id)
+        .first::<Course>(conn)
+        .map(|course| course.credits)
+}
+
+/// Fails when adding the section would take the student over their maximum
+/// for the term. The student row is locked so concurrent adds are counted
+/// one after another.
+pub(crate) fn ensure_within_max(
+    conn: &mut PgConnection,
+    student_id: i32,
+    section: &Section,
+    term: &Term,
+) -> Result<(), RegistrationError> {
+    let student = students::table
+        .find(student_id)
+        .for_update()
+        .first::<Student>(conn)?;
+    let limit = CreditLimit::for_student(conn, &student)?;
+    let overload = find_overload(conn, student_id, term.id)?;
+    let Some(max) = effective_max(limit.as_ref(), overload.as_ref()) else {
+        return Ok(());
+    };
+
+    let credits = term_credits(conn, student_id, term.id)? + course_credits(conn, section)?;
+    if credits > max {
+        return Err(RegistrationError::CreditLimitExceeded {
+            term: term.code.clone(),
+            credits,
+            max,
+        });
+    }
+    Ok(())
+}
+
+/// Fails when dropping the section would leave the student with some credits
+/// but fewer than the minimum. Dropping the last course is always allowed.
+pub(crate) fn ensure_above_min(
+    conn: &mut PgConnection,
+    student_id: i32,
+    section: &Section,
+    term: &Term,
+) -> Result<(), RegistrationError> {
+    let change = -course_credits(conn, section)?;
+    ensure_min_after_change(conn, student_id, term, change)
+}
+
+/// The minimum-load check for swapping `dropped` for `added`. Swaps that do
+/// not lower the load are always allowed.
+pub(crate) fn ensure_swap_above_min(
+    conn: &mut PgConnection,
+    student_id: i32,
+    dropped: &Section,
+    added: &Section,
+    term: &Term,
+) -> Result<(), RegistrationError> {
+    let change = course_credits(conn, added)? - course_credits(conn, dropped)?;
+    if change >= 0 {
+        return Ok(());
+    }
+    ensure_min_after_change(conn, student_id, term, change)
+}
+
+fn ensure_min_after_change(
+    conn: &mut PgConnection,
+    student_id: i32,
+    term: &Term,
+    change: i32,
+) -> Result<(), RegistrationError> {
+    let student = students::table
+        .find(student_id)
+        .for_update()
+        .first::<Student>(conn)?;
+    let Some(limit) = CreditLimit::for_student(conn, &student)? else {
+        return Ok(());
+    };
+
+    let credits = term_credits(conn, student_id, term.id)? + change;
+    if credits > 0 && credits < limit.min_credits {
+        return Err(RegistrationError::BelowMinimumLoad {
+            term: term.code.clone(),
+            credits,
+            min: limit.min_credits,
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn limit(max_credits: i32) -> CreditLimit {
+        CreditLimit {
+            id: 1,
+            level: "undergraduate".to_string(),
+            academic_standing: None,
+            min_credits: 3,
+            max_credits,
+            full_time_credits: 12,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    fn overload(max_credits: i32) -> CreditOverload {
+        CreditOverload {
+            id: 1,
+            student_id: 1,
+            term_id: 1,
+            max_credits,
+            reason: "Graduating senior".to_string(),
+            approved_by: None,
+            created_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn the_limit_applies_without_an_overload() {
+        assert_eq!(effective_max(Some(&limit(18)), None), Some(18));
+    }
+
+    #[test]
+    fn an_overload_raises_the_maximum() {
+        assert_eq!(
+            effective_max(Some(&limit(18)), Some(&overload(21))),
+            Some(21)
+        );
+    }
+
+    #[test]
+    fn an_overload_never_lowers_the_maximum() {
+        // The limit was raised past the overload after it was approved.
+        assert_eq!(
+            effective_max(Some(&limit(24)), Some(&overload(21))),
+            Some(24)
+        );
+        assert_eq!(
+            effective_max(Some(&limit(18)), Some(&overload(18))),
+            Some(18)
+        );
+    }
+
+    #[test]
+    fn there_is_no_maximum_without_a_limit() {
+        assert_eq!(effective_max(None, None), None);
+        assert_eq!(effective_max(None, Some(&overload(21))), None);
+    }
+}
@@ -0,0 +1,363 @@
+//! Degree audit: a student's record checked block by block against each
+//! program they have declared. A course counts once, at its best attempt: a
+//! final grade that earns credit completes it, while an ungraded or
+//! incomplete attempt leaves it in progress. Every course in the system is
+//! taken here, so all earned credit counts toward residency.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use serde::Serialize;
+
+use crate::models::program::{Program, ProgramRequirement};
+use crate::models::{
+    DeclaredProgram, Enrollment, GradeScaleDetail, Hold, HoldAction, ProgramDetail,
+    RequirementDetail,
+};
+use crate::schema::{courses, enrollments, sections, terms};
+use crate::services::grades::GpaTotals;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditStatus {
+    Missing,
+    InProgress,
+    Satisfied,
+}
+
+impl AuditStatus {
+    /// The weakest of a set of statuses; an empty set is satisfied.
+    fn all(statuses: impl IntoIterator<Item = AuditStatus>) -> AuditStatus {
+        statuses.into_iter().min().unwrap_or(AuditStatus::Satisfied)
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct CourseProgress {
+    pub course_code: String,
+    pub credits: i32,
+    pub status: AuditStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub grade: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub term_code: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RequirementResult {
+    pub name: String,
+    pub kind: String,
+    pub status: AuditStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub credits_required: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub credits_completed: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub credits_in_progress: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_gpa: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gpa: Option<f64>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub courses: Vec<CourseProgress>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ProgramAudit {
+    pub program_id: i32,
+    pub code: String,
+    pub name: String,
+    pub kind: String,
+    pub status: AuditStatus,
+    pub requirements: Vec<RequirementResult>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DegreeAudit {
+    pub student_id: i32,
+    pub evaluated_at: DateTime<Utc>,
+    pub status: AuditStatus,
+    pub programs: Vec<ProgramAudit>,
+    /// Holds in effect that would stop the student graduating even with
+    /// every program satisfied.
+    pub graduation_holds: Vec<Hold>,
+}
+
+struct Attempt {
+    credits: i32,
+    term_code: String,
+    grade: Option<String>,
+    grade_points: Option<f64>,
+    status: AuditStatus,
+}
+
+/// A student's active enrollments grouped by course code, with catalog
+/// credits for courses a program lists that were never taken.
+struct Record {
+    attempts: HashMap<String, Vec<Attempt>>,
+    catalog: HashMap<String, i32>,
+}
+
+impl Record {
+    fn load(conn: &mut PgConnection, student_id: i32, listed: &[&String]) -> QueryResult<Record> {
+        let scale = GradeScaleDetail::load_default(conn)?;
+        let rows = enrollments::table
+            .inner_join(
+                sections::table
+                    .inner_join(terms::table)
+                    .inner_join(courses::table),
+            )
+            .filter(enrollments::student_id.eq(student_id))
+            .filter(enrollments::status.eq(Enrollment::ENROLLED))
+            .order((terms::start_date.asc(), terms::id.asc()))
+            .select((
+                courses::code,
+                courses::credits,
+                terms::code,
+                enrollments::grade,
+                enrollments::grade_points,
+                enrollments::credit_earned,
+            ))
+            .load::<(String, i32, String, Option<String>, Option<f64>, bool)>(conn)?;
+
+        let mut attempts: HashMap<String, Vec<Attempt>> = HashMap::new();
+        for (code, credits, term_code, grade, grade_points, credit_earned) in rows {
+            let is_final = grade.as_deref().is_some_and(|grade| {
+                scale
+                    .as_ref()
+                    .and_then(|scale| scale.entry(grade))
+                    .is_none_or(|entry| entry.is_final)
+            });
+            let status = if !is_final {
+                AuditStatus::InProgress
+            } else if credit_earned {
+                AuditStatus::Satisfied
+            } else {
+                AuditStatus::Missing
+            };
+            attempts.entry(code).or_default().push(Attempt {
+                credits,
+                term_code,
+                grade,
+                grade_points,
+                status,
+            });
+        }
+
+        let catalog = courses::table
+            .filter(courses::code.eq_any(listed))
+            .select((courses::code, courses::credits))
+            .load::<(String, i32)>(conn)?
+            .into_iter()
+            .collect();
+
+        Ok(Record { attempts, catalog })
+    }
+
+    /// The best attempt at a course, the latest among equals, or `None` if it
+    /// was never taken.
+    fn best(&self, code: &str) -> Option<&Attempt> {
+        self.attempts
+            .get(code)?
+            .iter()
+            .max_by_key(|attempt| attempt.status)
+    }
+
+    fn progress(&self, code: &str) -> CourseProgress {
+        match self.best(code) {
+            Some(attempt) => CourseProgress {
+                course_code: code.to_string(),
+                credits: attempt.credits,
+                status: attempt.status,
+                grade: attempt.grade.clone(),
+                term_code: Some(attempt.term_code.clone()),
+            },
+            None => CourseProgress {
+                course_code: code.to_string(),
+                credits: self.catalog.get(code).copied().unwrap_or(0),
+                status: AuditStatus::Missing,
+                grade: None,
+                term_code: None,
+            },
+        }
+    }
+
+    /// Course codes a block draws on; an empty list means all coursework.
+    fn scope<'a>(&'a self, courses: &'a [String]) -> Vec<&'a str> {
+        if courses.is_empty() {
+            let mut codes: Vec<&str> = self.attempts.keys().map(String::as_str).collect();
+            codes.sort();
+            codes
+        } else {
+            courses.iter().map(String::as_str).collect()
+        }
+    }
+
+    /// Completed and in-progress credits over the given courses.
+    fn credits(&self, codes: &[&str]) -> (i32, i32, Vec<CourseProgress>) {
+        let mut completed = 0;
+        let mut in_progress = 0;
+        let mut progress = Vec::new();
+        for code in codes {
+            let course = self.progress(code);
+            match course.status {
+                AuditStatus::Satisfied => completed += course.credits,
+                AuditStatus::InProgress => in_progress += course.credits,
+                AuditStatus::Missing => {}
+            }
+            progress.push(course);
+        }
+        (completed, in_progress, progress)
+    }
+
+    /// GPA over every graded attempt at the given courses, retakes included,
+    /// the same way the cumulative GPA is computed.
+    fn gpa(&self, codes: &[&str]) -> Option<f64> {
+        let mut totals = GpaTotals::default();
+        for attempt in codes
+            .iter()
+            .filter_map(|code| self.attempts.get(*code))
+            .flatten()
+            .filter(|attempt| attempt.grade.is_some())
+        {
+            totals.add(attempt.credits, attempt.grade_points, false);
+        }
+        totals.finish().gpa
+    }
+}
+
+fn credit_status(completed: i32, in_progress: i32, required: i32) -> AuditStatus {
+    if completed >= required {
+        AuditStatus::Satisfied
+    } else if completed + in_progress >= required {
+        AuditStatus::InProgress
+    } else {
+        AuditStatus::Missing
+    }
+}
+
+fn evaluate_requirement(detail: &RequirementDetail, record: &Record) -> RequirementResult {
+    let requirement = &detail.requirement;
+    let mut result = RequirementResult {
+        name: requirement.name.clone(),
+        kind: requirement.kind.clone(),
+        status: AuditStatus::Missing,
+        credits_required: None,
+        credits_completed: None,
+        credits_in_progress: None,
+        min_gpa: None,
+        gpa: None,
+        courses: Vec::new(),
+    };
+
+    match requirement.kind.as_str() {
+        ProgramRequirement::REQUIRED_COURSES => {
+            result.courses = detail
+                .courses
+                .iter()
+                .map(|code| record.progress(code))
+                .collect();
+            result.status = AuditStatus::all(result.courses.iter().map(|course| course.status));
+        }
+        ProgramRequirement::CHOOSE_CREDITS | ProgramRequirement::RESIDENCY_CREDITS => {
+            let required = requirement.min_credits.unwrap_or(0);
+            let (completed, in_progress, progress) = record.credits(&record.scope(&detail.courses));
+            result.status = credit_status(completed, in_progress, required);
+            result.credits_required = Some(required);
+            result.credits_completed = Some(completed);
+            result.credits_in_progress = Some(in_progress);
+            // Only list courses for a choice block; residency is about totals.
+            if requirement.kind == ProgramRequirement::CHOOSE_CREDITS {
+                result.courses = progress;
+            }
+        }
+        ProgramRequirement::MIN_GPA => {
+            let min_gpa = requirement.min_gpa.unwrap_or(0.0);
+            let codes = record.scope(&detail.courses);
+            let gpa = record.gpa(&codes);
+            let pending = codes
+                .iter()
+                .any(|code| record.progress(code).status == AuditStatus::InProgress);
+            result.status = match gpa {
+                Some(gpa) if gpa >= min_gpa => AuditStatus::Satisfied,
+                _ if pending => AuditStatus::InProgress,
+                _ => AuditStatus::Missing,
+            };
+            result.min_gpa = Some(min_gpa);
+            result.gpa = gpa;
+        }
+        other => log::error!(
+            "Unknown requirement kind {:?} on block {}",
+            other,
+            requirement.id
+        ),
+    }
+
+    result
+}
+
+fn evaluate_program(detail: &ProgramDetail, record: &Record) -> ProgramAudit {
+    let requirements: Vec<RequirementResult> = detail
+        .requirements
+        .iter()
+        .map(|requirement| evaluate_requirement(requirement, record))
+        .collect();
+    let program: &Program = &detail.program;
+
+    ProgramAudit {
+        program_id: program.id,
+        code: program.code.clone(),
+        name: program.name.clone(),
+        kind: program.kind.clone(),
+        status: AuditStatus::all(requirements.iter().map(|requirement| requirement.status)),
+        requirements,
+    }
+}
+
+/// Audits the student against their declared programs, or only `program_id`
+/// when given. A student with no declared program is reported as missing.
+pub fn audit(
+    conn: &mut PgConnection,
+    student_id: i32,
+    program_id: Option<i32>,
+) -> QueryResult<DegreeAudit> {
+    let programs: Vec<Program> = DeclaredProgram::load_for_student(conn, student_id)?
+        .into_iter()
+        .map(|declared| declared.program)
+        .filter(|program| program_id.is_none_or(|program_id| program.id == program_id))
+        .collect();
+    let details = ProgramDetail::load(conn, programs)?;
+    let listed: Vec<&String> = details
+        .iter()
+        .flat_map(|detail| &detail.requirements)
+        .flat_map(|requirement| &requirement.courses)
+        .collect();
+    let record = Record::load(conn, student_id, &listed)?;
+
+    let programs: Vec<ProgramAudit> = details
+        .iter()
+        .map(|detail| evaluate_program(detail, &record))
+        .collect();
+    let status = if programs.is_empty() {
+        AuditStatus::Missing
+    } else {
+        AuditStatus::all(programs.iter().map(|program| program.status))
+    };
+
+    let evaluated_at = Utc::now();
+    let graduation_holds = Hold::active_for(
+        conn,
+        student_id,
+        HoldAction::Graduation,
+        evaluated_at.date_naive(),
+    )?;
+
+    Ok(DegreeAudit {
+        student_id,
+        evaluated_at,
+        status,
+        programs,
+        graduation_holds,
+    })
+}
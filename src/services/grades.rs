@@ -0,0 +1,307 @@
+//! Final grades and GPA. Posting a grade copies its points and credit from
+//! the default grade scale onto the enrollment, so later changes to a scale do
+//! not rewrite grades already on a student's record.
+
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel::result::Error as DieselError;
+use serde::Serialize;
+use serde_json::json;
+use thiserror::Error;
+
+use crate::models::{AuditContext, Enrollment, GradeScaleDetail};
+use crate::schema::{audit_logs, courses, enrollments, sections, terms};
+
+#[derive(Debug, Error)]
+pub enum GradeError {
+    #[error("Term not found")]
+    TermNotFound,
+
+    #[error("Section not found")]
+    SectionNotFound,
+
+    #[error("Only the section's instructor or a user with the grades.manage permission can manage its grades")]
+    NotInstructor,
+
+    #[error("Enrollment {0} is not an active enrollment in this section")]
+    EnrollmentNotFound(i32),
+
+    #[error("Grade {0} is not on the {1} grade scale")]
+    UnknownGrade(String, String),
+
+    #[error("No default grade scale is configured")]
+    NoDefaultScale,
+
+    #[error("Database error: {0}")]
+    Database(#[from] DieselError),
+}
+
+/// Posts final grades for enrollments in a section. Either every grade is
+/// recorded or, on the first invalid one, none are.
+pub fn post_grades(
+    conn: &mut PgConnection,
+    section_id: i32,
+    grades: Vec<(i32, String)>,
+    audit: &AuditContext,
+) -> Result<Vec<Enrollment>, GradeError> {
+    let scale = GradeScaleDetail::load_default(conn)?.ok_or(GradeError::NoDefaultScale)?;
+
+    let mut posted = Vec::with_capacity(grades.len());
+    for (enrollment_id, grade) in grades {
+        let entry = scale
+            .entry(&grade)
+            .ok_or_else(|| GradeError::UnknownGrade(grade.clone(), scale.scale.name.clone()))?;
+
+        let before = enrollments::table
+            .filter(enrollments::id.eq(enrollment_id))
+            .filter(enrollments::section_id.eq(section_id))
+            .filter(enrollments::status.eq(Enrollment::ENROLLED))
+            .for_update()
+            .first::<Enrollment>(conn)
+            .optional()?
+            .ok_or(GradeError::EnrollmentNotFound(enrollment_id))?;
+
+        let now = Utc::now();
+        let after = diesel::update(enrollments::table.find(before.id))
+            .set((
+                enrollments::grade.eq(Some(&entry.grade)),
+                enrollments::grade_points.eq(entry.points),
+                enrollments::credit_earned.eq(entry.earns_credit),
+                enrollments::graded_at.eq(Some(now)),
+                enrollments::graded_by.eq(audit.user_id),
+                enrollments::updated_by.eq(audit.user_id),
+                enrollments::updated_at.eq(now),
+            ))
+            .get_result::<Enrollment>(conn)?;
+
+        diesel::insert_into(audit_logs::table)
+            .values(&audit.activity(
+                "grade.post",
+                "enrollment",
+                Some(after.id),
+                Some(json!({ "before": before, "after": after })),
+            ))
+            .execute(conn)?;
+
+        posted.push(after);
+    }
+
+    Ok(posted)
+}
+
+/// Credit and quality-point totals for a set of graded enrollments. Only
+/// grades with points count toward `gpa_credits`; `gpa` is `None` until
+/// there is at least one.
+#[derive(Debug, Default, Serialize)]
+pub struct GpaTotals {
+    pub gpa: Option<f64>,
+    pub quality_points: f64,
+    pub gpa_credits: i32,
+    pub credits_earned: i32,
+}
+
+impl GpaTotals {
+    pub(crate) fn add(&mut self, credits: i32, points: Option<f64>, credit_earned: bool) {
+        if let Some(points) = points {
+            self.quality_points += points * f64::from(credits);
+            self.gpa_credits += credits;
+        }
+        if credit_earned {
+            self.credits_earned += credits;
+        }
+    }
+
+    pub(crate) fn finish(mut self) -> Self {
+        let round = |value: f64| (value * 100.0).round() / 100.0;
+        self.gpa = (self.gpa_credits > 0)
+            .then(|| round(self.quality_points / f64::from(self.gpa_credits)));
+        self.quality_points = round(self.quality_points);
+        self
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct TermGpa {
+    pub term_id: i32,
+    pub term_code: String,
+    pub term_name: String,
+    #[serde(flatten)]
+    pub totals: GpaTotals,
+}
+
+#[derive(Debug, Serialize)]
+pub struct StudentGpa {
+    pub student_id: i32,
+    #[serde(flatten)]
+    pub cumulative: GpaTotals,
+    pub terms: Vec<TermGpa>,
+}
+
+/// Term-by-term and cumulative GPA over the student's graded enrollments.
+pub fn student_gpa(conn: &mut PgConnection, student_id: i32) -> QueryResult<StudentGpa> {
+    let rows = enrollments::table
+        .inner_join(
+            sections::table
+                .inner_join(terms::table)
+                .inner_join(courses::table),
+        )
+        .filter(enrollments::student_id.eq(student_id))
+        .filter(enrollments::status.eq(Enrollment::ENROLLED))
+        .filter(enrollments::grade.is_not_null())
+        .order((terms::start_date.asc(), terms::id.asc()))
+        .select((
+            terms::id,
+            terms::code,
+            terms::name,
+            courses::credits,
+            enrollments::grade_points,
+            enrollments::credit_earned,
+        ))
+        .load::<GradedRow>(conn)?;
+
+    Ok(summarize(student_id, rows))
+}
+
+/// Term id, code and name, course credits, grade points and whether credit
+/// was earned, for one graded enrollment.
+type GradedRow = (i32, String, String, i32, Option<f64>, bool);
+
+/// Totals graded enrollments, ordered by term, per term and overall.
+fn summarize(student_id: i32, rows: Vec<GradedRow>) -> StudentGpa {
+    let mut cumulative = GpaTotals::default();
+    let mut terms: Vec<TermGpa> = Vec::new();
+    for (term_id, term_code, term_name, credits, points, credit_earned) in rows {
+        cumulative.add(credits, points, credit_earned);
+        match terms.last_mut() {
+            Some(term) if term.term_id == term_id => {
+                term.totals.add(credits, points, credit_earned)
+            }
+            _ => {
+                let mut totals = GpaTotals::default();
+                totals.add(credits, points, credit_earned);
+                terms.push(TermGpa {
+                    term_id,
+                    term_code,
+                    term_name,
+                    totals,
+                });
+            }
+        }
+    }
+
+    StudentGpa {
+        student_id,
+        cumulative: cumulative.finish(),
+        terms: terms
+            .into_iter()
+            .map(|term| TermGpa {
+                totals: term.totals.finish(),
+                ..term
+            })
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(term_id: i32, credits: i32, points: Option<f64>, credit_earned: bool) -> GradedRow {
+        let code = format!("T{}", term_id);
+        (term_id, code.clone(), code, credits, points, credit_earned)
+    }
+
+    #[test]
+    fn gpa_weights_points_by_credits() {
+        let mut totals = GpaTotals::default();
+        totals.add(3, Some(4.0), true);
+        totals.add(4, Some(3.0), true);
+        let totals = totals.finish();
+        assert_eq!(totals.quality_points, 24.0);
+        assert_eq!(totals.gpa_credits, 7);
+        assert_eq!(totals.credits_earned, 7);
+        assert_eq!(totals.gpa, Some(3.43));
+    }
+
+    #[test]
+    fn grades_without_points_count_for_credit_only() {
+        let mut totals = GpaTotals::default();
+        totals.add(3, Some(2.0), true);
+        totals.add(3, None, true);
+        totals.add(4, None, false);
+        let totals = totals.finish();
+        assert_eq!(totals.gpa, Some(2.0));
+        assert_eq!(totals.gpa_credits, 3);
+        assert_eq!(totals.credits_earned, 6);
+    }
+
+    #[test]
+    fn failing_grades_count_toward_gpa_but_earn_nothing() {
+        let mut totals = GpaTotals::default();
+        totals.add(3, Some(4.0), true);
+        totals.add(3, Some(0.0), false);
+        let totals = totals.finish();
+        assert_eq!(totals.gpa, Some(2.0));
+        assert_eq!(totals.gpa_credits, 6);
+        assert_eq!(totals.credits_earned, 3);
+    }
+
+    #[test]
+    fn no_gpa_without_graded_credits() {
+        let mut totals = GpaTotals::default();
+        totals.add(3, None, true);
+        let totals = totals.finish();
+        assert_eq!(totals.gpa, None);
+        assert_eq!(totals.credits_earned, 3);
+        assert_eq!(GpaTotals::default().finish().gpa, None);
+    }
+
+    #[test]
+    fn totals_are_kept_per_term_and_overall() {
+        let gpa = summarize(
+            7,
+            vec![
+                row(1, 3, Some(4.0), true),
+                row(1, 3, Some(3.0), true),
+                row(2, 4, Some(2.0), true),
+                row(2, 2, None, true),
+                row(3, 3, Some(0.0), false),
+            ],
+        );
+
+        assert_eq!(gpa.student_id, 7);
+        let terms: Vec<(i32, Option<f64>, i32, i32)> = gpa
+            .terms
+            .iter()
+            .map(|term| {
+                (
+                    term.term_id,
+                    term.totals.gpa,
+                    term.totals.gpa_credits,
+                    term.totals.credits_earned,
+                )
+            })
+            .collect();
+        assert_eq!(
+            terms,
+            vec![
+                (1, Some(3.5), 6, 6),
+                (2, Some(2.0), 4, 6),
+                (3, Some(0.0), 3, 0),
+            ]
+        );
+
+        assert_eq!(gpa.cumulative.quality_points, 29.0);
+        assert_eq!(gpa.cumulative.gpa_credits, 13);
+        assert_eq!(gpa.cumulative.credits_earned, 12);
+        assert_eq!(gpa.cumulative.gpa, Some(2.23));
+    }
+
+    #[test]
+    fn a_student_without_grades_has_no_terms() {
+        let gpa = summarize(7, Vec::new());
+        assert!(gpa.terms.is_empty());
+        assert_eq!(gpa.cumulative.gpa, None);
+        assert_eq!(gpa.cumulative.credits_earned, 0);
+    }
+}
@@ -0,0 +1,529 @@
+//! Lottery allocation for oversubscribed sections. Students rank lottery
+//! sections during the request phase; an administrator then runs the lottery
+//! for the term with a seed. Each student draws a ticket derived only from
+//! the seed and their id, scaled by their priority weight, and students are
+//! served in ticket order, one seat per round, each getting their best-ranked
+//! section that still has room and that they are eligible for. Requests for
+//! full sections land on the section's waitlist in draw order. The same
+//! seed, weights and requests always give the same allocation.
+
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashSet};
+
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use thiserror::Error;
+use validator::{Validate, ValidationError};
+
+use crate::models::lottery::{LotteryRequest, LotteryRun, NewLotteryRun};
+use crate::models::{AuditContext, Section, Student, Term};
+use crate::schema::{audit_logs, lottery_requests, lottery_runs, sections, students};
+use crate::services::registration::{self, RegistrationError};
+use crate::services::{credit_load, grades, requisites, waitlist};
+
+#[derive(Debug, Error)]
+pub enum LotteryError {
+    #[error("No sections in term {0} are waiting for a lottery")]
+    NothingToRun(String),
+
+    #[error(transparent)]
+    Registration(#[from] RegistrationError),
+
+    #[error("Database error: {0}")]
+    Database(#[from] diesel::result::Error),
+}
+
+/// Changes the odds for students with at least `min_credits` earned or in
+/// `cohort`. A student's weight is the highest that applies to them, or 1
+/// when none does, so a weight below 1 lowers the odds.
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+#[validate(schema(function = "validate_priority_weight"))]
+pub struct PriorityWeight {
+    #[validate(range(min = 0, max = 1000))]
+    pub min_credits: Option<i32>,
+    #[validate(length(min = 1, max = 50))]
+    pub cohort: Option<String>,
+    #[validate(range(min = 0.01, max = 100.0))]
+    pub weight: f64,
+}
+
+fn validate_priority_weight(weight: &PriorityWeight) -> Result<(), ValidationError> {
+    if weight.min_credits.is_some() != weight.cohort.is_some() {
+        return Ok(());
+    }
+    let mut error = ValidationError::new("priority_weight");
+    error.message = Some("a weight applies to either min_credits or a cohort".into());
+    Err(error)
+}
+
+impl PriorityWeight {
+    fn applies(&self, credits_earned: i32, cohort: Option<&str>) -> bool {
+        match (self.min_credits, &self.cohort) {
+            (Some(min_credits), _) => credits_earned >= min_credits,
+            (None, Some(weight_cohort)) => cohort == Some(weight_cohort.as_str()),
+            (None, None) => false,
+        }
+    }
+}
+
+/// The highest weight that applies to the student, or 1 if none does.
+fn student_weight(weights: &[PriorityWeight], credits_earned: i32, cohort: Option<&str>) -> f64 {
+    weights
+        .iter()
+        .filter(|weight| weight.applies(credits_earned, cohort))
+        .map(|weight| weight.weight)
+        .reduce(f64::max)
+        .unwrap_or(1.0)
+}
+
+#[derive(Debug, Serialize)]
+pub struct RequestOutcome {
+    pub section_id: i32,
+    pub rank: i32,
+    pub status: String,
+    /// Why a request was not allocated.
+    pub reason: Option<&'static str>,
+}
+
+/// One student's place in the draw and what became of their requests.
+#[derive(Debug, Serialize)]
+pub struct DrawEntry {
+    pub position: usize,
+    pub student_id: i32,
+    pub weight: f64,
+    pub ticket: f64,
+    pub requests: Vec<RequestOutcome>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SectionAllocation {
+    pub section_id: i32,
+    pub capacity: i32,
+    pub requested: usize,
+    pub enrolled: usize,
+    pub waitlisted: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LotteryReport {
+    pub term_code: String,
+    pub seed: i64,
+    pub weights: Vec<PriorityWeight>,
+    pub draw: Vec<DrawEntry>,
+    pub sections: Vec<SectionAllocation>,
+}
+
+/// SplitMix64. Kept here rather than taken from a crate so a seed gives the
+/// same draw regardless of dependency versions.
+fn splitmix64(value: u64) -> u64 {
+    let mut z = value.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// Weighted ticket in (0, 1); higher tickets are served first. Raising a
+/// uniform draw to `1 / weight` makes a student's chance of being ahead of
+/// another proportional to their weight.
+fn ticket(seed: i64, student_id: i32, weight: f64) -> f64 {
+    let bits = splitmix64(splitmix64(seed as u64) ^ student_id as u64);
+    let uniform = ((bits >> 11) as f64 + 0.5) / (1u64 << 53) as f64;
+    uniform.powf(1.0 / weight)
+}
+
+/// Draw order: highest ticket first, ties going to the lower student id.
+fn draw_order((ticket_a, student_a): (f64, i32), (ticket_b, student_b): (f64, i32)) -> Ordering {
+    ticket_b
+        .total_cmp(&ticket_a)
+        .then(student_a.cmp(&student_b))
+}
+
+struct Entrant {
+    student_id: i32,
+    weight: f64,
+    ticket: f64,
+    requests: Vec<(LotteryRequest, Section)>,
+    outcomes: Vec<Option<(&'static str, Option<&'static str>)>>,
+    won_courses: HashSet<i32>,
+    on_hold: bool,
+}
+
+impl Entrant {
+    fn settle(&mut self, index: usize, status: &'static str, reason: Option<&'static str>) {
+        self.outcomes[index] = Some((status, reason));
+    }
+}
+
+/// Why a registration check turned a request down, or the error to abort on.
+fn rejection(err: RegistrationError) -> Result<&'static str, LotteryError> {
+    match err {
+        RegistrationError::RequisitesNotMet(_) => Ok("requisites_not_met"),
+        RegistrationError::ScheduleConflict(_) => Ok("schedule_conflict"),
+        RegistrationError::CreditLimitExceeded { .. } => Ok("credit_limit"),
+        RegistrationError::SectionFull => Ok("section_full"),
+        other => Err(other.into()),
+    }
+}
+
+/// Tries to enroll the entrant in one of their requests, best rank first.
+/// Returns whether a seat was won.
+fn allocate_one(
+    conn: &mut PgConnection,
+    term: &Term,
+    entrant: &mut Entrant,
+    audit: &AuditContext,
+) -> Result<bool, LotteryError> {
+    for index in 0..entrant.requests.len() {
+        if entrant.outcomes[index].is_some() {
+            continue;
+        }
+        let section = entrant.requests[index].1.clone();
+        if entrant.won_courses.contains(&section.course_id) {
+            entrant.settle(
+                index,
+                LotteryRequest::UNSUCCESSFUL,
+                Some("allocated_other_section"),
+            );
+            continue;
+        }
+        if registration::enrolled_in_course(conn, entrant.student_id, &section)? {
+            entrant.settle(
+                index,
+                LotteryRequest::UNSUCCESSFUL,
+                Some("already_enrolled"),
+            );
+            continue;
+        }
+
+        let checked = requisites::ensure_met(conn, entrant.student_id, &section, term)
+            .and_then(|_| registration::ensure_no_conflicts(conn, entrant.student_id, &section))
+            .and_then(|_| credit_load::ensure_within_max(conn, entrant.student_id, &section, term))
+            .and_then(|_| registration::claim_seat(conn, section.id));
+        match checked {
+            Ok(()) => {
+                registration::insert_enrollment(conn, entrant.student_id, section.id, None, audit)?;
+                entrant.settle(index, LotteryRequest::ENROLLED, None);
+                entrant.won_courses.insert(section.course_id);
+                return Ok(true);
+            }
+            // Left open so it can go to the waitlist if nothing better
+            // turns up.
+            Err(RegistrationError::SectionFull) => continue,
+            Err(err) => {
+                let reason = rejection(err)?;
+                entrant.settle(index, LotteryRequest::UNSUCCESSFUL, Some(reason));
+            }
+        }
+    }
+    Ok(false)
+}
+
+/// Runs the lottery for every lottery section in the term and records the
+/// run. Expects to be called inside a transaction.
+pub fn run(
+    conn: &mut PgConnection,
+    term: &Term,
+    seed: i64,
+    weights: Vec<PriorityWeight>,
+    audit: &AuditContext,
+) -> Result<LotteryRun, LotteryError> {
+    let lottery_sections = sections::table
+        .filter(sections::term_id.eq(term.id))
+        .filter(sections::lottery.eq(true))
+        .order(sections::id.asc())
+        .for_update()
+        .load::<Section>(conn)?;
+    if lottery_sections.is_empty() {
+        return Err(LotteryError::NothingToRun(term.code.clone()));
+    }
+    let section_ids: Vec<i32> = lottery_sections.iter().map(|section| section.id).collect();
+
+    let requests = lottery_requests::table
+        .filter(lottery_requests::section_id.eq_any(&section_ids))
+        .filter(lottery_requests::status.eq(LotteryRequest::PENDING))
+        .order((
+            lottery_requests::student_id.asc(),
+            lottery_requests::rank.asc(),
+        ))
+        .load::<LotteryRequest>(conn)?;
+
+    let mut by_student: BTreeMap<i32, Vec<(LotteryRequest, Section)>> = BTreeMap::new();
+    for request in requests {
+        let section = lottery_sections
+            .iter()
+            .find(|section| section.id == request.section_id)
+            .cloned()
+            .expect("requests are loaded for lottery sections only");
+        by_student
+            .entry(request.student_id)
+            .or_default()
+            .push((request, section));
+    }
+
+    let mut entrants = Vec::with_capacity(by_student.len());
+    for (student_id, requests) in by_student {
+        let student = students::table.find(student_id).first::<Student>(conn)?;
+        let credits_earned = grades::student_gpa(conn, student_id)?
+            .cumulative
+            .credits_earned;
+        let weight = student_weight(&weights, credits_earned, student.cohort.as_deref());
+        let on_hold = match registration::ensure_no_holds(conn, student_id) {
+            Ok(()) => false,
+            Err(RegistrationError::OnHold(_)) => true,
+            Err(err) => return Err(err.into()),
+        };
+
+        entrants.push(Entrant {
+            student_id,
+            weight,
+            ticket: ticket(seed, student_id, weight),
+            outcomes: vec![None; requests.len()],
+            requests,
+            won_courses: HashSet::new(),
+            on_hold,
+        });
+    }
+    entrants.sort_by(|a, b| draw_order((a.ticket, a.student_id), (b.ticket, b.student_id)));
+
+    for entrant in entrants.iter_mut().filter(|entrant| entrant.on_hold) {
+        for index in 0..entrant.requests.len() {
+            entrant.settle(
+                index,
+                LotteryRequest::UNSUCCESSFUL,
+                Some("registration_hold"),
+            );
+        }
+    }
+
+    // One seat per student per round, so a lucky draw cannot sweep every
+    // popular section before others get their first choice.
+    loop {
+        let mut allocated = false;
+        for entrant in entrants.iter_mut() {
+            allocated |= allocate_one(conn, term, entrant, audit)?;
+        }
+        if !allocated {
+            break;
+        }
+    }
+
+    // Whatever is still open was turned away by full sections.
+    for entrant in entrants.iter_mut() {
+        for index in 0..entrant.requests.len() {
+            if entrant.outcomes[index].is_some() {
+                continue;
+            }
+            let section_id = entrant.requests[index].1.id;
+            let course_id = entrant.requests[index].1.course_id;
+            if entrant.won_courses.contains(&course_id) {
+                entrant.settle(
+                    index,
+                    LotteryRequest::UNSUCCESSFUL,
+                    Some("allocated_other_section"),
+                );
+                continue;
+            }
+            match waitlist::enqueue(conn, entrant.student_id, section_id, true, audit) {
+                Ok(_) | Err(RegistrationError::AlreadyWaitlisted) => {
+                    entrant.settle(index, LotteryRequest::WAITLISTED, Some("section_full"))
+                }
+                Err(err) => return Err(err.into()),
+            }
+        }
+    }
+
+    let draw: Vec<DrawEntry> = entrants
+        .iter()
+        .enumerate()
+        .map(|(position, entrant)| DrawEntry {
+            position: position + 1,
+            student_id: entrant.student_id,
+            weight: entrant.weight,
+            ticket: entrant.ticket,
+            requests: entrant
+                .requests
+                .iter()
+                .zip(&entrant.outcomes)
+                .map(|((request, _), outcome)| {
+                    let (status, reason) = outcome.expect("every request is settled");
+                    RequestOutcome {
+                        section_id: request.section_id,
+                        rank: request.rank,
+                        status: status.to_string(),
+                        reason,
+                    }
+                })
+                .collect(),
+        })
+        .collect();
+
+    let section_summaries = lottery_sections
+        .iter()
+        .map(|section| {
+            let outcomes: Vec<&RequestOutcome> = draw
+                .iter()
+                .flat_map(|entry| &entry.requests)
+                .filter(|outcome| outcome.section_id == section.id)
+                .collect();
+            let count = |status: &str| {
+                outcomes
+                    .iter()
+                    .filter(|outcome| outcome.status == status)
+                    .count()
+            };
+            SectionAllocation {
+                section_id: section.id,
+                capacity: section.capacity,
+                requested: outcomes.len(),
+                enrolled: count(LotteryRequest::ENROLLED),
+                waitlisted: count(LotteryRequest::WAITLISTED),
+            }
+        })
+        .collect();
+
+    let report = LotteryReport {
+        term_code: term.code.clone(),
+        seed,
+        weights,
+        draw,
+        sections: section_summaries,
+    };
+
+    let lottery_run = diesel::insert_into(lottery_runs::table)
+        .values(&NewLotteryRun {
+            term_id: term.id,
+            seed,
+            weights: json!(report.weights),
+            report: json!(report),
+            run_by: audit.user_id,
+        })
+        .get_result::<LotteryRun>(conn)?;
+
+    for entrant in &entrants {
+        for ((request, _), outcome) in entrant.requests.iter().zip(&entrant.outcomes) {
+            let (status, _) = outcome.expect("every request is settled");
+            diesel::update(lottery_requests::table.find(request.id))
+                .set((
+                    lottery_requests::status.eq(status),
+                    lottery_requests::lottery_run_id.eq(Some(lottery_run.id)),
+                ))
+                .execute(conn)?;
+        }
+    }
+
+    diesel::update(sections::table.filter(sections::id.eq_any(&section_ids)))
+        .set(sections::lottery.eq(false))
+        .execute(conn)?;
+
+    diesel::insert_into(audit_logs::table)
+        .values(&audit.activity(
+            "lottery.run",
+            "lottery_run",
+            Some(lottery_run.id),
+            Some(json!({
+                "term": term.code,
+                "seed": seed,
+                "sections": section_ids,
+                "students": entrants.len(),
+            })),
+        ))
+        .execute(conn)?;
+
+    Ok(lottery_run)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn weight(min_credits: Option<i32>, cohort: Option<&str>, weight: f64) -> PriorityWeight {
+        PriorityWeight {
+            min_credits,
+            cohort: cohort.map(str::to_string),
+            weight,
+        }
+    }
+
+    fn draw(seed: i64, students: &[(i32, f64)]) -> Vec<i32> {
+        let mut tickets: Vec<(f64, i32)> = students
+            .iter()
+            .map(|&(student_id, weight)| (ticket(seed, student_id, weight), student_id))
+            .collect();
+        tickets.sort_by(|&a, &b| draw_order(a, b));
+        tickets
+            .into_iter()
+            .map(|(_, student_id)| student_id)
+            .collect()
+    }
+
+    #[test]
+    fn tickets_depend_only_on_seed_student_and_weight() {
+        for student_id in 1..100 {
+            let first = ticket(42, student_id, 1.0);
+            assert_eq!(first, ticket(42, student_id, 1.0));
+            assert!(first > 0.0 && first < 1.0);
+        }
+        assert_ne!(ticket(42, 7, 1.0), ticket(43, 7, 1.0));
+        assert_ne!(ticket(42, 7, 1.0), ticket(42, 8, 1.0));
+    }
+
+    #[test]
+    fn higher_weights_raise_the_ticket() {
+        for student_id in 1..100 {
+            assert!(ticket(9, student_id, 0.5) < ticket(9, student_id, 1.0));
+            assert!(ticket(9, student_id, 1.0) < ticket(9, student_id, 3.0));
+        }
+    }
+
+    #[test]
+    fn weights_make_winning_proportionally_more_likely() {
+        let seeds = 20_000;
+        let ahead = (0..seeds)
+            .filter(|&seed| ticket(seed, 1, 2.0) > ticket(seed, 2, 1.0))
+            .count();
+        let share = ahead as f64 / seeds as f64;
+        assert!((share - 2.0 / 3.0).abs() < 0.02, "share was {}", share);
+    }
+
+    #[test]
+    fn the_same_seed_gives_the_same_order() {
+        let students: Vec<(i32, f64)> = (1..=50)
+            .map(|id| (id, if id % 5 == 0 { 2.0 } else { 1.0 }))
+            .collect();
+        let order = draw(2024, &students);
+        assert_eq!(order, draw(2024, &students));
+        assert_ne!(order, draw(2025, &students));
+
+        let mut reversed = students.clone();
+        reversed.reverse();
+        assert_eq!(order, draw(2024, &reversed));
+    }
+
+    #[test]
+    fn the_draw_for_a_seed_does_not_change_between_builds() {
+        let students: Vec<(i32, f64)> = (1..=8).map(|id| (id, 1.0)).collect();
+        assert_eq!(draw(1, &students), vec![1, 4, 2, 5, 3, 7, 6, 8]);
+    }
+
+    #[test]
+    fn equal_tickets_go_to_the_lower_student_id() {
+        let mut tickets = vec![(0.5, 9), (0.9, 4), (0.5, 3)];
+        tickets.sort_by(|&a, &b| draw_order(a, b));
+        assert_eq!(tickets, vec![(0.9, 4), (0.5, 3), (0.5, 9)]);
+    }
+
+    #[test]
+    fn students_take_the_highest_weight_that_applies() {
+        let weights = vec![
+            weight(Some(90), None, 2.0),
+            weight(None, Some("honors"), 3.0),
+            weight(None, Some("probation"), 0.5),
+        ];
+        assert_eq!(student_weight(&weights, 95, Some("honors")), 3.0);
+        assert_eq!(student_weight(&weights, 95, None), 2.0);
+        assert_eq!(student_weight(&weights, 10, None), 1.0);
+        assert_eq!(student_weight(&weights, 10, Some("probation")), 0.5);
+        assert_eq!(student_weight(&[], 10, None), 1.0);
+    }
+}
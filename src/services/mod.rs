@@ -0,0 +1,14 @@
+pub mod appointments;
+pub mod cart;
+pub mod credit_load;
+pub mod degree_audit;
+pub mod grades;
+pub mod lottery;
+pub mod registration;
+pub mod requisites;
+pub mod room_utilization;
+pub mod schedules;
+pub mod timetable;
+pub mod tokens;
+pub mod transcript;
+pub mod waitlist;
@@ -0,0 +1,451 @@
+//! Enrollment rules shared by every handler that changes a student's
+//! schedule. All functions expect to run inside a transaction opened by the
+//! caller so multi-step operations commit or roll back as a unit.
+
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use diesel::result::{DatabaseErrorKind, Error as DieselError};
+use serde_json::json;
+use thiserror::Error;
+
+use crate::models::{
+    AuditContext, Enrollment, Hold, HoldAction, NewEnrollment, Section, Student, Term,
+};
+use crate::schema::{audit_logs, enrollments, sections, students, terms};
+use crate::services::appointments;
+use crate::services::credit_load;
+use crate::services::requisites::{self, RequisiteReport};
+use crate::services::timetable::{self, ScheduleConflict};
+use crate::services::waitlist;
+
+#[derive(Debug, Error)]
+pub enum RegistrationError {
+    #[error("Student not found")]
+    StudentNotFound,
+
+    #[error("Section not found")]
+    SectionNotFound,
+
+    #[error("Course not found")]
+    CourseNotFound,
+
+    #[error("Term not found")]
+    TermNotFound,
+
+    #[error("Enrollment not found")]
+    EnrollmentNotFound,
+
+    #[error("Registration for term {0} is not open")]
+    RegistrationClosed(String),
+
+    #[error(
+        "Registration for term {term} opens for this student at {}",
+        .opens_at.to_rfc3339()
+    )]
+    BeforeAppointment {
+        term: String,
+        opens_at: DateTime<Utc>,
+    },
+
+    #[error("The withdrawal deadline for term {0} has passed")]
+    WithdrawalDeadlinePassed(String),
+
+    #[error("The add/drop deadline for term {0} has passed")]
+    AddDropDeadlinePassed(String),
+
+    #[error("Both sections of a swap must be in the same term")]
+    SwapAcrossTerms,
+
+    #[error("Student has active holds that block registration")]
+    OnHold(Vec<Hold>),
+
+    #[error("Seats in this section are allocated by lottery; submit a ranked request instead")]
+    LotteryPending,
+
+    #[error("Student is already enrolled in a section of this course")]
+    AlreadyEnrolled,
+
+    #[error("Section is full")]
+    SectionFull,
+
+    #[error("Section conflicts with the student's timetable")]
+    ScheduleConflict(Vec<ScheduleConflict>),
+
+    #[error(
+        "Enrolling would bring the student to {credits} credits in term {term}, above the \
+         maximum of {max}; an approved overload is required"
+    )]
+    CreditLimitExceeded {
+        term: String,
+        credits: i32,
+        max: i32,
+    },
+
+    #[error(
+        "Dropping would leave the student with {credits} credits in term {term}, below the \
+         minimum of {min}"
+    )]
+    BelowMinimumLoad {
+        term: String,
+        credits: i32,
+        min: i32,
+    },
+
+    #[error("Requirements for {} are not met", .0.course_code)]
+    RequisitesNotMet(Box<RequisiteReport>),
+
+    #[error("Student is already on the waitlist for this section")]
+    AlreadyWaitlisted,
+
+    #[error("Waitlist entry not found")]
+    WaitlistEntryNotFound,
+
+    #[error("The waitlist offer has expired")]
+    OfferExpired,
+
+    #[error("Database error: {0}")]
+    Database(#[from] DieselError),
+}
+
+/// Checks an administrator may waive when enrolling a student.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Overrides {
+    pub schedule_conflicts: bool,
+}
+
+pub fn find_student(
+    conn: &mut PgConnection,
+    student_id: i32,
+) -> Result<Student, RegistrationError> {
+    students::table
+        .find(student_id)
+        .first::<Student>(conn)
+        .optional()?
+        .ok_or(RegistrationError::StudentNotFound)
+}
+
+pub(crate) fn find_section_with_term(
+    conn: &mut PgConnection,
+    section_id: i32,
+) -> Result<(Section, Term), RegistrationError> {
+    sections::table
+        .inner_join(terms::table)
+        .filter(sections::id.eq(section_id))
+        .first::<(Section, Term)>(conn)
+        .optional()?
+        .ok_or(RegistrationError::SectionNotFound)
+}
+
+/// Rejects the student while any hold that blocks registration is in effect.
+pub(crate) fn ensure_no_holds(
+    conn: &mut PgConnection,
+    student_id: i32,
+) -> Result<(), RegistrationError> {
+    let holds = Hold::active_for(
+        conn,
+        student_id,
+        HoldAction::Registration,
+        Utc::now().date_naive(),
+    )?;
+    if !holds.is_empty() {
+        return Err(RegistrationError::OnHold(holds));
+    }
+    Ok(())
+}
+
+/// Takes one seat, or fails if none are left. The capacity guard is evaluated
+/// under the row lock taken by the UPDATE, so two concurrent requests for the
+/// last seat cannot both succeed. Seats reserved for waitlist offers count as
+/// taken.
+pub(crate) fn claim_seat(
+    conn: &mut PgConnection,
+    section_id: i32,
+) -> Result<(), RegistrationError> {
+    let updated = diesel::update(
+        sections::table
+            .filter(sections::id.eq(section_id))
+            .filter((sections::enrolled_count + sections::reserved_count).lt(sections::capacity)),
+    )
+    .set(sections::enrolled_count.eq(sections::enrolled_count + 1))
+    .execute(conn)?;
+
+    if updated == 0 {
+        return Err(RegistrationError::SectionFull);
+    }
+    Ok(())
+}
+
+fn release_seat(conn: &mut PgConnection, section_id: i32) -> QueryResult<()> {
+    diesel::update(
+        sections::table
+            .filter(sections::id.eq(section_id))
+            .filter(sections::enrolled_count.gt(0)),
+    )
+    .set(sections::enrolled_count.eq(sections::enrolled_count - 1))
+    .execute(conn)?;
+    Ok(())
+}
+
+/// Whether the student already holds a seat in any section of the course.
+/// Locks the student row first, as `credit_load::ensure_within_max` does, so
+/// two adds for the same course cannot both pass the check.
+pub(crate) fn enrolled_in_course(
+    conn: &mut PgConnection,
+    student_id: i32,
+    section: &Section,
+) -> QueryResult<bool> {
+    students::table
+        .find(student_id)
+        .select(students::id)
+        .for_update()
+        .execute(conn)?;
+
+    let existing: i64 = enrollments::table
+        .inner_join(sections::table)
+        .filter(enrollments::student_id.eq(student_id))
+        .filter(enrollments::status.eq(Enrollment::ENROLLED))
+        .filter(sections::term_id.eq(section.term_id))
+        .filter(sections::course_id.eq(section.course_id))
+        .count()
+        .get_result(conn)?;
+    Ok(existing > 0)
+}
+
+/// Rejects the section if it overlaps the student's timetable for the term.
+pub(crate) fn ensure_no_conflicts(
+    conn: &mut PgConnection,
+    student_id: i32,
+    section: &Section,
+) -> Result<(), RegistrationError> {
+    let conflicts = timetable::find_conflicts(conn, student_id, section)?;
+    if !conflicts.is_empty() {
+        return Err(RegistrationError::ScheduleConflict(conflicts));
+    }
+    Ok(())
+}
+
+/// Enrolls a student in a section, writing an audit entry for the change.
+/// Waived checks are recorded in that entry.
+pub fn enroll(
+    conn: &mut PgConnection,
+    student_id: i32,
+    section_id: i32,
+    overrides: Overrides,
+    audit: &AuditContext,
+) -> Result<Enrollment, RegistrationError> {
+    let (section, waived) = check_and_claim(conn, student_id, section_id, overrides)?;
+    insert_enrollment(conn, student_id, section.id, Some(waived), audit)
+}
+
+/// Runs every check for adding the section and takes a seat in it. Returns
+/// the section and the checks an administrator waived.
+fn check_and_claim(
+    conn: &mut PgConnection,
+    student_id: i32,
+    section_id: i32,
+    overrides: Overrides,
+) -> Result<(Section, serde_json::Map<String, serde_json::Value>), RegistrationError> {
+    let student = find_student(conn, student_id)?;
+    ensure_no_holds(conn, student_id)?;
+    let (section, term) = find_section_with_term(conn, section_id)?;
+    if section.lottery {
+        return Err(RegistrationError::LotteryPending);
+    }
+
+    appointments::ensure_open(conn, &student, &term)?;
+    if enrolled_in_course(conn, student_id, &section)? {
+        return Err(RegistrationError::AlreadyEnrolled);
+    }
+    requisites::ensure_met(conn, student_id, &section, &term)?;
+
+    let mut waived = serde_json::Map::new();
+    let conflicts = timetable::find_conflicts(conn, student_id, &section)?;
+    if !conflicts.is_empty() {
+        if !overrides.schedule_conflicts {
+            return Err(RegistrationError::ScheduleConflict(conflicts));
+        }
+        waived.insert("schedule_conflicts".to_string(), json!(conflicts));
+    }
+    credit_load::ensure_within_max(conn, student_id, &section, &term)?;
+
+    claim_seat(conn, section.id)?;
+    Ok((section, waived))
+}
+
+fn insert_enrollment_row(
+    conn: &mut PgConnection,
+    student_id: i32,
+    section_id: i32,
+    audit: &AuditContext,
+) -> Result<Enrollment, RegistrationError> {
+    diesel::insert_into(enrollments::table)
+        .values(&NewEnrollment {
+            student_id,
+            section_id,
+            status: Enrollment::ENROLLED.to_string(),
+            created_by: audit.user_id,
+            updated_by: audit.user_id,
+        })
+        .get_result::<Enrollment>(conn)
+        .map_err(|e| match e {
+            DieselError::DatabaseError(DatabaseErrorKind::UniqueViolation, _) => {
+                RegistrationError::AlreadyEnrolled
+            }
+            other => RegistrationError::Database(other),
+        })
+}
+
+/// Records an enrollment for a seat the caller has already claimed, noting
+/// any checks an administrator waived.
+pub(crate) fn insert_enrollment(
+    conn: &mut PgConnection,
+    student_id: i32,
+    section_id: i32,
+    waived: Option<serde_json::Map<String, serde_json::Value>>,
+    audit: &AuditContext,
+) -> Result<Enrollment, RegistrationError> {
+    let enrollment = insert_enrollment_row(conn, student_id, section_id, audit)?;
+
+    let mut details = json!({ "before": null, "after": enrollment });
+    if let Some(waived) = waived.filter(|waived| !waived.is_empty()) {
+        details["overrides"] = waived.into();
+    }
+    diesel::insert_into(audit_logs::table)
+        .values(&audit.activity(
+            "enrollment.create",
+            "enrollment",
+            Some(enrollment.id),
+            Some(details),
+        ))
+        .execute(conn)?;
+
+    Ok(enrollment)
+}
+
+fn find_active_enrollment(
+    conn: &mut PgConnection,
+    student_id: i32,
+    enrollment_id: i32,
+) -> Result<Enrollment, RegistrationError> {
+    enrollments::table
+        .filter(enrollments::id.eq(enrollment_id))
+        .filter(enrollments::student_id.eq(student_id))
+        .for_update()
+        .first::<Enrollment>(conn)
+        .optional()?
+        .filter(Enrollment::is_active)
+        .ok_or(RegistrationError::EnrollmentNotFound)
+}
+
+/// Moves the enrollment to `status` and frees its seat.
+fn vacate(
+    conn: &mut PgConnection,
+    enrollment: &Enrollment,
+    status: &str,
+    audit: &AuditContext,
+) -> Result<Enrollment, RegistrationError> {
+    let now = Utc::now();
+    let after = diesel::update(enrollments::table.find(enrollment.id))
+        .set((
+            enrollments::status.eq(status),
+            enrollments::dropped_at.eq(Some(now)),
+            enrollments::updated_by.eq(audit.user_id),
+            enrollments::updated_at.eq(now),
+        ))
+        .get_result::<Enrollment>(conn)?;
+
+    release_seat(conn, enrollment.section_id)?;
+    Ok(after)
+}
+
+/// Gives up a student's seat. Before the add/drop deadline the enrollment is
+/// marked dropped; after it, and up to the withdrawal deadline, withdrawn. The
+/// freed seat goes straight to the section's waitlist.
+pub fn drop_enrollment(
+    conn: &mut PgConnection,
+    student_id: i32,
+    enrollment_id: i32,
+    audit: &AuditContext,
+) -> Result<Enrollment, RegistrationError> {
+    let before = find_active_enrollment(conn, student_id, enrollment_id)?;
+    let (section, term) = find_section_with_term(conn, before.section_id)?;
+    let today = Utc::now().date_naive();
+
+    let status = if today <= term.add_drop_deadline {
+        Enrollment::DROPPED
+    } else if today <= term.withdrawal_deadline {
+        Enrollment::WITHDRAWN
+    } else {
+        return Err(RegistrationError::WithdrawalDeadlinePassed(term.code));
+    };
+    credit_load::ensure_above_min(conn, student_id, &section, &term)?;
+
+    let after = vacate(conn, &before, status, audit)?;
+
+    diesel::insert_into(audit_logs::table)
+        .values(&audit.activity(
+            "enrollment.drop",
+            "enrollment",
+            Some(after.id),
+            Some(json!({ "before": before, "after": after })),
+        ))
+        .execute(conn)?;
+
+    waitlist::promote(conn, section.id, audit)?;
+
+    Ok(after)
+}
+
+/// Drops one enrollment and adds another section in its place, recorded as
+/// a single audit entry. The new section goes through every enrollment
+/// check with the old seat already given up, so it may be another section of
+/// the same course or one that clashed only with the old section. Any
+/// failure leaves the caller's transaction to roll back with the student
+/// still in the old section. Swaps are only possible up to the add/drop
+/// deadline.
+pub fn swap(
+    conn: &mut PgConnection,
+    student_id: i32,
+    enrollment_id: i32,
+    section_id: i32,
+    overrides: Overrides,
+    audit: &AuditContext,
+) -> Result<(Enrollment, Enrollment), RegistrationError> {
+    let before = find_active_enrollment(conn, student_id, enrollment_id)?;
+    let (old_section, term) = find_section_with_term(conn, before.section_id)?;
+    let (new_section, _) = find_section_with_term(conn, section_id)?;
+    if new_section.term_id != term.id {
+        return Err(RegistrationError::SwapAcrossTerms);
+    }
+    if new_section.id == old_section.id {
+        return Err(RegistrationError::AlreadyEnrolled);
+    }
+    if Utc::now().date_naive() > term.add_drop_deadline {
+        return Err(RegistrationError::AddDropDeadlinePassed(term.code));
+    }
+    credit_load::ensure_swap_above_min(conn, student_id, &old_section, &new_section, &term)?;
+
+    let dropped = vacate(conn, &before, Enrollment::DROPPED, audit)?;
+    let (new_section, waived) = check_and_claim(conn, student_id, section_id, overrides)?;
+    let added = insert_enrollment_row(conn, student_id, new_section.id, audit)?;
+
+    let mut details = json!({
+        "dropped": { "before": before, "after": dropped },
+        "added": { "before": null, "after": added },
+    });
+    if !waived.is_empty() {
+        details["overrides"] = waived.into();
+    }
+    diesel::insert_into(audit_logs::table)
+        .values(&audit.activity(
+            "enrollment.swap",
+            "enrollment",
+            Some(added.id),
+            Some(details),
+        ))
+        .execute(conn)?;
+
+    waitlist::promote(conn, old_section.id, audit)?;
+
+    Ok((dropped, added))
+}
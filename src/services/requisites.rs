@@ -0,0 +1,333 @@
+//! Prerequisite and corequisite checks. A prerequisite must be completed, or
+//! be in progress in an earlier term; a corequisite may also be taken in the
+//! same term. Courses still awaiting a grade count provisionally.
+
+use std::collections::HashMap;
+
+use chrono::{NaiveDate, Utc};
+use diesel::prelude::*;
+use serde::Serialize;
+
+use crate::models::{Course, Enrollment, GradeScaleDetail, Requirement, Section, Term};
+use crate::schema::{courses, enrollments, sections, terms};
+use crate::services::registration::RegistrationError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ClauseStatus {
+    Completed,
+    InProgress,
+    Concurrent,
+    GradePending,
+    GradeTooLow,
+    Missing,
+    Invalid,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Rule {
+    Prerequisite,
+    Corequisite,
+}
+
+/// Outcome of one clause of an expression. Groups carry their operator and
+/// the outcome of each member; single courses carry the student's standing.
+#[derive(Debug, Serialize)]
+pub struct ClauseResult {
+    pub clause: String,
+    pub satisfied: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub operator: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<ClauseStatus>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub grade: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub clauses: Vec<ClauseResult>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RequisiteReport {
+    pub student_id: i32,
+    pub course_code: String,
+    pub term_code: String,
+    pub satisfied: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prerequisites: Option<ClauseResult>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub corequisites: Option<ClauseResult>,
+}
+
+struct Attempt {
+    status: ClauseStatus,
+    grade: Option<String>,
+    grade_points: Option<f64>,
+    credit_earned: bool,
+}
+
+/// A student's enrollments up to and including the target term, keyed by
+/// course code, read against the default grade scale.
+struct Coursework {
+    attempts: HashMap<String, Vec<Attempt>>,
+    scale: Option<GradeScaleDetail>,
+}
+
+impl Coursework {
+    fn load(
+        conn: &mut PgConnection,
+        student_id: i32,
+        term: &Term,
+        today: NaiveDate,
+    ) -> QueryResult<Coursework> {
+        let rows = enrollments::table
+            .inner_join(
+                sections::table
+                    .inner_join(terms::table)
+                    .inner_join(courses::table),
+            )
+            .filter(enrollments::student_id.eq(student_id))
+            .filter(enrollments::status.eq(Enrollment::ENROLLED))
+            .filter(terms::start_date.le(term.start_date))
+            .select((
+                courses::code,
+                terms::id,
+                terms::end_date,
+                enrollments::grade,
+                enrollments::grade_points,
+                enrollments::credit_earned,
+            ))
+            .load::<(String, i32, NaiveDate, Option<String>, Option<f64>, bool)>(conn)?;
+
+        let mut attempts: HashMap<String, Vec<Attempt>> = HashMap::new();
+        for (code, term_id, end_date, grade, grade_points, credit_earned) in rows {
+            let status = if term_id == term.id {
+                ClauseStatus::Concurrent
+            } else if end_date < today {
+                ClauseStatus::Completed
+            } else {
+                ClauseStatus::InProgress
+            };
+            attempts.entry(code).or_default().push(Attempt {
+                status,
+                grade,
+                grade_points,
+                credit_earned,
+            });
+        }
+
+        Ok(Coursework {
+            attempts,
+            scale: GradeScaleDetail::load_default(conn)?,
+        })
+    }
+
+    /// Whether a completed attempt's grade satisfies the clause: at least the
+    /// minimum's points when one is set, otherwise any grade that earns
+    /// credit.
+    fn grade_status(
+        &self,
+        attempt: &Attempt,
+        grade: &str,
+        min_grade: Option<&str>,
+    ) -> ClauseStatus {
+        let entry = self.scale.as_ref().and_then(|scale| scale.entry(grade));
+        if entry.is_some_and(|entry| !entry.is_final) {
+            return ClauseStatus::GradePending;
+        }
+
+        let meets = match min_grade {
+            Some(min_grade) => {
+                let required = self
+                    .scale
+                    .as_ref()
+                    .and_then(|scale| scale.entry(min_grade))
+                    .and_then(|entry| entry.points);
+                matches!(
+                    (attempt.grade_points, required),
+                    (Some(points), Some(required)) if points >= required
+                )
+            }
+            None => attempt.credit_earned,
+        };
+
+        if meets {
+            ClauseStatus::Completed
+        } else {
+            ClauseStatus::GradeTooLow
+        }
+    }
+
+    /// The student's best standing against a single course clause.
+    fn check(
+        &self,
+        code: &str,
+        min_grade: Option<&str>,
+        rule: Rule,
+    ) -> (bool, ClauseStatus, Option<String>) {
+        let outcomes = self
+            .attempts
+            .get(code)
+            .into_iter()
+            .flatten()
+            .map(|attempt| {
+                let status = match (attempt.status, attempt.grade.as_deref()) {
+                    (ClauseStatus::Completed, Some(grade)) => {
+                        self.grade_status(attempt, grade, min_grade)
+                    }
+                    (ClauseStatus::Completed, None) => ClauseStatus::GradePending,
+                    (status, _) => status,
+                };
+                let satisfied = match status {
+                    ClauseStatus::Concurrent => rule == Rule::Corequisite,
+                    ClauseStatus::GradeTooLow => false,
+                    _ => true,
+                };
+                (satisfied, status, attempt.grade.clone())
+            });
+
+        let mut best = (false, ClauseStatus::Missing, None);
+        for outcome in outcomes {
+            if outcome.0 {
+                return outcome;
+            }
+            if best.1 == ClauseStatus::Missing {
+                best = outcome;
+            }
+        }
+        best
+    }
+}
+
+fn evaluate_requirement(
+    requirement: &Requirement,
+    coursework: &Coursework,
+    rule: Rule,
+) -> ClauseResult {
+    let (operator, members) = match requirement {
+        Requirement::Course { code, min_grade } => {
+            let (satisfied, status, grade) = coursework.check(code, min_grade.as_deref(), rule);
+            return ClauseResult {
+                clause: requirement.to_string(),
+                satisfied,
+                operator: None,
+                status: Some(status),
+                grade,
+                clauses: Vec::new(),
+            };
+        }
+        Requirement::All(members) => ("AND", members),
+        Requirement::Any(members) => ("OR", members),
+    };
+
+    let clauses: Vec<ClauseResult> = members
+        .iter()
+        .map(|member| evaluate_requirement(member, coursework, rule))
+        .collect();
+    let satisfied = if operator == "AND" {
+        clauses.iter().all(|clause| clause.satisfied)
+    } else {
+        clauses.iter().any(|clause| clause.satisfied)
+    };
+
+    ClauseResult {
+        clause: requirement.to_string(),
+        satisfied,
+        operator: Some(operator),
+        status: None,
+        grade: None,
+        clauses,
+    }
+}
+
+fn evaluate_expression(expression: &str, coursework: &Coursework, rule: Rule) -> ClauseResult {
+    match Requirement::parse(expression) {
+        Ok(requirement) => evaluate_requirement(&requirement, coursework, rule),
+        Err(err) => {
+            log::error!("Stored requisite {:?} is invalid: {}", expression, err);
+            ClauseResult {
+                clause: expression.to_string(),
+                satisfied: false,
+                operator: None,
+                status: Some(ClauseStatus::Invalid),
+                grade: None,
+                clauses: Vec::new(),
+            }
+        }
+    }
+}
+
+/// Minimum grades in the expression that the default grade scale cannot
+/// compare against, because they are missing from it or carry no points.
+/// Unparseable expressions are left to validation.
+pub fn unknown_grades(conn: &mut PgConnection, expression: &str) -> QueryResult<Vec<String>> {
+    let Ok(requirement) = Requirement::parse(expression) else {
+        return Ok(Vec::new());
+    };
+    let grades = requirement.min_grades();
+    if grades.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let scale = GradeScaleDetail::load_default(conn)?;
+    Ok(grades
+        .into_iter()
+        .filter(|grade| {
+            let entry = scale.as_ref().and_then(|scale| scale.entry(grade));
+            entry.and_then(|entry| entry.points).is_none()
+        })
+        .map(str::to_string)
+        .collect())
+}
+
+/// Evaluates a course's requirements for a student registering in `term`.
+pub fn evaluate(
+    conn: &mut PgConnection,
+    student_id: i32,
+    course: &Course,
+    term: &Term,
+) -> QueryResult<RequisiteReport> {
+    let coursework = Coursework::load(conn, student_id, term, Utc::now().date_naive())?;
+
+    let prerequisites = course
+        .prerequisites
+        .as_deref()
+        .map(|expression| evaluate_expression(expression, &coursework, Rule::Prerequisite));
+    let corequisites = course
+        .corequisites
+        .as_deref()
+        .map(|expression| evaluate_expression(expression, &coursework, Rule::Corequisite));
+    let satisfied = [&prerequisites, &corequisites]
+        .iter()
+        .all(|result| result.as_ref().is_none_or(|clause| clause.satisfied));
+
+    Ok(RequisiteReport {
+        student_id,
+        course_code: course.code.clone(),
+        term_code: term.code.clone(),
+        satisfied,
+        prerequisites,
+        corequisites,
+    })
+}
+
+/// Fails with the full report when the student does not meet the
+/// requirements of the section's course.
+pub(crate) fn ensure_met(
+    conn: &mut PgConnection,
+    student_id: i32,
+    section: &Section,
+    term: &Term,
+) -> Result<(), RegistrationError> {
+    let course = courses::table
+        .find(section.course_id)
+        .first::<Course>(conn)?;
+    if course.prerequisites.is_none() && course.corequisites.is_none() {
+        return Ok(());
+    }
+
+    let report = evaluate(conn, student_id, &course, term)?;
+    if !report.satisfied {
+        return Err(RegistrationError::RequisitesNotMet(Box::new(report)));
+    }
+    Ok(())
+}
@@ -0,0 +1,91 @@
+//! Room utilization. For each room, how much of the teaching week the
+//! sections booked into it occupy in a term and how many of its seats they
+//! fill.
+
+use diesel::prelude::*;
+use serde::Serialize;
+
+use crate::models::room::{Room, RoomDetail};
+use crate::models::{Section, SectionDetail, Term};
+use crate::schema::{buildings, rooms, sections};
+
+/// Scheduled time is measured against Monday to Friday, 08:00 to 22:00.
+pub const AVAILABLE_MINUTES_PER_WEEK: i32 = 5 * 14 * 60;
+
+#[derive(Debug, Serialize)]
+pub struct RoomUtilization {
+    #[serde(flatten)]
+    pub room: RoomDetail,
+    pub sections: usize,
+    /// Weekly minutes of meetings booked in the room.
+    pub scheduled_minutes: i32,
+    /// `scheduled_minutes` as a percentage of `AVAILABLE_MINUTES_PER_WEEK`.
+    pub time_utilization: f64,
+    /// Students enrolled across the room's sections.
+    pub seats_filled: i32,
+    /// `seats_filled` as a percentage of the room's seats over all of its
+    /// sections.
+    pub seat_utilization: f64,
+}
+
+fn percentage(part: i32, whole: i32) -> f64 {
+    if whole <= 0 {
+        return 0.0;
+    }
+    (part as f64 * 1000.0 / whole as f64).round() / 10.0
+}
+
+/// Every room, or every room in one building, with its use in the term.
+/// Rooms without sections are listed with zero use.
+pub fn report(
+    conn: &mut PgConnection,
+    term: &Term,
+    building_id: Option<i32>,
+) -> QueryResult<Vec<RoomUtilization>> {
+    let mut rooms_query = rooms::table
+        .inner_join(buildings::table)
+        .order((buildings::code.asc(), rooms::room_number.asc()))
+        .select(rooms::all_columns)
+        .into_boxed();
+    if let Some(building_id) = building_id {
+        rooms_query = rooms_query.filter(rooms::building_id.eq(building_id));
+    }
+    let rooms = rooms_query.load::<Room>(conn)?;
+    let rooms = RoomDetail::load(conn, rooms)?;
+
+    let room_ids: Vec<i32> = rooms.iter().map(|detail| detail.room.id).collect();
+    let booked = sections::table
+        .filter(sections::term_id.eq(term.id))
+        .filter(sections::room_id.eq_any(room_ids))
+        .load::<Section>(conn)?;
+    let booked = SectionDetail::load(conn, booked)?;
+
+    Ok(rooms
+        .into_iter()
+        .map(|room| {
+            let in_room: Vec<&SectionDetail> = booked
+                .iter()
+                .filter(|detail| detail.section.room_id == Some(room.room.id))
+                .collect();
+            let scheduled_minutes: i32 = in_room
+                .iter()
+                .flat_map(|detail| &detail.meetings)
+                .map(|meeting| (meeting.end_time - meeting.start_time).num_minutes() as i32)
+                .sum();
+            let seats_filled: i32 = in_room
+                .iter()
+                .map(|detail| detail.section.enrolled_count)
+                .sum();
+            let seats_offered = room.room.capacity * in_room.len() as i32;
+
+            RoomUtilization {
+                sections: in_room.len(),
+                scheduled_minutes,
+                time_utilization: percentage(scheduled_minutes, AVAILABLE_MINUTES_PER_WEEK),
+                seats_filled,
+                seat_utilization: percentage(seats_filled, seats_offered),
+                room,
+            }
+        })
+        .collect())
+}
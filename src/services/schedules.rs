@@ -0,0 +1,314 @@
+//! Schedule generation. Given the courses a student wants in a term, finds
+//! every combination of one section per course whose meetings do not
+//! overlap, honouring hard constraints (earliest start time, days off) and
+//! ranking the results by a simple score.
+
+use std::cmp::Reverse;
+use std::collections::BTreeSet;
+
+use chrono::NaiveTime;
+use diesel::prelude::*;
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::models::section::SectionMeeting;
+use crate::models::{Course, Section, SectionDetail, Term};
+use crate::schema::{courses, sections};
+
+/// Generation stops after this many schedules so a long course list with
+/// many sections cannot run away.
+pub const MAX_SCHEDULES: usize = 1000;
+
+/// Points for each section taught by a preferred instructor.
+const PREFERRED_INSTRUCTOR_POINTS: i32 = 10;
+/// Points for each weekday (Monday to Friday) without classes.
+const FREE_DAY_POINTS: i32 = 5;
+/// One point is lost for every this many minutes spent waiting between
+/// classes on the same day.
+const GAP_MINUTES_PER_POINT: i32 = 30;
+
+#[derive(Debug, Error)]
+pub enum ScheduleError {
+    #[error("Course {0} not found")]
+    CourseNotFound(String),
+
+    #[error("Database error: {0}")]
+    Database(#[from] diesel::result::Error),
+}
+
+#[derive(Debug)]
+pub struct ScheduleConstraints {
+    /// No meeting may start before this time.
+    pub not_before: Option<NaiveTime>,
+    /// ISO weekdays that must stay free.
+    pub days_off: Vec<i16>,
+    /// Instructors (user ids) whose sections score higher.
+    pub preferred_instructors: Vec<i32>,
+    /// Consider sections with no seats left.
+    pub include_full: bool,
+}
+
+/// How many of a course's sections in the term satisfy the constraints.
+#[derive(Debug, Serialize)]
+pub struct CourseOptions {
+    pub course_code: String,
+    pub sections_offered: usize,
+    pub sections_eligible: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Schedule {
+    /// Position in the ranking, starting at 1.
+    pub rank: usize,
+    pub score: i32,
+    pub preferred_instructor_sections: usize,
+    /// Weekdays (1 = Monday to 5 = Friday) without classes.
+    pub free_days: Vec<i16>,
+    pub gap_minutes: i32,
+    pub sections: Vec<SectionDetail>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct GeneratedSchedules {
+    pub courses: Vec<CourseOptions>,
+    /// Whether generation stopped at `MAX_SCHEDULES`.
+    pub truncated: bool,
+    pub schedules: Vec<Schedule>,
+}
+
+fn satisfies(detail: &SectionDetail, constraints: &ScheduleConstraints) -> bool {
+    if detail.section.lottery {
+        return false;
+    }
+    if !constraints.include_full && detail.seats_available == 0 {
+        return false;
+    }
+    detail.meetings.iter().all(|meeting| {
+        !constraints.days_off.contains(&meeting.day_of_week)
+            && constraints
+                .not_before
+                .is_none_or(|not_before| meeting.start_time >= not_before)
+    })
+}
+
+fn conflicts(chosen: &[&SectionDetail], candidate: &SectionDetail) -> bool {
+    chosen.iter().any(|picked| {
+        picked.meetings.iter().any(|meeting| {
+            candidate
+                .meetings
+                .iter()
+                .any(|other| meeting.overlaps(other))
+        })
+    })
+}
+
+fn score(sections: Vec<SectionDetail>, constraints: &ScheduleConstraints) -> Schedule {
+    let preferred_instructor_sections = sections
+        .iter()
+        .filter(|detail| {
+            detail
+                .section
+                .instructor_id
+                .is_some_and(|id| constraints.preferred_instructors.contains(&id))
+        })
+        .count();
+
+    let meetings: Vec<&SectionMeeting> = sections
+        .iter()
+        .flat_map(|detail| &detail.meetings)
+        .collect();
+    let busy_days: BTreeSet<i16> = meetings.iter().map(|meeting| meeting.day_of_week).collect();
+    let free_days: Vec<i16> = (1..=5).filter(|day| !busy_days.contains(day)).collect();
+
+    let mut gap_minutes = 0;
+    for day in &busy_days {
+        let mut day_meetings: Vec<&&SectionMeeting> = meetings
+            .iter()
+            .filter(|meeting| meeting.day_of_week == *day)
+            .collect();
+        day_meetings.sort_by_key(|meeting| meeting.start_time);
+        for pair in day_meetings.windows(2) {
+            gap_minutes += (pair[1].start_time - pair[0].end_time).num_minutes() as i32;
+        }
+    }
+
+    let score = preferred_instructor_sections as i32 * PREFERRED_INSTRUCTOR_POINTS
+        + free_days.len() as i32 * FREE_DAY_POINTS
+        - gap_minutes / GAP_MINUTES_PER_POINT;
+
+    Schedule {
+        rank: 0,
+        score,
+        preferred_instructor_sections,
+        free_days,
+        gap_minutes,
+        sections,
+    }
+}
+
+/// Depth-first search over the courses, one section each, skipping any
+/// section that overlaps one already picked.
+fn search<'a>(
+    options: &'a [Vec<SectionDetail>],
+    chosen: &mut Vec<&'a SectionDetail>,
+    found: &mut Vec<Vec<SectionDetail>>,
+) {
+    if found.len() >= MAX_SCHEDULES {
+        return;
+    }
+    let Some(candidates) = options.get(chosen.len()) else {
+        found.push(chosen.iter().map(|detail| (*detail).clone()).collect());
+        return;
+    };
+    for candidate in candidates {
+        if conflicts(chosen, candidate) {
+            continue;
+        }
+        chosen.push(candidate);
+        search(options, chosen, found);
+        chosen.pop();
+    }
+}
+
+/// Every conflict-free schedule for the courses in the term, best score
+/// first. Ties keep the order of the course list and section ids.
+pub fn generate(
+    conn: &mut PgConnection,
+    term: &Term,
+    course_codes: &[String],
+    constraints: &ScheduleConstraints,
+) -> Result<GeneratedSchedules, ScheduleError> {
+    let mut course_summaries = Vec::with_capacity(course_codes.len());
+    let mut options = Vec::with_capacity(course_codes.len());
+
+    for code in course_codes {
+        let course = courses::table
+            .filter(courses::code.eq(code))
+            .first::<Course>(conn)
+            .optional()?
+            .ok_or_else(|| ScheduleError::CourseNotFound(code.clone()))?;
+        let offered = sections::table
+            .filter(sections::term_id.eq(term.id))
+            .filter(sections::course_id.eq(course.id))
+            .order(sections::id.asc())
+            .load::<Section>(conn)?;
+        let sections_offered = offered.len();
+        let eligible: Vec<SectionDetail> = SectionDetail::load(conn, offered)?
+            .into_iter()
+            .filter(|detail| satisfies(detail, constraints))
+            .collect();
+
+        course_summaries.push(CourseOptions {
+            course_code: course.code,
+            sections_offered,
+            sections_eligible: eligible.len(),
+        });
+        options.push(eligible);
+    }
+
+    let mut found = Vec::new();
+    search(&options, &mut Vec::new(), &mut found);
+    let truncated = found.len() >= MAX_SCHEDULES;
+
+    let mut schedules: Vec<Schedule> = found
+        .into_iter()
+        .map(|sections| score(sections, constraints))
+        .collect();
+    // Stable, so equal scores stay in search order.
+    schedules.sort_by_key(|schedule| Reverse(schedule.score));
+    for (index, schedule) in schedules.iter_mut().enumerate() {
+        schedule.rank = index + 1;
+    }
+
+    Ok(GeneratedSchedules {
+        courses: course_summaries,
+        truncated,
+        schedules,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn section(id: i32, course_id: i32, day_of_week: i16, start: &str, end: &str) -> SectionDetail {
+        SectionDetail {
+            section: Section {
+                id,
+                term_id: 1,
+                course_id,
+                section_number: format!("{:03}", id),
+                capacity: 30,
+                instructor_id: None,
+                room: None,
+                created_at: Utc::now(),
+                updated_at: Utc::now(),
+                enrolled_count: 0,
+                reserved_count: 0,
+                lottery: false,
+                room_id: None,
+            },
+            seats_available: 30,
+            course_code: format!("C{}", course_id),
+            meetings: vec![SectionMeeting {
+                id,
+                section_id: id,
+                day_of_week,
+                start_time: NaiveTime::parse_from_str(start, "%H:%M").unwrap(),
+                end_time: NaiveTime::parse_from_str(end, "%H:%M").unwrap(),
+            }],
+        }
+    }
+
+    /// `sections_per_course` sections of each course, every course meeting
+    /// on its own day so no combination conflicts.
+    fn options(courses: i32, sections_per_course: i32) -> Vec<Vec<SectionDetail>> {
+        (0..courses)
+            .map(|course| {
+                (0..sections_per_course)
+                    .map(|n| {
+                        let id = course * sections_per_course + n + 1;
+                        section(id, course + 1, (course % 5 + 1) as i16, "09:00", "10:00")
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn run(options: &[Vec<SectionDetail>]) -> Vec<Vec<SectionDetail>> {
+        let mut found = Vec::new();
+        search(options, &mut Vec::new(), &mut found);
+        found
+    }
+
+    #[test]
+    fn every_combination_is_found_below_the_limit() {
+        let found = run(&options(3, 4));
+        assert_eq!(found.len(), 64);
+        assert!(found.iter().all(|schedule| schedule.len() == 3));
+    }
+
+    #[test]
+    fn the_search_stops_at_the_limit() {
+        // 10^4 combinations, far more than the limit.
+        let found = run(&options(4, 10));
+        assert_eq!(found.len(), MAX_SCHEDULES);
+        assert!(found.iter().all(|schedule| schedule.len() == 4));
+    }
+
+    #[test]
+    fn overlapping_sections_are_never_combined() {
+        let options = vec![
+            vec![
+                section(1, 1, 1, "09:00", "10:30"),
+                section(2, 1, 2, "09:00", "10:30"),
+            ],
+            vec![section(3, 2, 1, "10:00", "11:00")],
+        ];
+        let found = run(&options);
+        assert_eq!(found.len(), 1);
+        let ids: Vec<i32> = found[0].iter().map(|detail| detail.section.id).collect();
+        assert_eq!(ids, vec![2, 3]);
+    }
+}
@@ -0,0 +1,59 @@
+//! Weekly timetable checks against the sections a student already holds in a
+//! term.
+
+use diesel::prelude::*;
+use serde::Serialize;
+
+use crate::models::section::SectionMeeting;
+use crate::models::{Enrollment, Section, SectionDetail};
+use crate::schema::{enrollments, sections};
+
+/// An enrolled section that meets at the same time as the requested one.
+#[derive(Debug, Serialize)]
+pub struct ScheduleConflict {
+    pub section_id: i32,
+    pub course_code: String,
+    pub section_number: String,
+    /// The enrolled section's meetings that overlap the requested section.
+    pub meetings: Vec<SectionMeeting>,
+}
+
+/// Sections the student is enrolled in during the same term whose meetings
+/// overlap `section`'s.
+pub fn find_conflicts(
+    conn: &mut PgConnection,
+    student_id: i32,
+    section: &Section,
+) -> QueryResult<Vec<ScheduleConflict>> {
+    let requested = SectionMeeting::belonging_to(section).load::<SectionMeeting>(conn)?;
+    if requested.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let enrolled = enrollments::table
+        .inner_join(sections::table)
+        .filter(enrollments::student_id.eq(student_id))
+        .filter(enrollments::status.eq(Enrollment::ENROLLED))
+        .filter(sections::term_id.eq(section.term_id))
+        .filter(sections::id.ne(section.id))
+        .select(sections::all_columns)
+        .load::<Section>(conn)?;
+
+    Ok(SectionDetail::load(conn, enrolled)?
+        .into_iter()
+        .filter_map(|detail| {
+            let meetings: Vec<SectionMeeting> = detail
+                .meetings
+                .into_iter()
+                .filter(|meeting| requested.iter().any(|wanted| wanted.overlaps(meeting)))
+                .collect();
+
+            (!meetings.is_empty()).then_some(ScheduleConflict {
+                section_id: detail.section.id,
+                course_code: detail.course_code,
+                section_number: detail.section.section_number,
+                meetings,
+            })
+        })
+        .collect())
+}
@@ -0,0 +1,420 @@
+//! Access and refresh tokens. Access tokens are short-lived JWTs; refresh
+//! tokens are opaque, stored only as a hash, and single use. Each login opens
+//! a session, and each refresh rotates the session's refresh token, so a token
+//! presented a second time means it has leaked and the whole session is
+//! revoked. Access tokens from revoked sessions are denied by their `jti`
+//! until they expire.
+
+use chrono::{DateTime, Duration, Utc};
+use diesel::prelude::*;
+use diesel::result::Error as DieselError;
+use jsonwebtoken::errors::Error as JwtError;
+use std::collections::HashMap;
+use std::env;
+use std::sync::{Arc, RwLock};
+use std::time::Duration as StdDuration;
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::config::AuthConfig;
+use crate::models::role::Role;
+use crate::models::user::{NewUserSession, NewUserToken, User, UserSession, UserToken};
+use crate::schema::{roles, user_sessions, user_tokens, users};
+use crate::DbPool;
+
+#[derive(Debug, Error)]
+pub enum TokenError {
+    #[error("Invalid or expired refresh token")]
+    InvalidRefreshToken,
+
+    #[error("Refresh token has already been used")]
+    RefreshTokenReused { user_id: i32, session_id: i32 },
+
+    #[error("Token generation failed: {0}")]
+    Jwt(#[from] JwtError),
+
+    #[error("Database error: {0}")]
+    Database(#[from] DieselError),
+}
+
+/// An access token and the refresh token to renew it with.
+#[derive(Debug)]
+pub struct IssuedTokens {
+    pub access_token: String,
+    pub refresh_token: String,
+    /// Lifetime of the access token in seconds.
+    pub expires_in: u64,
+}
+
+/// Where a sign-in came from, recorded on its session.
+#[derive(Debug, Clone, Default)]
+pub struct ClientInfo {
+    pub user_agent: Option<String>,
+    pub ip_address: Option<String>,
+}
+
+/// Issues tokens for a fresh login, opening a new session.
+pub fn issue(
+    conn: &mut PgConnection,
+    config: &AuthConfig,
+    user: &User,
+    role_name: &str,
+    client: ClientInfo,
+) -> Result<IssuedTokens, TokenError> {
+    let session = diesel::insert_into(user_sessions::table)
+        .values(&NewUserSession {
+            user_id: user.id,
+            user_agent: client.user_agent,
+            ip_address: client.ip_address,
+            expires_at: refresh_expiry(config),
+        })
+        .get_result::<UserSession>(conn)?;
+
+    issue_in_session(conn, config, user, role_name, &session)
+}
+
+fn refresh_expiry(config: &AuthConfig) -> DateTime<Utc> {
+    Utc::now() + Duration::seconds(config.refresh_token_expiration as i64)
+}
+
+fn issue_in_session(
+    conn: &mut PgConnection,
+    config: &AuthConfig,
+    user: &User,
+    role_name: &str,
+    session: &UserSession,
+) -> Result<IssuedTokens, TokenError> {
+    let jti = Uuid::new_v4().to_string();
+    let access_expires_at = Utc::now() + Duration::seconds(config.token_expiration as i64);
+    let access_token = user.generate_token(
+        &config.secret_key,
+        role_name,
+        session.id,
+        &jti,
+        access_expires_at.timestamp() as usize,
+    )?;
+
+    let refresh_token = UserToken::generate();
+    diesel::insert_into(user_tokens::table)
+        .values(&NewUserToken {
+            user_id: user.id,
+            token_hash: UserToken::hash(&refresh_token),
+            expires_at: session.expires_at,
+            session_id: session.id,
+            access_jti: Some(jti),
+            access_expires_at: Some(access_expires_at),
+        })
+        .execute(conn)?;
+
+    Ok(IssuedTokens {
+        access_token,
+        refresh_token,
+        expires_in: config.token_expiration,
+    })
+}
+
+/// Exchanges a refresh token for a new access token and refresh token in the
+/// same session. Run it in a transaction. A token that was already rotated is
+/// reported as `RefreshTokenReused`; the caller then revokes its session with
+/// `revoke_sessions` outside the rolled-back transaction.
+pub fn rotate(
+    conn: &mut PgConnection,
+    config: &AuthConfig,
+    refresh_token: &str,
+    client: ClientInfo,
+) -> Result<(User, Role, IssuedTokens), TokenError> {
+    let now = Utc::now();
+    let token = user_tokens::table
+        .filter(user_tokens::token_hash.eq(UserToken::hash(refresh_token)))
+        .first::<UserToken>(conn)
+        .optional()?
+        .ok_or(TokenError::InvalidRefreshToken)?;
+
+    // Lock the session first, as revoking does, so a refresh cannot slip in
+    // while the session is being revoked
+    let session = user_sessions::table
+        .find(token.session_id)
+        .for_update()
+        .first::<UserSession>(conn)?;
+    let token = user_tokens::table
+        .find(token.id)
+        .for_update()
+        .first::<UserToken>(conn)?;
+
+    check_usable(&token, &session, now)?;
+
+    let user = users::table
+        .find(token.user_id)
+        .filter(users::is_active.eq(true))
+        .first::<User>(conn)
+        .optional()?
+        .ok_or(TokenError::InvalidRefreshToken)?;
+    let role = roles::table.find(user.role_id).first::<Role>(conn)?;
+
+    diesel::update(user_tokens::table.find(token.id))
+        .set(user_tokens::rotated_at.eq(now))
+        .execute(conn)?;
+    let session = diesel::update(user_sessions::table.find(session.id))
+        .set((
+            user_sessions::last_used_at.eq(now),
+            user_sessions::ip_address.eq(client.ip_address.or(session.ip_address)),
+            user_sessions::expires_at.eq(refresh_expiry(config)),
+        ))
+        .get_result::<UserSession>(conn)?;
+
+    let issued = issue_in_session(conn, config, &user, &role.name, &session)?;
+    Ok((user, role, issued))
+}
+
+/// Whether a refresh token may be exchanged. Revocation wins over reuse: a
+/// spent token from a session that is already revoked needs no second
+/// revocation.
+fn check_usable(
+    token: &UserToken,
+    session: &UserSession,
+    now: DateTime<Utc>,
+) -> Result<(), TokenError> {
+    if token.revoked_at.is_some() || session.revoked_at.is_some() {
+        return Err(TokenError::InvalidRefreshToken);
+    }
+    if token.is_spent() {
+        return Err(TokenError::RefreshTokenReused {
+            user_id: token.user_id,
+            session_id: session.id,
+        });
+    }
+    if token.expires_at <= now {
+        return Err(TokenError::InvalidRefreshToken);
+    }
+    Ok(())
+}
+
+/// Sessions still in use: neither revoked nor expired. Most recently used
+/// first.
+pub fn active_sessions() -> user_sessions::BoxedQuery<'static, diesel::pg::Pg> {
+    user_sessions::table
+        .filter(user_sessions::revoked_at.is_null())
+        .filter(user_sessions::expires_at.gt(Utc::now()))
+        .order(user_sessions::last_used_at.desc())
+        .into_boxed()
+}
+
+/// Access tokens denied by a revocation, to add to the `TokenDenylist` once
+/// the revocation has been committed.
+#[derive(Debug, Default)]
+pub struct RevokedAccessTokens(Vec<(String, DateTime<Utc>)>);
+
+/// Revokes the sessions, their refresh tokens and the unexpired access tokens
+/// issued from them. Returns the ids of the sessions that were still active
+/// and the denied access tokens.
+pub fn revoke_sessions(
+    conn: &mut PgConnection,
+    session_ids: &[i32],
+) -> QueryResult<(Vec<i32>, RevokedAccessTokens)> {
+    let now = Utc::now();
+    let revoked = diesel::update(
+        user_sessions::table
+            .filter(user_sessions::id.eq_any(session_ids))
+            .filter(user_sessions::revoked_at.is_null()),
+    )
+    .set(user_sessions::revoked_at.eq(now))
+    .returning(user_sessions::id)
+    .get_results::<i32>(conn)?;
+
+    diesel::update(
+        user_tokens::table
+            .filter(user_tokens::session_id.eq_any(session_ids))
+            .filter(user_tokens::revoked_at.is_null()),
+    )
+    .set(user_tokens::revoked_at.eq(now))
+    .execute(conn)?;
+
+    let denied = user_tokens::table
+        .filter(user_tokens::session_id.eq_any(session_ids))
+        .filter(user_tokens::access_expires_at.gt(now))
+        .select((user_tokens::access_jti, user_tokens::access_expires_at))
+        .load::<(Option<String>, Option<DateTime<Utc>>)>(conn)?;
+
+    Ok((
+        revoked,
+        RevokedAccessTokens(
+            denied
+                .into_iter()
+                .filter_map(|(jti, expires_at)| Some((jti?, expires_at?)))
+                .collect(),
+        ),
+    ))
+}
+
+/// The `jti`s of revoked access tokens that have not expired yet, held in
+/// memory so `JwtAuth` can check every request without a query. Revocations
+/// made by this process are added as they happen; `run_denylist_sync`
+/// reloads the list from the database to pick up the rest and drop expired
+/// entries.
+#[derive(Debug, Clone, Default)]
+pub struct TokenDenylist {
+    entries: Arc<RwLock<HashMap<String, DateTime<Utc>>>>,
+}
+
+impl TokenDenylist {
+    pub fn load(conn: &mut PgConnection) -> QueryResult<Self> {
+        let denylist = TokenDenylist::default();
+        denylist.reload(conn)?;
+        Ok(denylist)
+    }
+
+    pub fn contains(&self, jti: &str) -> bool {
+        self.entries
+            .read()
+            .expect("denylist lock poisoned")
+            .contains_key(jti)
+    }
+
+    pub fn extend(&self, revoked: RevokedAccessTokens) {
+        self.entries
+            .write()
+            .expect("denylist lock poisoned")
+            .extend(revoked.0);
+    }
+
+    fn reload(&self, conn: &mut PgConnection) -> QueryResult<()> {
+        let now = Utc::now();
+        let rows = user_tokens::table
+            .inner_join(user_sessions::table)
+            .filter(user_sessions::revoked_at.is_not_null())
+            .filter(user_tokens::access_expires_at.gt(now))
+            .select((user_tokens::access_jti, user_tokens::access_expires_at))
+            .load::<(Option<String>, Option<DateTime<Utc>>)>(conn)?;
+
+        // Revocations are permanent, so merge rather than replace and only
+        // forget tokens that have expired
+        let mut entries = self.entries.write().expect("denylist lock poisoned");
+        entries.retain(|_, expires_at| *expires_at > now);
+        entries.extend(
+            rows.into_iter()
+                .filter_map(|(jti, expires_at)| Some((jti?, expires_at?))),
+        );
+        Ok(())
+    }
+}
+
+fn denylist_sync_interval() -> StdDuration {
+    let secs = env::var("TOKEN_DENYLIST_SYNC_SECS")
+        .ok()
+        .and_then(|value| value.parse::<u64>().ok())
+        .unwrap_or(30);
+    StdDuration::from_secs(secs)
+}
+
+/// Periodically reloads the denylist so revocations made by other instances
+/// take effect here and expired tokens are forgotten.
+pub async fn run_denylist_sync(pool: DbPool, denylist: TokenDenylist) {
+    let mut interval = actix_rt::time::interval(denylist_sync_interval());
+
+    loop {
+        interval.tick().await;
+
+        let pool = pool.clone();
+        let denylist = denylist.clone();
+        let result = tokio::task::spawn_blocking(move || {
+            let mut conn = pool.get().map_err(|e| e.to_string())?;
+            denylist.reload(&mut conn).map_err(|e| e.to_string())
+        })
+        .await;
+
+        match result {
+            Ok(Ok(_)) => {}
+            Ok(Err(e)) => log::error!("Token denylist sync failed: {}", e),
+            Err(e) => log::error!("Token denylist sync task panicked: {:?}", e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session() -> UserSession {
+        let now = Utc::now();
+        UserSession {
+            id: 7,
+            user_id: 3,
+            user_agent: None,
+            ip_address: None,
+            created_at: now,
+            last_used_at: now,
+            expires_at: now + Duration::days(7),
+            revoked_at: None,
+        }
+    }
+
+    fn token() -> UserToken {
+        let now = Utc::now();
+        UserToken {
+            id: 1,
+            user_id: 3,
+            token_hash: UserToken::hash("refresh"),
+            expires_at: now + Duration::days(7),
+            created_at: now,
+            rotated_at: None,
+            revoked_at: None,
+            session_id: 7,
+            access_jti: None,
+            access_expires_at: None,
+        }
+    }
+
+    #[test]
+    fn a_fresh_token_is_usable() {
+        assert!(check_usable(&token(), &session(), Utc::now()).is_ok());
+    }
+
+    #[test]
+    fn a_rotated_token_is_reported_as_reused() {
+        let token = UserToken {
+            rotated_at: Some(Utc::now()),
+            ..token()
+        };
+        assert!(matches!(
+            check_usable(&token, &session(), Utc::now()),
+            Err(TokenError::RefreshTokenReused {
+                user_id: 3,
+                session_id: 7
+            })
+        ));
+    }
+
+    #[test]
+    fn a_rotated_token_of_a_revoked_session_is_only_invalid() {
+        let token = UserToken {
+            rotated_at: Some(Utc::now()),
+            ..token()
+        };
+        let session = UserSession {
+            revoked_at: Some(Utc::now()),
+            ..session()
+        };
+        assert!(matches!(
+            check_usable(&token, &session, Utc::now()),
+            Err(TokenError::InvalidRefreshToken)
+        ));
+    }
+
+    #[test]
+    fn revoked_and_expired_tokens_are_invalid() {
+        let revoked = UserToken {
+            revoked_at: Some(Utc::now()),
+            ..token()
+        };
+        assert!(matches!(
+            check_usable(&revoked, &session(), Utc::now()),
+            Err(TokenError::InvalidRefreshToken)
+        ));
+
+        let expired = token();
+        assert!(matches!(
+            check_usable(&expired, &session(), expired.expires_at),
+            Err(TokenError::InvalidRefreshToken)
+        ));
+    }
+}
@@ -0,0 +1,567 @@
+//! Official transcripts. A transcript lists every term a student enrolled in
+//! with the courses, credits and grades on record, and is returned either as
+//! data or as a PDF rendered here without any external service. Each issuance
+//! is written to the audit log; none is made while a hold blocks transcripts.
+
+use chrono::{DateTime, NaiveDate, Utc};
+use diesel::prelude::*;
+use diesel::result::Error as DieselError;
+use pdf_writer::{Content, Finish, Name, Pdf, Rect, Ref, Str, TextStr};
+use serde::Serialize;
+use serde_json::json;
+use thiserror::Error;
+
+use crate::models::program::Program;
+use crate::models::{AuditContext, Course, DeclaredProgram, Enrollment, Hold, HoldAction, Student};
+use crate::schema::{audit_logs, courses, enrollments, sections, students, terms};
+use crate::services::grades::GpaTotals;
+
+#[derive(Debug, Error)]
+pub enum TranscriptError {
+    #[error("Student has active holds that block transcripts")]
+    OnHold(Vec<Hold>),
+
+    #[error("Database error: {0}")]
+    Database(#[from] DieselError),
+}
+
+#[derive(Debug, Serialize)]
+pub struct TranscriptStudent {
+    pub id: i32,
+    pub name: String,
+    pub email: String,
+}
+
+/// The program of study the student is registered under.
+#[derive(Debug, Serialize)]
+pub struct TranscriptProgram {
+    pub code: String,
+    pub title: String,
+    pub department: String,
+}
+
+/// A major or minor the student has declared.
+#[derive(Debug, Serialize)]
+pub struct TranscriptDegree {
+    pub code: String,
+    pub name: String,
+    pub kind: String,
+    pub declared_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TranscriptCourse {
+    pub course_code: String,
+    pub course_title: String,
+    pub section_number: String,
+    pub credits: i32,
+    pub status: String,
+    pub grade: Option<String>,
+    pub grade_points: Option<f64>,
+    pub credit_earned: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TranscriptTerm {
+    pub term_code: String,
+    pub term_name: String,
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    pub courses: Vec<TranscriptCourse>,
+    #[serde(flatten)]
+    pub totals: GpaTotals,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Transcript {
+    pub student: TranscriptStudent,
+    pub program: Option<TranscriptProgram>,
+    pub degrees: Vec<TranscriptDegree>,
+    pub terms: Vec<TranscriptTerm>,
+    pub cumulative: GpaTotals,
+    pub issued_at: DateTime<Utc>,
+    pub issued_by: Option<i32>,
+}
+
+type CourseRow = (
+    (i32, String, String, NaiveDate, NaiveDate),
+    (String, String, i32),
+    String,
+    (String, Option<String>, Option<f64>, bool),
+);
+
+/// Assembles a student's transcript. Dropped enrollments are left off;
+/// withdrawals stay on the record without a grade.
+pub fn build(
+    conn: &mut PgConnection,
+    student_id: i32,
+    audit: &AuditContext,
+) -> QueryResult<Transcript> {
+    let student = students::table.find(student_id).first::<Student>(conn)?;
+    let program = courses::table
+        .find(student.course_id)
+        .first::<Course>(conn)
+        .optional()?
+        .map(|course| TranscriptProgram {
+            code: course.code,
+            title: course.title,
+            department: course.department,
+        });
+    let degrees = DeclaredProgram::load_for_student(conn, student_id)?
+        .into_iter()
+        .map(|declared| TranscriptDegree {
+            code: declared.program.code,
+            name: declared.program.name,
+            kind: declared.program.kind,
+            declared_at: declared.declaration.declared_at,
+        })
+        .collect();
+
+    let rows = enrollments::table
+        .inner_join(
+            sections::table
+                .inner_join(terms::table)
+                .inner_join(courses::table),
+        )
+        .filter(enrollments::student_id.eq(student_id))
+        .filter(enrollments::status.eq_any([Enrollment::ENROLLED, Enrollment::WITHDRAWN]))
+        .order((
+            terms::start_date.asc(),
+            terms::id.asc(),
+            courses::code.asc(),
+        ))
+        .select((
+            (
+                terms::id,
+                terms::code,
+                terms::name,
+                terms::start_date,
+                terms::end_date,
+            ),
+            (courses::code, courses::title, courses::credits),
+            sections::section_number,
+            (
+                enrollments::status,
+                enrollments::grade,
+                enrollments::grade_points,
+                enrollments::credit_earned,
+            ),
+        ))
+        .load::<CourseRow>(conn)?;
+
+    let mut cumulative = GpaTotals::default();
+    let mut term_ids: Vec<i32> = Vec::new();
+    let mut transcript_terms: Vec<TranscriptTerm> = Vec::new();
+    for (term, course, section_number, enrollment) in rows {
+        let (term_id, term_code, term_name, start_date, end_date) = term;
+        let (course_code, course_title, credits) = course;
+        let (status, grade, grade_points, credit_earned) = enrollment;
+
+        if term_ids.last() != Some(&term_id) {
+            term_ids.push(term_id);
+            transcript_terms.push(TranscriptTerm {
+                term_code,
+                term_name,
+                start_date,
+                end_date,
+                courses: Vec::new(),
+                totals: GpaTotals::default(),
+            });
+        }
+        let current = transcript_terms
+            .last_mut()
+            .expect("a term was pushed above");
+
+        if status == Enrollment::ENROLLED && grade.is_some() {
+            current.totals.add(credits, grade_points, credit_earned);
+            cumulative.add(credits, grade_points, credit_earned);
+        }
+        current.courses.push(TranscriptCourse {
+            course_code,
+            course_title,
+            section_number,
+            credits,
+            status,
+            grade,
+            grade_points,
+            credit_earned,
+        });
+    }
+
+    Ok(Transcript {
+        student: TranscriptStudent {
+            id: student.id,
+            name: student.name,
+            email: student.email,
+        },
+        program,
+        degrees,
+        terms: transcript_terms
+            .into_iter()
+            .map(|term| TranscriptTerm {
+                totals: term.totals.finish(),
+                ..term
+            })
+            .collect(),
+        cumulative: cumulative.finish(),
+        issued_at: Utc::now(),
+        issued_by: audit.user_id,
+    })
+}
+
+/// Builds a transcript and records its issuance against the student.
+pub fn issue(
+    conn: &mut PgConnection,
+    student_id: i32,
+    format: &str,
+    audit: &AuditContext,
+) -> Result<Transcript, TranscriptError> {
+    let transcript = build(conn, student_id, audit)?;
+
+    let holds = Hold::active_for(
+        conn,
+        student_id,
+        HoldAction::Transcript,
+        Utc::now().date_naive(),
+    )?;
+    if !holds.is_empty() {
+        return Err(TranscriptError::OnHold(holds));
+    }
+
+    diesel::insert_into(audit_logs::table)
+        .values(&audit.activity(
+            "transcript.issue",
+            "student",
+            Some(student_id),
+            Some(json!({
+                "format": format,
+                "issued_at": transcript.issued_at,
+                "terms": transcript.terms.len(),
+                "gpa": transcript.cumulative.gpa,
+                "credits_earned": transcript.cumulative.credits_earned,
+            })),
+        ))
+        .execute(conn)?;
+
+    Ok(transcript)
+}
+
+const PAGE_WIDTH: f32 = 595.0;
+const PAGE_HEIGHT: f32 = 842.0;
+const MARGIN: f32 = 50.0;
+const BODY_SIZE: f32 = 10.0;
+
+/// Column offsets for course rows: code, title, section, credits, grade,
+/// points.
+const COLUMNS: [f32; 6] = [0.0, 80.0, 310.0, 360.0, 410.0, 455.0];
+
+struct Text {
+    x: f32,
+    y: f32,
+    size: f32,
+    bold: bool,
+    text: String,
+}
+
+#[derive(Default)]
+struct Page {
+    texts: Vec<Text>,
+    rules: Vec<f32>,
+}
+
+/// Top-down text layout that starts a new page when the current one is full.
+struct Layout {
+    pages: Vec<Page>,
+    y: f32,
+}
+
+impl Layout {
+    fn new() -> Layout {
+        Layout {
+            pages: vec![Page::default()],
+            y: PAGE_HEIGHT - MARGIN,
+        }
+    }
+
+    /// Moves down by `height`, breaking the page first when `height` plus
+    /// `keep` (space the caller needs for what follows) would not fit.
+    fn advance(&mut self, height: f32, keep: f32) -> f32 {
+        if self.y - height - keep < MARGIN + 20.0 {
+            self.pages.push(Page::default());
+            self.y = PAGE_HEIGHT - MARGIN;
+        }
+        self.y -= height;
+        self.y
+    }
+
+    fn page(&mut self) -> &mut Page {
+        self.pages.last_mut().expect("layout always has a page")
+    }
+
+    fn text(&mut self, x: f32, size: f32, bold: bool, text: impl Into<String>) {
+        let y = self.y;
+        self.page().texts.push(Text {
+            x: MARGIN + x,
+            y,
+            size,
+            bold,
+            text: text.into(),
+        });
+    }
+
+    fn line(&mut self, size: f32, bold: bool, text: impl Into<String>) {
+        self.advance(size * 1.5, 0.0);
+        self.text(0.0, size, bold, text);
+    }
+
+    fn rule(&mut self) {
+        self.advance(4.0, 0.0);
+        let y = self.y;
+        self.page().rules.push(y);
+    }
+
+    fn row(&mut self, bold: bool, cells: [&str; 6]) {
+        self.advance(BODY_SIZE * 1.4, 0.0);
+        for (x, cell) in COLUMNS.iter().zip(cells) {
+            self.text(*x, BODY_SIZE, bold, cell);
+        }
+    }
+
+    fn gap(&mut self, height: f32) {
+        self.y -= height;
+    }
+}
+
+/// Standard PDF fonts only cover WinAnsi: Latin-1 plus a few punctuation
+/// marks. Anything else is replaced.
+fn encode(text: &str) -> Vec<u8> {
+    text.chars()
+        .map(|c| match c {
+            '\u{2013}' => 0x96,
+            '\u{2014}' => 0x97,
+            '\u{2018}' => 0x91,
+            '\u{2019}' => 0x92,
+            '\u{201c}' => 0x93,
+            '\u{201d}' => 0x94,
+            '\u{2022}' => 0x95,
+            '\u{2026}' => 0x85,
+            c => match u32::from(c) {
+                code @ (0x20..=0x7e | 0xa0..=0xff) => code as u8,
+                _ => b'?',
+            },
+        })
+        .collect()
+}
+
+fn truncate(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        return text.to_string();
+    }
+    let mut truncated: String = text.chars().take(max_chars - 3).collect();
+    truncated.push_str("...");
+    truncated
+}
+
+fn format_totals(totals: &GpaTotals) -> String {
+    let gpa = totals
+        .gpa
+        .map_or_else(|| "-".to_string(), |gpa| format!("{:.2}", gpa));
+    format!(
+        "GPA {}    GPA credits {}    Quality points {:.2}    Credits earned {}",
+        gpa, totals.gpa_credits, totals.quality_points, totals.credits_earned
+    )
+}
+
+fn layout(transcript: &Transcript) -> Layout {
+    let mut layout = Layout::new();
+
+    layout.line(16.0, true, "Official Academic Transcript");
+    layout.gap(6.0);
+    layout.line(
+        BODY_SIZE,
+        false,
+        format!(
+            "Student: {} (ID {})",
+            transcript.student.name, transcript.student.id
+        ),
+    );
+    layout.line(
+        BODY_SIZE,
+        false,
+        format!("Email: {}", transcript.student.email),
+    );
+    if let Some(program) = &transcript.program {
+        layout.line(
+            BODY_SIZE,
+            false,
+            format!(
+                "Program: {} {} ({})",
+                program.code, program.title, program.department
+            ),
+        );
+    }
+    for degree in &transcript.degrees {
+        let label = if degree.kind == Program::MINOR {
+            "Minor"
+        } else {
+            "Major"
+        };
+        layout.line(
+            BODY_SIZE,
+            false,
+            format!("{}: {} {}", label, degree.code, degree.name),
+        );
+    }
+    layout.line(
+        BODY_SIZE,
+        false,
+        format!(
+            "Issued: {}",
+            transcript.issued_at.format("%Y-%m-%d %H:%M UTC")
+        ),
+    );
+    layout.rule();
+
+    if transcript.terms.is_empty() {
+        layout.gap(6.0);
+        layout.line(BODY_SIZE, false, "No coursework on record.");
+    }
+
+    for term in &transcript.terms {
+        // Keep a term heading together with its column headers and first row.
+        layout.advance(18.0, BODY_SIZE * 3.0);
+        layout.text(
+            0.0,
+            12.0,
+            true,
+            format!("{} ({})", term.term_name, term.term_code),
+        );
+        layout.row(
+            true,
+            ["Course", "Title", "Section", "Credits", "Grade", "Points"],
+        );
+        for course in &term.courses {
+            let grade = match (&course.grade, course.status.as_str()) {
+                (Some(grade), _) => grade.clone(),
+                (None, Enrollment::WITHDRAWN) => "W".to_string(),
+                (None, _) => "IP".to_string(),
+            };
+            let points = course
+                .grade_points
+                .map_or_else(String::new, |points| format!("{:.2}", points));
+            layout.row(
+                false,
+                [
+                    &course.course_code,
+                    &truncate(&course.course_title, 40),
+                    &course.section_number,
+                    &course.credits.to_string(),
+                    &grade,
+                    &points,
+                ],
+            );
+        }
+        layout.line(
+            BODY_SIZE,
+            false,
+            format!("Term: {}", format_totals(&term.totals)),
+        );
+        layout.gap(4.0);
+    }
+
+    layout.rule();
+    layout.line(
+        11.0,
+        true,
+        format!("Cumulative: {}", format_totals(&transcript.cumulative)),
+    );
+    layout.line(
+        8.0,
+        false,
+        "W = withdrawn, IP = in progress. Grades without points are not included in GPA.",
+    );
+
+    layout
+}
+
+/// Renders a transcript as an A4 PDF using the built-in Helvetica fonts.
+pub fn render_pdf(transcript: &Transcript) -> Vec<u8> {
+    let layout = layout(transcript);
+    let page_count = layout.pages.len();
+
+    let catalog_id = Ref::new(1);
+    let page_tree_id = Ref::new(2);
+    let regular_id = Ref::new(3);
+    let bold_id = Ref::new(4);
+    let info_id = Ref::new(5);
+    let page_ids: Vec<Ref> = (0..page_count)
+        .map(|i| Ref::new(6 + 2 * i as i32))
+        .collect();
+
+    let mut pdf = Pdf::new();
+    pdf.catalog(catalog_id).pages(page_tree_id);
+    pdf.pages(page_tree_id)
+        .kids(page_ids.iter().copied())
+        .count(page_count as i32);
+    pdf.type1_font(regular_id)
+        .base_font(Name(b"Helvetica"))
+        .encoding_predefined(Name(b"WinAnsiEncoding"));
+    pdf.type1_font(bold_id)
+        .base_font(Name(b"Helvetica-Bold"))
+        .encoding_predefined(Name(b"WinAnsiEncoding"));
+
+    let title = format!("Transcript - {}", transcript.student.name);
+    pdf.document_info(info_id)
+        .title(TextStr(&title))
+        .creator(TextStr("University Registration System"));
+
+    for (index, (page, page_id)) in layout.pages.iter().zip(&page_ids).enumerate() {
+        let content_id = Ref::new(page_id.get() + 1);
+
+        let mut content = Content::new();
+        content.set_line_width(0.5);
+        for y in &page.rules {
+            content.move_to(MARGIN, *y);
+            content.line_to(PAGE_WIDTH - MARGIN, *y);
+            content.stroke();
+        }
+
+        content.begin_text();
+        let footer = Text {
+            x: MARGIN,
+            y: MARGIN - 20.0,
+            size: 8.0,
+            bold: false,
+            text: format!(
+                "Student {}    Page {} of {}",
+                transcript.student.id,
+                index + 1,
+                page_count
+            ),
+        };
+        for text in page.texts.iter().chain(std::iter::once(&footer)) {
+            let font = if text.bold { Name(b"F2") } else { Name(b"F1") };
+            content.set_font(font, text.size);
+            content.set_text_matrix([1.0, 0.0, 0.0, 1.0, text.x, text.y]);
+            content.show(Str(&encode(&text.text)));
+        }
+        content.end_text();
+
+        let mut pdf_page = pdf.page(*page_id);
+        pdf_page
+            .media_box(Rect::new(0.0, 0.0, PAGE_WIDTH, PAGE_HEIGHT))
+            .parent(page_tree_id)
+            .contents(content_id);
+        let mut resources = pdf_page.resources();
+        let mut fonts = resources.fonts();
+        fonts.pair(Name(b"F1"), regular_id);
+        fonts.pair(Name(b"F2"), bold_id);
+        fonts.finish();
+        resources.finish();
+        pdf_page.finish();
+
+        pdf.stream(content_id, &content.finish());
+    }
+
+    pdf.finish()
+}
@@ -0,0 +1,362 @@
+//! Section waitlists. Freed seats are handed to the earliest waiting student:
+//! auto-enroll entries take the seat immediately, the rest get an offer that
+//! reserves the seat for `WAITLIST_CLAIM_HOURS`. A background sweep expires
+//! lapsed offers and moves the seat on to the next student.
+
+use std::env;
+use std::time::Duration as StdDuration;
+
+use chrono::{Duration, Utc};
+use diesel::prelude::*;
+use diesel::result::{DatabaseErrorKind, Error as DieselError};
+use serde_json::json;
+
+use crate::models::{
+    AuditContext, Enrollment, Hold, HoldAction, NewWaitlistEntry, Section, Term, WaitlistEntry,
+};
+use crate::schema::{audit_logs, sections, terms, waitlist_entries};
+use crate::services::registration::{self, RegistrationError};
+use crate::services::{appointments, credit_load, requisites, timetable};
+use crate::DbPool;
+
+/// How long a waitlist offer holds its seat.
+pub fn claim_window() -> Duration {
+    let hours = env::var("WAITLIST_CLAIM_HOURS")
+        .ok()
+        .and_then(|value| value.parse::<i64>().ok())
+        .unwrap_or(24);
+    Duration::hours(hours)
+}
+
+fn sweep_interval() -> StdDuration {
+    let secs = env::var("WAITLIST_SWEEP_INTERVAL_SECS")
+        .ok()
+        .and_then(|value| value.parse::<u64>().ok())
+        .unwrap_or(300);
+    StdDuration::from_secs(secs)
+}
+
+fn write_audit(
+    conn: &mut PgConnection,
+    audit: &AuditContext,
+    action: &str,
+    before: Option<&WaitlistEntry>,
+    after: &WaitlistEntry,
+) -> QueryResult<()> {
+    diesel::insert_into(audit_logs::table)
+        .values(&audit.activity(
+            action,
+            "waitlist_entry",
+            Some(after.id),
+            Some(json!({ "before": before, "after": after })),
+        ))
+        .execute(conn)?;
+    Ok(())
+}
+
+fn set_status(
+    conn: &mut PgConnection,
+    entry: &WaitlistEntry,
+    status: &str,
+) -> QueryResult<WaitlistEntry> {
+    diesel::update(waitlist_entries::table.find(entry.id))
+        .set((
+            waitlist_entries::status.eq(status),
+            waitlist_entries::updated_at.eq(Utc::now()),
+        ))
+        .get_result::<WaitlistEntry>(conn)
+}
+
+fn release_reservation(conn: &mut PgConnection, section_id: i32) -> QueryResult<()> {
+    diesel::update(
+        sections::table
+            .filter(sections::id.eq(section_id))
+            .filter(sections::reserved_count.gt(0)),
+    )
+    .set(sections::reserved_count.eq(sections::reserved_count - 1))
+    .execute(conn)?;
+    Ok(())
+}
+
+/// 1-based place in line among entries still waiting for the section.
+pub fn position(conn: &mut PgConnection, entry: &WaitlistEntry) -> QueryResult<Option<i64>> {
+    if entry.status != WaitlistEntry::WAITING {
+        return Ok(None);
+    }
+
+    let ahead: i64 = waitlist_entries::table
+        .filter(waitlist_entries::section_id.eq(entry.section_id))
+        .filter(waitlist_entries::status.eq(WaitlistEntry::WAITING))
+        .filter(waitlist_entries::id.lt(entry.id))
+        .count()
+        .get_result(conn)?;
+    Ok(Some(ahead + 1))
+}
+
+/// Puts the student at the back of a full section's waitlist.
+pub fn join(
+    conn: &mut PgConnection,
+    student_id: i32,
+    section_id: i32,
+    auto_enroll: bool,
+    audit: &AuditContext,
+) -> Result<WaitlistEntry, RegistrationError> {
+    let student = registration::find_student(conn, student_id)?;
+    registration::ensure_no_holds(conn, student_id)?;
+    let (section, term) = registration::find_section_with_term(conn, section_id)?;
+    if section.lottery {
+        return Err(RegistrationError::LotteryPending);
+    }
+
+    appointments::ensure_open(conn, &student, &term)?;
+    if registration::enrolled_in_course(conn, student_id, &section)? {
+        return Err(RegistrationError::AlreadyEnrolled);
+    }
+    requisites::ensure_met(conn, student_id, &section, &term)?;
+    registration::ensure_no_conflicts(conn, student_id, &section)?;
+    credit_load::ensure_within_max(conn, student_id, &section, &term)?;
+
+    enqueue(conn, student_id, section.id, auto_enroll, audit)
+}
+
+/// Adds an entry at the back of the section's waitlist without any
+/// eligibility checks; callers are expected to have made them.
+pub(crate) fn enqueue(
+    conn: &mut PgConnection,
+    student_id: i32,
+    section_id: i32,
+    auto_enroll: bool,
+    audit: &AuditContext,
+) -> Result<WaitlistEntry, RegistrationError> {
+    let entry = diesel::insert_into(waitlist_entries::table)
+        .values(&NewWaitlistEntry {
+            student_id,
+            section_id,
+            auto_enroll,
+        })
+        .get_result::<WaitlistEntry>(conn)
+        .map_err(|e| match e {
+            DieselError::DatabaseError(DatabaseErrorKind::UniqueViolation, _) => {
+                RegistrationError::AlreadyWaitlisted
+            }
+            other => RegistrationError::Database(other),
+        })?;
+
+    write_audit(conn, audit, "waitlist.join", None, &entry)?;
+    Ok(entry)
+}
+
+/// Hands free seats in the section to waitlisted students, in order, until
+/// either runs out.
+pub fn promote(
+    conn: &mut PgConnection,
+    section_id: i32,
+    audit: &AuditContext,
+) -> Result<(), RegistrationError> {
+    loop {
+        let section = sections::table
+            .find(section_id)
+            .for_update()
+            .first::<Section>(conn)?;
+        if section.seats_available() == 0 {
+            return Ok(());
+        }
+
+        let next = waitlist_entries::table
+            .filter(waitlist_entries::section_id.eq(section_id))
+            .filter(waitlist_entries::status.eq(WaitlistEntry::WAITING))
+            .order(waitlist_entries::id.asc())
+            .for_update()
+            .first::<WaitlistEntry>(conn)
+            .optional()?;
+        let Some(entry) = next else {
+            return Ok(());
+        };
+
+        // The student may have found a seat in another section meanwhile.
+        if registration::enrolled_in_course(conn, entry.student_id, &section)? {
+            let after = set_status(conn, &entry, WaitlistEntry::CANCELLED)?;
+            write_audit(conn, audit, "waitlist.cancel", Some(&entry), &after)?;
+            continue;
+        }
+
+        // A timetable clash, a full credit load or a hold that appeared since
+        // joining turns an automatic enrollment into an ordinary offer the
+        // student can act on.
+        let clashes = !timetable::find_conflicts(conn, entry.student_id, &section)?.is_empty();
+        let held = !Hold::active_for(
+            conn,
+            entry.student_id,
+            HoldAction::Registration,
+            Utc::now().date_naive(),
+        )?
+        .is_empty();
+        let term = terms::table.find(section.term_id).first::<Term>(conn)?;
+        let over_limit =
+            match credit_load::ensure_within_max(conn, entry.student_id, &section, &term) {
+                Ok(()) => false,
+                Err(RegistrationError::CreditLimitExceeded { .. }) => true,
+                Err(err) => return Err(err),
+            };
+
+        if entry.auto_enroll && !clashes && !over_limit && !held {
+            registration::claim_seat(conn, section_id)?;
+            registration::insert_enrollment(conn, entry.student_id, section_id, None, audit)?;
+            let after = set_status(conn, &entry, WaitlistEntry::ENROLLED)?;
+            write_audit(conn, audit, "waitlist.enroll", Some(&entry), &after)?;
+        } else {
+            diesel::update(sections::table.find(section_id))
+                .set(sections::reserved_count.eq(sections::reserved_count + 1))
+                .execute(conn)?;
+
+            let now = Utc::now();
+            let after = diesel::update(waitlist_entries::table.find(entry.id))
+                .set((
+                    waitlist_entries::status.eq(WaitlistEntry::OFFERED),
+                    waitlist_entries::offered_at.eq(Some(now)),
+                    waitlist_entries::offer_expires_at.eq(Some(now + claim_window())),
+                    waitlist_entries::updated_at.eq(now),
+                ))
+                .get_result::<WaitlistEntry>(conn)?;
+            write_audit(conn, audit, "waitlist.offer", Some(&entry), &after)?;
+        }
+    }
+}
+
+fn find_open_entry(
+    conn: &mut PgConnection,
+    student_id: i32,
+    entry_id: i32,
+) -> Result<WaitlistEntry, RegistrationError> {
+    waitlist_entries::table
+        .filter(waitlist_entries::id.eq(entry_id))
+        .filter(waitlist_entries::student_id.eq(student_id))
+        .for_update()
+        .first::<WaitlistEntry>(conn)
+        .optional()?
+        .filter(WaitlistEntry::is_open)
+        .ok_or(RegistrationError::WaitlistEntryNotFound)
+}
+
+/// Converts an open offer into an enrollment using the reserved seat.
+pub fn accept_offer(
+    conn: &mut PgConnection,
+    student_id: i32,
+    entry_id: i32,
+    audit: &AuditContext,
+) -> Result<Enrollment, RegistrationError> {
+    let entry = find_open_entry(conn, student_id, entry_id)?;
+    if entry.status != WaitlistEntry::OFFERED {
+        return Err(RegistrationError::WaitlistEntryNotFound);
+    }
+    if entry
+        .offer_expires_at
+        .is_some_and(|expires| expires < Utc::now())
+    {
+        return Err(RegistrationError::OfferExpired);
+    }
+    registration::ensure_no_holds(conn, student_id)?;
+
+    let (section, term) = registration::find_section_with_term(conn, entry.section_id)?;
+    if registration::enrolled_in_course(conn, student_id, &section)? {
+        return Err(RegistrationError::AlreadyEnrolled);
+    }
+    registration::ensure_no_conflicts(conn, student_id, &section)?;
+    credit_load::ensure_within_max(conn, student_id, &section, &term)?;
+
+    diesel::update(sections::table.find(section.id))
+        .set((
+            sections::reserved_count.eq(sections::reserved_count - 1),
+            sections::enrolled_count.eq(sections::enrolled_count + 1),
+        ))
+        .execute(conn)?;
+    let enrollment = registration::insert_enrollment(conn, student_id, section.id, None, audit)?;
+
+    let after = set_status(conn, &entry, WaitlistEntry::ENROLLED)?;
+    write_audit(conn, audit, "waitlist.accept", Some(&entry), &after)?;
+
+    Ok(enrollment)
+}
+
+/// Takes the student off the waitlist, passing on any seat they were offered.
+pub fn leave(
+    conn: &mut PgConnection,
+    student_id: i32,
+    entry_id: i32,
+    audit: &AuditContext,
+) -> Result<WaitlistEntry, RegistrationError> {
+    let entry = find_open_entry(conn, student_id, entry_id)?;
+    let after = set_status(conn, &entry, WaitlistEntry::CANCELLED)?;
+    write_audit(conn, audit, "waitlist.cancel", Some(&entry), &after)?;
+
+    if entry.status == WaitlistEntry::OFFERED {
+        release_reservation(conn, entry.section_id)?;
+        promote(conn, entry.section_id, audit)?;
+    }
+    Ok(after)
+}
+
+/// Expires lapsed offers and fills any free seats from the waitlists.
+/// Returns how many offers expired.
+pub fn sweep(conn: &mut PgConnection) -> Result<usize, RegistrationError> {
+    let audit = AuditContext::default();
+
+    let expired = conn.transaction::<_, RegistrationError, _>(|conn| {
+        let lapsed = waitlist_entries::table
+            .filter(waitlist_entries::status.eq(WaitlistEntry::OFFERED))
+            .filter(waitlist_entries::offer_expires_at.lt(Utc::now()))
+            .for_update()
+            .skip_locked()
+            .load::<WaitlistEntry>(conn)?;
+
+        for entry in &lapsed {
+            let after = set_status(conn, entry, WaitlistEntry::EXPIRED)?;
+            release_reservation(conn, entry.section_id)?;
+            write_audit(conn, &audit, "waitlist.expire", Some(entry), &after)?;
+        }
+        Ok(lapsed.len())
+    })?;
+
+    // Seats can also free up through expiries above or capacity increases.
+    let section_ids: Vec<i32> = waitlist_entries::table
+        .filter(waitlist_entries::status.eq(WaitlistEntry::WAITING))
+        .select(waitlist_entries::section_id)
+        .distinct()
+        .load(conn)?;
+
+    // One section failing to promote must not hold up the others.
+    for section_id in section_ids {
+        if let Err(e) = conn.transaction(|conn| promote(conn, section_id, &audit)) {
+            log::error!(
+                "Promoting the waitlist of section {} failed: {}",
+                section_id,
+                e
+            );
+        }
+    }
+
+    Ok(expired)
+}
+
+/// Runs [`sweep`] forever on a fixed interval. Spawned once at startup.
+pub async fn run_sweeper(pool: DbPool) {
+    let mut interval = actix_rt::time::interval(sweep_interval());
+
+    loop {
+        interval.tick().await;
+
+        let pool = pool.clone();
+        let result = tokio::task::spawn_blocking(move || {
+            let mut conn = pool.get().map_err(|e| e.to_string())?;
+            sweep(&mut conn).map_err(|e| e.to_string())
+        })
+        .await;
+
+        match result {
+            Ok(Ok(0)) => {}
+            Ok(Ok(expired)) => log::info!("Expired {} waitlist offers", expired),
+            Ok(Err(e)) => log::error!("Waitlist sweep failed: {}", e),
+            Err(e) => log::error!("Waitlist sweep task panicked: {:?}", e),
+        }
+    }
+}